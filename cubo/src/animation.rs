@@ -0,0 +1,516 @@
+use cgmath::{InnerSpace, Matrix4, Point3, Quaternion, Vector3, VectorSpace};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// Nombres de nodo glTF que se interpretan como la cámara del visor
+const GLTF_CAMERA_EYE: &str = "camera_eye";
+const GLTF_CAMERA_TARGET: &str = "camera_target";
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    Step,
+    // Hermite como en glTF: cada keyframe guarda [tangente de entrada, valor, tangente de salida]
+    CubicSpline,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnimationTarget {
+    // Índice del objeto en la escena (en glTF, índice del nodo)
+    Object(usize),
+    CameraEye,
+    CameraTarget,
+}
+
+// Valores de los keyframes. Las rotaciones son cuaterniones [x, y, z, w] como en glTF.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Keyframes {
+    Translation(Vec<[f32; 3]>),
+    Rotation(Vec<[f32; 4]>),
+    Scale(Vec<[f32; 3]>),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Channel {
+    pub target: AnimationTarget,
+    #[serde(default)]
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    pub values: Keyframes,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AnimationClip {
+    pub name: String,
+    pub channels: Vec<Channel>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Transform {
+    pub fn from_translation(translation: Vector3<f32>) -> Self {
+        Self {
+            translation,
+            ..Self::default()
+        }
+    }
+
+    // Mezcla con otra transformación: lerp para traslación y escala, slerp para la rotación
+    pub fn lerp(&self, other: &Transform, s: f32) -> Transform {
+        let [x, y, z, w] = <[f32; 4] as Keyframe>::lerp(
+            [self.rotation.v.x, self.rotation.v.y, self.rotation.v.z, self.rotation.s],
+            [other.rotation.v.x, other.rotation.v.y, other.rotation.v.z, other.rotation.s],
+            s,
+        );
+        Transform {
+            translation: self.translation.lerp(other.translation, s),
+            rotation: Quaternion::new(w, x, y, z),
+            scale: self.scale.lerp(other.scale, s),
+        }
+    }
+
+    pub fn to_matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+// Posición de la cámara que pueden animar los canales CameraEye/CameraTarget
+#[derive(Copy, Clone, Debug)]
+pub struct CameraPose {
+    pub eye: Point3<f32>,
+    pub target: Point3<f32>,
+}
+
+impl AnimationClip {
+    // Carga los clips de un archivo .ron, .json, .gltf o .glb
+    pub fn load(path: &Path) -> Result<Vec<AnimationClip>, Box<dyn std::error::Error>> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        let clips: Vec<AnimationClip> = match extension.as_deref() {
+            Some("ron") => ron::from_str(&std::fs::read_to_string(path)?)?,
+            Some("json") => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            Some("gltf") | Some("glb") => return load_gltf(path),
+            _ => return Err(format!("Formato de animación no soportado: {}", path.display()).into()),
+        };
+        for clip in &clips {
+            clip.validate()?;
+        }
+        Ok(clips)
+    }
+
+    // Error con el primer canal mal formado
+    pub fn validate(&self) -> Result<(), String> {
+        for (index, channel) in self.channels.iter().enumerate() {
+            channel.check().map_err(|e| format!("clip '{}', canal {}: {}", self.name, index, e))?;
+        }
+        Ok(())
+    }
+
+    // Tiempo del último keyframe de todos los canales
+    pub fn duration(&self) -> f32 {
+        self.channels
+            .iter()
+            .filter_map(|c| c.times.last().copied())
+            .fold(0.0, f32::max)
+    }
+
+    // Aplica el clip en `time` sobre las transformaciones de los objetos y la cámara.
+    // Lo que no tiene canal queda como estaba.
+    pub fn apply(&self, time: f32, objects: &mut [Transform], mut camera: Option<&mut CameraPose>) {
+        for channel in self.channels.iter().filter(|c| c.is_valid()) {
+            match (channel.target, &channel.values) {
+                (AnimationTarget::Object(i), values) => {
+                    let Some(transform) = objects.get_mut(i) else {
+                        continue;
+                    };
+                    match values {
+                        Keyframes::Translation(v) => {
+                            transform.translation = channel.sample(v, time).into();
+                        }
+                        Keyframes::Rotation(v) => {
+                            let [x, y, z, w] = channel.sample(v, time);
+                            transform.rotation = Quaternion::new(w, x, y, z);
+                        }
+                        Keyframes::Scale(v) => transform.scale = channel.sample(v, time).into(),
+                    }
+                }
+                (AnimationTarget::CameraEye, Keyframes::Translation(v)) => {
+                    if let Some(camera) = camera.as_deref_mut() {
+                        camera.eye = channel.sample(v, time).into();
+                    }
+                }
+                (AnimationTarget::CameraTarget, Keyframes::Translation(v)) => {
+                    if let Some(camera) = camera.as_deref_mut() {
+                        camera.target = channel.sample(v, time).into();
+                    }
+                }
+                // La cámara solo tiene posiciones
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Channel {
+    // Hace falta al menos un keyframe, tiempos estrictamente crecientes y un valor por
+    // tiempo (tres en cubic spline)
+    fn check(&self) -> Result<(), String> {
+        if self.times.is_empty() {
+            return Err("el canal no tiene keyframes".to_string());
+        }
+        if self.times.iter().any(|t| !t.is_finite()) {
+            return Err("hay tiempos que no son números finitos".to_string());
+        }
+        if let Some(i) = self.times.windows(2).position(|pair| pair[0] >= pair[1]) {
+            return Err(format!(
+                "los tiempos no son estrictamente crecientes ({} seguido de {})",
+                self.times[i],
+                self.times[i + 1]
+            ));
+        }
+        let per_key = if self.interpolation == Interpolation::CubicSpline { 3 } else { 1 };
+        let count = match &self.values {
+            Keyframes::Translation(v) | Keyframes::Scale(v) => v.len(),
+            Keyframes::Rotation(v) => v.len(),
+        };
+        if count != self.times.len() * per_key {
+            return Err(format!("{} valores para {} keyframes", count, self.times.len()));
+        }
+        Ok(())
+    }
+
+    fn is_valid(&self) -> bool {
+        self.check().is_ok()
+    }
+
+    fn sample<T: Keyframe>(&self, values: &[T], time: f32) -> T {
+        let times = &self.times;
+        let cubic = self.interpolation == Interpolation::CubicSpline;
+        // En cubic spline el valor del keyframe i está en values[3i + 1]
+        let value = |i: usize| if cubic { values[i * 3 + 1] } else { values[i] };
+
+        let last = times.len() - 1;
+        if time <= times[0] {
+            return value(0);
+        }
+        if time >= times[last] {
+            return value(last);
+        }
+
+        // Keyframe i tal que times[i] <= time < times[i + 1]
+        let i = times.partition_point(|&t| t <= time) - 1;
+        let dt = times[i + 1] - times[i];
+        let s = (time - times[i]) / dt;
+
+        match self.interpolation {
+            Interpolation::Step => value(i),
+            Interpolation::Linear => T::lerp(value(i), value(i + 1), s),
+            Interpolation::CubicSpline => T::hermite(
+                value(i),
+                values[i * 3 + 2],
+                value(i + 1),
+                values[(i + 1) * 3],
+                s,
+                dt,
+            ),
+        }
+    }
+}
+
+trait Keyframe: Copy {
+    fn lerp(a: Self, b: Self, s: f32) -> Self;
+    // Spline de Hermite entre v0 y v1 con tangentes escaladas por dt
+    fn hermite(v0: Self, out0: Self, v1: Self, in1: Self, s: f32, dt: f32) -> Self;
+}
+
+pub(crate) fn hermite_component(v0: f32, out0: f32, v1: f32, in1: f32, s: f32, dt: f32) -> f32 {
+    let s2 = s * s;
+    let s3 = s2 * s;
+    (2.0 * s3 - 3.0 * s2 + 1.0) * v0
+        + (s3 - 2.0 * s2 + s) * dt * out0
+        + (-2.0 * s3 + 3.0 * s2) * v1
+        + (s3 - s2) * dt * in1
+}
+
+impl Keyframe for [f32; 3] {
+    fn lerp(a: Self, b: Self, s: f32) -> Self {
+        Vector3::from(a).lerp(Vector3::from(b), s).into()
+    }
+
+    fn hermite(v0: Self, out0: Self, v1: Self, in1: Self, s: f32, dt: f32) -> Self {
+        std::array::from_fn(|c| hermite_component(v0[c], out0[c], v1[c], in1[c], s, dt))
+    }
+}
+
+impl Keyframe for [f32; 4] {
+    // Interpolación esférica por el camino más corto
+    fn lerp(a: Self, b: Self, s: f32) -> Self {
+        let a = Quaternion::new(a[3], a[0], a[1], a[2]);
+        let mut b = Quaternion::new(b[3], b[0], b[1], b[2]);
+        if a.dot(b) < 0.0 {
+            b = -b;
+        }
+        let q = a.slerp(b, s).normalize();
+        [q.v.x, q.v.y, q.v.z, q.s]
+    }
+
+    fn hermite(v0: Self, out0: Self, v1: Self, in1: Self, s: f32, dt: f32) -> Self {
+        let q: [f32; 4] = std::array::from_fn(|c| hermite_component(v0[c], out0[c], v1[c], in1[c], s, dt));
+        let q = Quaternion::new(q[3], q[0], q[1], q[2]).normalize();
+        [q.v.x, q.v.y, q.v.z, q.s]
+    }
+}
+
+fn load_gltf(path: &Path) -> Result<Vec<AnimationClip>, Box<dyn std::error::Error>> {
+    let gltf = gltf::Gltf::open(path)?;
+    let buffers = gltf::import_buffers(&gltf.document, path.parent(), gltf.blob.clone())?;
+    clips_from_gltf(&gltf.document, &buffers)
+}
+
+// Lee los canales de traslación, rotación y escala de todas las animaciones del documento.
+// Un canal mal formado es un error de importación.
+pub(crate) fn clips_from_gltf(
+    document: &gltf::Document,
+    buffers: &[gltf::buffer::Data],
+) -> Result<Vec<AnimationClip>, Box<dyn std::error::Error>> {
+    let mut clips = Vec::new();
+    for animation in document.animations() {
+        let mut channels = Vec::new();
+        for channel in animation.channels() {
+            let node = channel.target().node();
+            let target = match node.name() {
+                Some(GLTF_CAMERA_EYE) => AnimationTarget::CameraEye,
+                Some(GLTF_CAMERA_TARGET) => AnimationTarget::CameraTarget,
+                _ => AnimationTarget::Object(node.index()),
+            };
+            let interpolation = match channel.sampler().interpolation() {
+                gltf::animation::Interpolation::Linear => Interpolation::Linear,
+                gltf::animation::Interpolation::Step => Interpolation::Step,
+                gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+            };
+
+            let reader = channel.reader(|buffer| Some(buffers[buffer.index()].0.as_slice()));
+            let (Some(inputs), Some(outputs)) = (reader.read_inputs(), reader.read_outputs()) else {
+                continue;
+            };
+            let values = match outputs {
+                gltf::animation::util::ReadOutputs::Translations(v) => Keyframes::Translation(v.collect()),
+                gltf::animation::util::ReadOutputs::Rotations(v) => Keyframes::Rotation(v.into_f32().collect()),
+                gltf::animation::util::ReadOutputs::Scales(v) => Keyframes::Scale(v.collect()),
+                // Los pesos de morph targets no son transformaciones
+                gltf::animation::util::ReadOutputs::MorphTargetWeights(_) => continue,
+            };
+
+            channels.push(Channel {
+                target,
+                interpolation,
+                times: inputs.collect(),
+                values,
+            });
+        }
+
+        let clip = AnimationClip {
+            name: animation
+                .name()
+                .map(String::from)
+                .unwrap_or_else(|| format!("animación {}", animation.index())),
+            channels,
+        };
+        clip.validate()?;
+        clips.push(clip);
+    }
+    Ok(clips)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlaybackMode {
+    Once,
+    Loop,
+    PingPong,
+}
+
+// Estado de reproducción de un clip: avanza con el dt del AnimationClock
+#[derive(Copy, Clone, Debug)]
+pub struct AnimationPlayer {
+    pub mode: PlaybackMode,
+    pub speed: f32,
+    pub playing: bool,
+    time: f32,
+}
+
+impl AnimationPlayer {
+    pub fn new(mode: PlaybackMode) -> Self {
+        Self {
+            mode,
+            speed: 1.0,
+            playing: true,
+            time: 0.0,
+        }
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        if self.playing {
+            self.time += dt * self.speed;
+        }
+    }
+
+    // Tiempo acumulado desde el inicio, sin aplicar el modo de reproducción
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn seek(&mut self, time: f32) {
+        self.time = time.max(0.0);
+    }
+
+    // Tiempo dentro del clip según el modo de reproducción
+    pub fn clip_time(&self, duration: f32) -> f32 {
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        match self.mode {
+            PlaybackMode::Once => self.time.clamp(0.0, duration),
+            PlaybackMode::Loop => self.time.rem_euclid(duration),
+            PlaybackMode::PingPong => {
+                let t = self.time.rem_euclid(duration * 2.0);
+                if t > duration { duration * 2.0 - t } else { t }
+            }
+        }
+    }
+}
+
+impl Default for AnimationPlayer {
+    fn default() -> Self {
+        Self::new(PlaybackMode::Loop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(interpolation: Interpolation, times: Vec<f32>, values: Vec<[f32; 3]>) -> Channel {
+        Channel {
+            target: AnimationTarget::Object(0),
+            interpolation,
+            times,
+            values: Keyframes::Translation(values),
+        }
+    }
+
+    fn sample(channel: &Channel, time: f32) -> [f32; 3] {
+        let Keyframes::Translation(values) = &channel.values else { unreachable!() };
+        channel.sample(values, time)
+    }
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        assert!(a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-5), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn linear_interpolates_and_clamps_at_the_ends() {
+        let c = channel(Interpolation::Linear, vec![1.0, 3.0], vec![[0.0; 3], [2.0, 4.0, -2.0]]);
+        assert_close(sample(&c, 0.0), [0.0; 3]);
+        assert_close(sample(&c, 2.0), [1.0, 2.0, -1.0]);
+        assert_close(sample(&c, 2.5), [1.5, 3.0, -1.5]);
+        assert_close(sample(&c, 10.0), [2.0, 4.0, -2.0]);
+    }
+
+    #[test]
+    fn step_holds_the_previous_keyframe() {
+        let c = channel(Interpolation::Step, vec![0.0, 1.0, 2.0], vec![[0.0; 3], [1.0; 3], [2.0; 3]]);
+        assert_close(sample(&c, 0.99), [0.0; 3]);
+        assert_close(sample(&c, 1.0), [1.0; 3]);
+        assert_close(sample(&c, 1.5), [1.0; 3]);
+        assert_close(sample(&c, 2.0), [2.0; 3]);
+    }
+
+    #[test]
+    fn cubic_spline_follows_the_tangents() {
+        // [entrada, valor, salida] por keyframe. Con tangentes iguales a la pendiente
+        // (1 por segundo) la curva es la recta.
+        let key = |v: f32| [[1.0; 3], [v; 3], [1.0; 3]];
+        let values: Vec<[f32; 3]> = [key(0.0), key(2.0)].concat();
+        let c = channel(Interpolation::CubicSpline, vec![0.0, 2.0], values);
+        assert!(c.is_valid());
+        assert_close(sample(&c, 0.5), [0.5; 3]);
+        assert_close(sample(&c, 1.0), [1.0; 3]);
+
+        // Con tangentes nulas es la curva suave 3s² - 2s³
+        let key = |v: f32| [[0.0; 3], [v; 3], [0.0; 3]];
+        let c = channel(Interpolation::CubicSpline, vec![0.0, 1.0], [key(0.0), key(1.0)].concat());
+        assert_close(sample(&c, 0.25), [0.15625; 3]);
+        assert_close(sample(&c, 0.5), [0.5; 3]);
+        assert_close(sample(&c, 1.0), [1.0; 3]);
+    }
+
+    #[test]
+    fn rejects_times_that_do_not_increase() {
+        let two = vec![[0.0; 3], [1.0; 3]];
+        assert!(channel(Interpolation::Linear, vec![0.0, 1.0], two.clone()).check().is_ok());
+        assert!(channel(Interpolation::Linear, vec![1.0, 1.0], two.clone()).check().is_err());
+        assert!(channel(Interpolation::Linear, vec![2.0, 1.0], two.clone()).check().is_err());
+        assert!(channel(Interpolation::Linear, vec![0.0, f32::NAN], two.clone()).check().is_err());
+        assert!(channel(Interpolation::Linear, vec![0.0], two.clone()).check().is_err());
+        assert!(channel(Interpolation::CubicSpline, vec![0.0, 1.0], two).check().is_err());
+        assert!(channel(Interpolation::Linear, Vec::new(), Vec::new()).check().is_err());
+
+        let clip = AnimationClip {
+            name: "roto".to_string(),
+            channels: vec![channel(Interpolation::Linear, vec![0.0, 0.0], vec![[0.0; 3]; 2])],
+        };
+        assert!(clip.validate().unwrap_err().contains("estrictamente crecientes"));
+    }
+
+    #[test]
+    fn bundled_animations_are_valid() {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        for file in ["animations/demo.ron", "models/barra.gltf"] {
+            let clips = AnimationClip::load(&assets.join(file)).unwrap();
+            assert!(clips.iter().all(|clip| clip.channels.iter().all(Channel::is_valid)), "{}", file);
+        }
+    }
+
+    #[test]
+    fn playback_modes_wrap_the_clip_time() {
+        let at = |mode: PlaybackMode, time: f32| {
+            let mut player = AnimationPlayer::new(mode);
+            player.advance(time);
+            player.clip_time(2.0)
+        };
+        assert_eq!(at(PlaybackMode::Once, 1.5), 1.5);
+        assert_eq!(at(PlaybackMode::Once, 5.0), 2.0);
+        assert_eq!(at(PlaybackMode::Loop, 1.5), 1.5);
+        assert_eq!(at(PlaybackMode::Loop, 5.0), 1.0);
+        assert_eq!(at(PlaybackMode::PingPong, 1.5), 1.5);
+        assert_eq!(at(PlaybackMode::PingPong, 3.0), 1.0);
+        assert_eq!(at(PlaybackMode::PingPong, 5.0), 1.0);
+        assert_eq!(at(PlaybackMode::PingPong, 4.0), 0.0);
+
+        let mut player = AnimationPlayer::new(PlaybackMode::Loop);
+        player.playing = false;
+        player.advance(1.0);
+        assert_eq!(player.time(), 0.0);
+        assert_eq!(player.clip_time(0.0), 0.0);
+    }
+}
@@ -0,0 +1,223 @@
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use image::{Rgba, RgbaImage};
+
+use crate::compressed_texture::CompressedImage;
+use crate::cooked::{self, CookedMesh, CookedTexture, MESH_EXTENSION, TEXTURE_EXTENSION};
+use crate::primitives::MeshData;
+
+// Identificador de una carga, para reconocer su resultado al recibirlo
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LoadId(u64);
+
+// Resultado ya decodificado en CPU; la subida a la GPU la hace el hilo principal
+pub enum LoadedAsset {
+    Image(RgbaImage),
+    Compressed(CompressedImage),
+    Mesh(MeshData),
+}
+
+pub struct LoadResult {
+    pub id: LoadId,
+    pub label: String,
+    pub asset: Result<LoadedAsset, String>,
+    pub elapsed: Duration,
+}
+
+type Job = (LoadId, String, Box<dyn FnOnce() -> Result<LoadedAsset, String> + Send>);
+
+// Grupo de hilos que leen y decodifican assets en segundo plano. Los resultados se
+// recogen con poll() una vez por frame, sin bloquear.
+pub struct AssetLoader {
+    jobs: Option<Sender<Job>>,
+    results: Receiver<LoadResult>,
+    workers: Vec<JoinHandle<()>>,
+    next_id: u64,
+    requested: usize,
+    finished: usize,
+}
+
+impl AssetLoader {
+    pub fn new(threads: usize) -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<Job>();
+        let (result_sender, results) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let workers = (0..threads.max(1))
+            .map(|i| {
+                let job_receiver = Arc::clone(&job_receiver);
+                let result_sender = result_sender.clone();
+                std::thread::Builder::new()
+                    .name(format!("asset-loader-{}", i))
+                    .spawn(move || loop {
+                        // El lock solo se tiene mientras se espera el siguiente trabajo
+                        let job = job_receiver.lock().unwrap().recv();
+                        let Ok((id, label, load)) = job else { break };
+                        let start = Instant::now();
+                        // Un panic en la carga se informa como error; si no, el
+                        // resultado nunca llegaría y is_idle() no volvería a ser true
+                        let asset = std::panic::catch_unwind(std::panic::AssertUnwindSafe(load))
+                            .unwrap_or_else(|panic| Err(format!("la carga falló: {}", panic_message(panic.as_ref()))));
+                        let result = LoadResult { id, label, asset, elapsed: start.elapsed() };
+                        if result_sender.send(result).is_err() {
+                            break;
+                        }
+                    })
+                    .expect("no se pudo crear el hilo de carga")
+            })
+            .collect();
+
+        Self {
+            jobs: Some(jobs),
+            results,
+            workers,
+            next_id: 0,
+            requested: 0,
+            finished: 0,
+        }
+    }
+
+    // Un hilo por núcleo, dejando uno para el render
+    pub fn with_default_threads() -> Self {
+        let cores = std::thread::available_parallelism().map_or(2, |n| n.get());
+        Self::new(cores.saturating_sub(1).clamp(1, 4))
+    }
+
+    // Encola una carga arbitraria; `label` se usa en el log y en el progreso
+    pub fn load_with(
+        &mut self,
+        label: impl Into<String>,
+        load: impl FnOnce() -> Result<LoadedAsset, String> + Send + 'static,
+    ) -> LoadId {
+        let id = LoadId(self.next_id);
+        self.next_id += 1;
+        self.requested += 1;
+        if let Some(jobs) = &self.jobs {
+            jobs.send((id, label.into(), Box::new(load))).expect("los hilos de carga terminaron");
+        }
+        id
+    }
+
+    // .ktx2, .dds y .cubotex quedan comprimidas; el resto se decodifica a RGBA8
+    pub fn load_texture(&mut self, path: &Path) -> LoadId {
+        let path = path.to_path_buf();
+        self.load_with(path.display().to_string(), move || load_texture(&path).map_err(|e| e.to_string()))
+    }
+
+    // .cubomesh, .gltf o .glb
+    pub fn load_mesh(&mut self, path: &Path) -> LoadId {
+        let path = path.to_path_buf();
+        self.load_with(path.display().to_string(), move || load_mesh(&path).map_err(|e| e.to_string()))
+    }
+
+    // Resultados terminados desde la última llamada
+    pub fn poll(&mut self) -> Vec<LoadResult> {
+        let results: Vec<LoadResult> = self.results.try_iter().collect();
+        self.finished += results.len();
+        results
+    }
+
+    // (terminadas, pedidas)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.finished, self.requested)
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.finished == self.requested
+    }
+}
+
+impl Drop for AssetLoader {
+    fn drop(&mut self) {
+        // Al cerrar el canal los hilos terminan después del trabajo en curso
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("panic sin mensaje")
+}
+
+pub(crate) fn load_texture(path: &Path) -> Result<LoadedAsset, Box<dyn std::error::Error>> {
+    if CompressedImage::is_container(path) {
+        Ok(LoadedAsset::Compressed(CompressedImage::load(path)?))
+    } else if path.extension().is_some_and(|ext| ext == TEXTURE_EXTENSION) {
+        Ok(LoadedAsset::Compressed(CookedTexture::open(path)?.to_image()))
+    } else {
+        Ok(LoadedAsset::Image(image::open(path)?.to_rgba8()))
+    }
+}
+
+pub fn load_mesh(path: &Path) -> Result<LoadedAsset, Box<dyn std::error::Error>> {
+    if path.extension().is_some_and(|ext| ext == MESH_EXTENSION) {
+        Ok(LoadedAsset::Mesh(CookedMesh::open(path)?.to_mesh_data()))
+    } else {
+        Ok(LoadedAsset::Mesh(cooked::load_gltf_mesh(path)?))
+    }
+}
+
+// Tablero de ajedrez gris que se muestra mientras la textura real se carga
+pub fn checkerboard(size: u32, cells: u32) -> RgbaImage {
+    let cell = (size / cells.max(1)).max(1);
+    RgbaImage::from_fn(size, size, |x, y| {
+        if (x / cell + y / cell).is_multiple_of(2) {
+            Rgba([200, 200, 200, 255])
+        } else {
+            Rgba([90, 90, 90, 255])
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Espera hasta que terminen todas las cargas pedidas
+    fn wait(loader: &mut AssetLoader) -> Vec<LoadResult> {
+        let start = Instant::now();
+        let mut results = Vec::new();
+        while !loader.is_idle() {
+            assert!(start.elapsed() < Duration::from_secs(10), "las cargas no terminaron");
+            results.extend(loader.poll());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        results
+    }
+
+    #[test]
+    fn reports_results_and_errors() {
+        let mut loader = AssetLoader::new(2);
+        let ok = loader.load_with("tablero", || Ok(LoadedAsset::Image(checkerboard(4, 2))));
+        let missing = loader.load_texture(Path::new("no-existe/textura.png"));
+        let results = wait(&mut loader);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().any(|r| r.id == ok && r.asset.is_ok()));
+        assert!(results.iter().any(|r| r.id == missing && r.asset.is_err()));
+        assert_eq!(loader.progress(), (2, 2));
+    }
+
+    #[test]
+    fn a_panicking_job_is_reported_as_an_error() {
+        let mut loader = AssetLoader::new(1);
+        let id = loader.load_with("roto", || panic!("archivo corrupto"));
+        let results = wait(&mut loader);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, id);
+        assert!(results[0].asset.as_ref().err().is_some_and(|e| e.contains("archivo corrupto")));
+
+        // El hilo sigue atendiendo cargas después del panic
+        loader.load_with("tablero", || Ok(LoadedAsset::Image(checkerboard(4, 2))));
+        assert!(wait(&mut loader)[0].asset.is_ok());
+    }
+}
@@ -0,0 +1,540 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+
+use wgpu::util::DeviceExt;
+
+use crate::asset_loader::{self, LoadedAsset};
+use crate::bounds::Aabb;
+use crate::compressed_texture::CompressedImage;
+use crate::cooked::{self, TextureCompression, TextureCookSettings};
+use crate::primitives::MeshData;
+use crate::sampler::{SamplerCache, SamplerSettings};
+
+// Referencia compartida a un asset del registro. Los recursos de GPU se liberan
+// cuando se suelta el último handle.
+pub struct Handle<T> {
+    slot: Arc<Slot<T>>,
+}
+
+struct Slot<T> {
+    name: String,
+    bytes: u64,
+    // Contador de memoria del tipo de asset, compartido con el registro
+    counter: Arc<AtomicU64>,
+    asset: T,
+}
+
+impl<T> Drop for Slot<T> {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(self.bytes, Ordering::Relaxed);
+        log::debug!("📦 Liberado {} ({})", self.name, format_bytes(self.bytes));
+    }
+}
+
+impl<T> Handle<T> {
+    pub fn name(&self) -> &str {
+        &self.slot.name
+    }
+
+    // Memoria de GPU que ocupa el asset
+    pub fn gpu_bytes(&self) -> u64 {
+        self.slot.bytes
+    }
+
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Arc::ptr_eq(&a.slot, &b.slot)
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self { slot: Arc::clone(&self.slot) }
+    }
+}
+
+impl<T> Deref for Handle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.slot.asset
+    }
+}
+
+// Ajustes de importación de texturas; forman parte de la clave junto con la ruta
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextureSettings {
+    pub srgb: bool,
+    pub mipmaps: bool,
+}
+
+impl Default for TextureSettings {
+    fn default() -> Self {
+        Self { srgb: true, mipmaps: true }
+    }
+}
+
+pub struct TextureAsset {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    // Formato del archivo; si compressed es false se descomprimió a RGBA8
+    pub source_format: wgpu::TextureFormat,
+    pub compressed: bool,
+}
+
+// Malla con el layout de MeshVertex e índices u32
+pub struct MeshAsset {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub vertex_count: u32,
+    pub index_count: u32,
+    pub aabb: Aabb,
+}
+
+// Textura difusa con su sampler y bind group; el material mantiene viva la textura.
+// El sampler sale de la caché y lo comparten los materiales con los mismos ajustes.
+pub struct MaterialAsset {
+    pub texture: Handle<TextureAsset>,
+    pub sampler: Arc<wgpu::Sampler>,
+    pub bind_group: wgpu::BindGroup,
+}
+
+pub struct ShaderAsset {
+    pub module: wgpu::ShaderModule,
+}
+
+// Memoria de GPU en bytes por tipo de asset
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AssetMemory {
+    pub textures: u64,
+    pub meshes: u64,
+}
+
+impl AssetMemory {
+    pub fn total(&self) -> u64 {
+        self.textures + self.meshes
+    }
+}
+
+impl std::fmt::Display for AssetMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "texturas {} | mallas {} | total {}",
+            format_bytes(self.textures),
+            format_bytes(self.meshes),
+            format_bytes(self.total())
+        )
+    }
+}
+
+// Fila del listado de assets vivos
+#[derive(Clone, Debug)]
+pub struct AssetInfo {
+    pub kind: &'static str,
+    pub name: String,
+    pub bytes: u64,
+    pub handles: usize,
+}
+
+// Assets vivos de un tipo. Guarda referencias débiles: el registro no mantiene
+// nada cargado por sí mismo.
+struct Cache<K, T> {
+    entries: HashMap<K, Weak<Slot<T>>>,
+    bytes: Arc<AtomicU64>,
+}
+
+impl<K: Hash + Eq, T> Cache<K, T> {
+    fn new() -> Self {
+        Self { entries: HashMap::new(), bytes: Arc::new(AtomicU64::new(0)) }
+    }
+
+    fn get(&mut self, key: &K) -> Option<Handle<T>> {
+        let slot = self.entries.get(key)?.upgrade();
+        if slot.is_none() {
+            self.entries.remove(key);
+        }
+        slot.map(|slot| Handle { slot })
+    }
+
+    fn insert(&mut self, key: K, name: String, bytes: u64, asset: T) -> Handle<T> {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        let slot = Arc::new(Slot { name, bytes, counter: Arc::clone(&self.bytes), asset });
+        self.entries.insert(key, Arc::downgrade(&slot));
+        Handle { slot }
+    }
+
+    fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    fn report(&self, kind: &'static str, out: &mut Vec<AssetInfo>) {
+        out.extend(self.entries.values().filter_map(|weak| {
+            let handles = weak.strong_count();
+            let slot = weak.upgrade()?;
+            Some(AssetInfo { kind, name: slot.name.clone(), bytes: slot.bytes, handles })
+        }));
+    }
+
+    fn purge(&mut self) {
+        self.entries.retain(|_, weak| weak.strong_count() > 0);
+    }
+}
+
+type TextureKey = (PathBuf, TextureSettings);
+type MaterialKey = (PathBuf, TextureSettings, SamplerSettings);
+
+// Registro de assets de GPU indexado por ruta y ajustes de importación. Pedir dos
+// veces lo mismo devuelve el mismo handle mientras el primero siga vivo.
+pub struct Assets {
+    textures: Cache<TextureKey, TextureAsset>,
+    meshes: Cache<PathBuf, MeshAsset>,
+    materials: Cache<MaterialKey, MaterialAsset>,
+    shaders: Cache<PathBuf, ShaderAsset>,
+    samplers: SamplerCache,
+}
+
+impl Default for Assets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Assets {
+    pub fn new() -> Self {
+        Self {
+            textures: Cache::new(),
+            meshes: Cache::new(),
+            materials: Cache::new(),
+            shaders: Cache::new(),
+            samplers: SamplerCache::new(),
+        }
+    }
+
+    // Textura ya cargada o leída del disco en el hilo actual (.ktx2, .dds, .cubotex o imagen)
+    pub fn texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: &Path,
+        settings: TextureSettings,
+    ) -> Result<Handle<TextureAsset>, Box<dyn std::error::Error>> {
+        if let Some(handle) = self.get_texture(path, settings) {
+            return Ok(handle);
+        }
+        let asset = asset_loader::load_texture(path)?;
+        self.insert_texture(device, queue, path, settings, &asset)
+    }
+
+    pub fn get_texture(&mut self, path: &Path, settings: TextureSettings) -> Option<Handle<TextureAsset>> {
+        self.textures.get(&(path.to_path_buf(), settings))
+    }
+
+    // Sube una textura ya decodificada, por ejemplo el resultado del AssetLoader. Si
+    // la clave ya está viva se devuelve esa y no se sube nada.
+    pub fn insert_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: &Path,
+        settings: TextureSettings,
+        asset: &LoadedAsset,
+    ) -> Result<Handle<TextureAsset>, Box<dyn std::error::Error>> {
+        if let Some(handle) = self.get_texture(path, settings) {
+            return Ok(handle);
+        }
+        let label = path.display().to_string();
+        let (cooked, adjusted);
+        let image = match asset {
+            LoadedAsset::Image(image) => {
+                let cook_settings = TextureCookSettings {
+                    compression: TextureCompression::None,
+                    mipmaps: settings.mipmaps,
+                    srgb: settings.srgb,
+                };
+                cooked = cooked::cook_texture(image, &cook_settings);
+                &cooked
+            }
+            LoadedAsset::Compressed(image) => {
+                adjusted = with_settings(image, settings);
+                adjusted.as_ref().unwrap_or(image)
+            }
+            LoadedAsset::Mesh(_) => return Err(format!("{} es una malla, se esperaba una textura", label).into()),
+        };
+        self.upload_texture(device, queue, path, settings, image)
+    }
+
+    fn upload_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: &Path,
+        settings: TextureSettings,
+        image: &CompressedImage,
+    ) -> Result<Handle<TextureAsset>, Box<dyn std::error::Error>> {
+        let label = path.display().to_string();
+        let (texture, compressed) = image.upload(device, queue, &label)?;
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bytes = texture_bytes(&texture);
+        let asset = TextureAsset { texture, view, source_format: image.format, compressed };
+        log::info!("📦 Textura {} en GPU ({})", label, format_bytes(bytes));
+        Ok(self.textures.insert((path.to_path_buf(), settings), label, bytes, asset))
+    }
+
+    // Textura creada fuera del registro (cubemaps, texturas array), para que se
+    // cuente en la memoria y se comparta por ruta como las demás
+    pub fn insert_gpu_texture(
+        &mut self,
+        path: &Path,
+        settings: TextureSettings,
+        texture: wgpu::Texture,
+        view: wgpu::TextureView,
+    ) -> Handle<TextureAsset> {
+        if let Some(handle) = self.get_texture(path, settings) {
+            return handle;
+        }
+        let label = path.display().to_string();
+        let bytes = texture_bytes(&texture);
+        let asset = TextureAsset { source_format: texture.format(), texture, view, compressed: false };
+        log::info!("📦 Textura {} en GPU ({})", label, format_bytes(bytes));
+        self.textures.insert((path.to_path_buf(), settings), label, bytes, asset)
+    }
+
+    pub fn insert_mesh(&mut self, device: &wgpu::Device, path: &Path, data: &MeshData) -> Handle<MeshAsset> {
+        if let Some(handle) = self.meshes.get(&path.to_path_buf()) {
+            return handle;
+        }
+        let label = path.display().to_string();
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Vertex Buffer", label)),
+            contents: bytemuck::cast_slice(&data.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Index Buffer", label)),
+            contents: bytemuck::cast_slice(&data.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let bytes = vertex_buffer.size() + index_buffer.size();
+        let asset = MeshAsset {
+            vertex_buffer,
+            index_buffer,
+            vertex_count: data.vertices.len() as u32,
+            index_count: data.indices.len() as u32,
+            aabb: Aabb::from_points(data.positions()),
+        };
+        log::info!("📦 Malla {} en GPU ({})", label, format_bytes(bytes));
+        self.meshes.insert(path.to_path_buf(), label, bytes, asset)
+    }
+
+    // Material con la textura de `path`. Todos los materiales comparten el layout del
+    // bind group del visor: textura en el binding 0 y sampler en el 1.
+    pub fn material(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        path: &Path,
+        texture_settings: TextureSettings,
+        sampler: &SamplerSettings,
+    ) -> Result<Handle<MaterialAsset>, Box<dyn std::error::Error>> {
+        let key = (path.to_path_buf(), texture_settings, *sampler);
+        if let Some(handle) = self.materials.get(&key) {
+            return Ok(handle);
+        }
+        let texture = self.texture(device, queue, path, texture_settings)?;
+        let sampler = self.samplers.get(device, sampler);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{} Material", texture.name())),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        let name = texture.name().to_string();
+        // La memoria del material es la de su textura, que ya se cuenta aparte
+        Ok(self.materials.insert(key, name, 0, MaterialAsset { texture, sampler, bind_group }))
+    }
+
+    // Shader incluido en el binario; `name` hace de clave
+    pub fn shader_from_source(&mut self, device: &wgpu::Device, name: &str, source: &str) -> Handle<ShaderAsset> {
+        let key = PathBuf::from(name);
+        if let Some(handle) = self.shaders.get(&key) {
+            return handle;
+        }
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        self.shaders.insert(key, name.to_string(), 0, ShaderAsset { module })
+    }
+
+    // Sampler compartido con todos los pedidos de los mismos ajustes
+    pub fn sampler(&mut self, device: &wgpu::Device, settings: &SamplerSettings) -> Arc<wgpu::Sampler> {
+        self.samplers.get(device, settings)
+    }
+
+    pub fn sampler_count(&self) -> usize {
+        self.samplers.len()
+    }
+
+    pub fn memory(&self) -> AssetMemory {
+        AssetMemory { textures: self.textures.bytes(), meshes: self.meshes.bytes() }
+    }
+
+    // Assets vivos, de mayor a menor memoria
+    pub fn report(&self) -> Vec<AssetInfo> {
+        let mut out = Vec::new();
+        self.textures.report("textura", &mut out);
+        self.meshes.report("malla", &mut out);
+        self.materials.report("material", &mut out);
+        self.shaders.report("shader", &mut out);
+        out.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+        out
+    }
+
+    // Quita del índice las entradas cuyos handles ya se soltaron
+    pub fn purge(&mut self) {
+        self.textures.purge();
+        self.meshes.purge();
+        self.materials.purge();
+        self.shaders.purge();
+        self.samplers.purge();
+    }
+}
+
+// Aplica los ajustes de importación a una textura que ya viene comprimida: srgb
+// elige la variante del formato y sin mipmaps se sube solo el primer nivel. A las
+// RGBA8 sin mipmaps se les generan; a las de bloques no, habría que recomprimirlas.
+// None si la imagen ya cumple los ajustes.
+fn with_settings(image: &CompressedImage, settings: TextureSettings) -> Option<CompressedImage> {
+    use wgpu::TextureFormat as F;
+    let format = if settings.srgb { image.format.add_srgb_suffix() } else { image.format.remove_srgb_suffix() };
+    let rgba = matches!(format, F::Rgba8Unorm | F::Rgba8UnormSrgb);
+    if settings.mipmaps && image.level_count() == 1 && rgba && (image.width > 1 || image.height > 1) {
+        let base = image::RgbaImage::from_raw(image.width, image.height, image.levels[0].clone())?;
+        let cook_settings = TextureCookSettings { compression: TextureCompression::None, mipmaps: true, srgb: settings.srgb };
+        return Some(cooked::cook_texture(&base, &cook_settings));
+    }
+    let drop_mips = !settings.mipmaps && image.level_count() > 1;
+    if format == image.format && !drop_mips {
+        return None;
+    }
+    let levels = if drop_mips { image.levels[..1].to_vec() } else { image.levels.clone() };
+    Some(CompressedImage { format, width: image.width, height: image.height, levels })
+}
+
+// Tamaño de todos los mipmaps y capas según los bloques del formato
+fn texture_bytes(texture: &wgpu::Texture) -> u64 {
+    let format = texture.format();
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = format.block_copy_size(None).unwrap_or(4) as u64;
+    let level_bytes: u64 = (0..texture.mip_level_count())
+        .map(|level| {
+            let width = (texture.width() >> level).max(1).div_ceil(block_width) as u64;
+            let height = (texture.height() >> level).max(1).div_ceil(block_height) as u64;
+            width * height * block_size
+        })
+        .sum();
+    level_bytes * texture.depth_or_array_layers() as u64
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1 << 20 {
+        format!("{:.1} MiB", bytes as f64 / (1u64 << 20) as f64)
+    } else if bytes >= 1 << 10 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_returns_the_same_handle_while_alive() {
+        let mut cache: Cache<&str, u32> = Cache::new();
+        let first = cache.insert("a", "a".to_string(), 100, 1);
+        let again = cache.get(&"a").unwrap();
+        assert!(Handle::ptr_eq(&first, &again));
+        assert_eq!(*again, 1);
+        assert!(cache.get(&"b").is_none());
+    }
+
+    #[test]
+    fn cache_releases_when_the_last_handle_drops() {
+        let mut cache: Cache<&str, u32> = Cache::new();
+        let handle = cache.insert("a", "a".to_string(), 100, 1);
+        let copy = handle.clone();
+        drop(handle);
+        assert!(cache.get(&"a").is_some());
+        drop(copy);
+        assert!(cache.get(&"a").is_none());
+        assert!(cache.entries.is_empty());
+
+        // purge quita las entradas muertas sin que nadie las pida
+        let handle = cache.insert("b", "b".to_string(), 10, 2);
+        drop(handle);
+        assert_eq!(cache.entries.len(), 1);
+        cache.purge();
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn cache_counts_memory_of_live_assets() {
+        let mut cache: Cache<&str, u32> = Cache::new();
+        let a = cache.insert("a", "a".to_string(), 100, 1);
+        let b = cache.insert("b", "b".to_string(), 50, 2);
+        assert_eq!(cache.bytes(), 150);
+
+        let mut report = Vec::new();
+        let extra = a.clone();
+        cache.report("prueba", &mut report);
+        let a_info = report.iter().find(|info| info.name == "a").unwrap();
+        assert_eq!((a_info.bytes, a_info.handles), (100, 2));
+
+        drop(a);
+        drop(extra);
+        assert_eq!(cache.bytes(), 50);
+        drop(b);
+        assert_eq!(cache.bytes(), 0);
+    }
+
+    fn rgba_image(levels: usize, format: wgpu::TextureFormat) -> CompressedImage {
+        let image = CompressedImage { format, width: 4, height: 4, levels: Vec::new() };
+        let levels = (0..levels as u32).map(|level| vec![255; image.level_size(level)]).collect();
+        CompressedImage { levels, ..image }
+    }
+
+    #[test]
+    fn compressed_textures_follow_the_import_settings() {
+        use wgpu::TextureFormat as F;
+        let linear = TextureSettings { srgb: false, mipmaps: true };
+        let bc1 = rgba_image(3, F::Bc1RgbaUnormSrgb);
+        let adjusted = with_settings(&bc1, linear).unwrap();
+        assert_eq!(adjusted.format, F::Bc1RgbaUnorm);
+        assert_eq!(adjusted.level_count(), 3);
+
+        let no_mips = with_settings(&bc1, TextureSettings { srgb: true, mipmaps: false }).unwrap();
+        assert_eq!((no_mips.format, no_mips.level_count()), (F::Bc1RgbaUnormSrgb, 1));
+
+        // Ya cumple los ajustes: no se copia
+        assert!(with_settings(&bc1, TextureSettings::default()).is_none());
+
+        // A una RGBA8 de un nivel se le generan los mipmaps
+        let rgba = with_settings(&rgba_image(1, F::Rgba8Unorm), TextureSettings::default()).unwrap();
+        assert_eq!((rgba.format, rgba.level_count()), (F::Rgba8UnormSrgb, 3));
+    }
+}
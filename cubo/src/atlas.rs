@@ -0,0 +1,283 @@
+use std::path::Path;
+
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::{CubeUvLayout, UvRect};
+
+// Nombres de las imágenes que usa cada cara del cubo, en el orden de CubeFace
+pub const CUBE_FACE_NAMES: [&str; 6] = ["front", "back", "top", "bottom", "right", "left"];
+
+// Región de una imagen dentro del atlas, en píxeles y sin contar el relleno
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AtlasRect {
+    pub name: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Descripción del atlas que se guarda en JSON junto a la imagen
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AtlasLayout {
+    pub width: u32,
+    pub height: u32,
+    pub padding: u32,
+    pub rects: Vec<AtlasRect>,
+}
+
+impl AtlasLayout {
+    pub fn load_json(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save_json(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn find(&self, name: &str) -> Option<&AtlasRect> {
+        self.rects.iter().find(|rect| rect.name == name)
+    }
+
+    // UV de una imagen; el relleno alrededor permite muestrear justo en el borde
+    pub fn uv_rect(&self, name: &str) -> Option<UvRect> {
+        let rect = self.find(name)?;
+        let (w, h) = (self.width as f32, self.height as f32);
+        Some(UvRect {
+            min: [rect.x as f32 / w, rect.y as f32 / h],
+            max: [(rect.x + rect.width) as f32 / w, (rect.y + rect.height) as f32 / h],
+        })
+    }
+
+    // Distribución de UV del cubo buscando las imágenes por CUBE_FACE_NAMES
+    pub fn cube_layout(&self) -> Option<CubeUvLayout> {
+        let mut layout = CubeUvLayout::full();
+        for (face, name) in CUBE_FACE_NAMES.iter().enumerate() {
+            layout.faces[face] = self.uv_rect(name)?;
+        }
+        Some(layout)
+    }
+}
+
+// Empaqueta varias imágenes en una sola textura por filas ("shelf packing").
+// Alrededor de cada imagen se deja `padding` píxeles repitiendo su borde, así los
+// mipmaps y el filtrado no mezclan colores de imágenes vecinas.
+pub struct AtlasBuilder {
+    padding: u32,
+    max_width: u32,
+    images: Vec<(String, RgbaImage)>,
+}
+
+impl AtlasBuilder {
+    pub fn new(padding: u32) -> Self {
+        Self {
+            padding,
+            max_width: 4096,
+            images: Vec::new(),
+        }
+    }
+
+    pub fn max_width(mut self, max_width: u32) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, image: RgbaImage) {
+        self.images.push((name.into(), image));
+    }
+
+    // Agrega todas las imágenes de una carpeta, con el nombre del archivo sin extensión.
+    // Devuelve cuántas se cargaron.
+    pub fn add_dir(&mut self, dir: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| image::ImageFormat::from_path(path).is_ok())
+            .collect();
+        paths.sort();
+        for path in &paths {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            self.add(name, image::open(path)?.to_rgba8());
+        }
+        Ok(paths.len())
+    }
+
+    pub fn build(&self) -> Result<(RgbaImage, AtlasLayout), Box<dyn std::error::Error>> {
+        if self.images.is_empty() {
+            return Err("el atlas no tiene imágenes".into());
+        }
+        if let Some((name, _)) = self.images.iter().find(|(_, image)| image.width() == 0 || image.height() == 0) {
+            return Err(format!("la imagen '{}' está vacía", name).into());
+        }
+        let slot = |image: &RgbaImage| (image.width() + 2 * self.padding, image.height() + 2 * self.padding);
+
+        // Ancho: potencia de dos cercana a un atlas cuadrado, al menos la imagen más ancha
+        let area: u64 = self.images.iter().map(|(_, image)| {
+            let (w, h) = slot(image);
+            w as u64 * h as u64
+        }).sum();
+        let widest = self.images.iter().map(|(_, image)| slot(image).0).max().unwrap_or(1);
+        if widest > self.max_width {
+            return Err(format!("una imagen mide {} px de ancho, más que el máximo {}", widest, self.max_width).into());
+        }
+        let width = ((area as f64).sqrt().ceil() as u32)
+            .max(widest)
+            .next_power_of_two()
+            .min(self.max_width);
+
+        // Las imágenes más altas primero para desperdiciar menos espacio en cada fila
+        let mut order: Vec<usize> = (0..self.images.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(self.images[i].1.height()));
+
+        let mut positions = vec![(0, 0); self.images.len()];
+        let (mut x, mut y, mut row_height) = (0, 0, 0);
+        for &i in &order {
+            let (w, h) = slot(&self.images[i].1);
+            if x + w > width {
+                x = 0;
+                y += row_height;
+                row_height = 0;
+            }
+            positions[i] = (x, y);
+            x += w;
+            row_height = row_height.max(h);
+        }
+        let height = (y + row_height).next_power_of_two();
+
+        let mut atlas = RgbaImage::new(width, height);
+        let mut rects = Vec::with_capacity(self.images.len());
+        for ((name, image), &(x, y)) in self.images.iter().zip(&positions) {
+            let (w, h) = slot(image);
+            // Cada píxel del hueco copia el píxel más cercano de la imagen
+            for sy in 0..h {
+                for sx in 0..w {
+                    let ix = sx.saturating_sub(self.padding).min(image.width() - 1);
+                    let iy = sy.saturating_sub(self.padding).min(image.height() - 1);
+                    atlas.put_pixel(x + sx, y + sy, *image.get_pixel(ix, iy));
+                }
+            }
+            rects.push(AtlasRect {
+                name: name.clone(),
+                x: x + self.padding,
+                y: y + self.padding,
+                width: image.width(),
+                height: image.height(),
+            });
+        }
+
+        Ok((
+            atlas,
+            AtlasLayout {
+                width,
+                height,
+                padding: self.padding,
+                rects,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Imagen de un solo color, distinto para cada índice
+    fn solid(width: u32, height: u32, index: u8) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, image::Rgba([index, 255 - index, index.wrapping_mul(37), 255]))
+    }
+
+    fn builder(padding: u32, sizes: &[(u32, u32)]) -> AtlasBuilder {
+        let mut builder = AtlasBuilder::new(padding);
+        for (i, &(w, h)) in sizes.iter().enumerate() {
+            builder.add(format!("img{}", i), solid(w, h, i as u8 * 20));
+        }
+        builder
+    }
+
+    // Rectángulo con el relleno incluido
+    fn slot(rect: &AtlasRect, padding: u32) -> (u32, u32, u32, u32) {
+        (rect.x - padding, rect.y - padding, rect.width + 2 * padding, rect.height + 2 * padding)
+    }
+
+    const SIZES: [(u32, u32); 7] = [(64, 32), (16, 16), (100, 7), (33, 70), (1, 1), (48, 48), (20, 90)];
+
+    #[test]
+    fn rects_do_not_overlap_including_padding() {
+        let padding = 3;
+        let (_, layout) = builder(padding, &SIZES).build().unwrap();
+        assert_eq!(layout.rects.len(), SIZES.len());
+        for (i, a) in layout.rects.iter().enumerate() {
+            let (ax, ay, aw, ah) = slot(a, padding);
+            for b in &layout.rects[i + 1..] {
+                let (bx, by, bw, bh) = slot(b, padding);
+                let disjoint = ax + aw <= bx || bx + bw <= ax || ay + ah <= by || by + bh <= ay;
+                assert!(disjoint, "{} y {} se pisan", a.name, b.name);
+            }
+        }
+    }
+
+    #[test]
+    fn rects_fit_inside_atlas() {
+        let padding = 4;
+        let (image, layout) = builder(padding, &SIZES).build().unwrap();
+        assert_eq!((image.width(), image.height()), (layout.width, layout.height));
+        assert!(layout.width.is_power_of_two() && layout.height.is_power_of_two());
+        for (rect, &(w, h)) in layout.rects.iter().zip(&SIZES) {
+            assert_eq!((rect.width, rect.height), (w, h));
+            let (x, y, sw, sh) = slot(rect, padding);
+            assert!(x + sw <= layout.width && y + sh <= layout.height, "{} no entra", rect.name);
+        }
+    }
+
+    #[test]
+    fn pixels_and_padding_copy_the_image() {
+        let padding = 2;
+        let (image, layout) = builder(padding, &SIZES).build().unwrap();
+        for (i, rect) in layout.rects.iter().enumerate() {
+            let color = *solid(1, 1, i as u8 * 20).get_pixel(0, 0);
+            let (x, y, w, h) = slot(rect, padding);
+            for py in y..y + h {
+                for px in x..x + w {
+                    assert_eq!(*image.get_pixel(px, py), color, "{} en ({}, {})", rect.name, px, py);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn padding_repeats_the_nearest_edge_pixel() {
+        let mut source = RgbaImage::new(2, 2);
+        source.put_pixel(0, 0, image::Rgba([255, 0, 0, 255]));
+        source.put_pixel(1, 0, image::Rgba([0, 255, 0, 255]));
+        source.put_pixel(0, 1, image::Rgba([0, 0, 255, 255]));
+        source.put_pixel(1, 1, image::Rgba([255, 255, 255, 255]));
+        let mut builder = AtlasBuilder::new(3);
+        builder.add("cuadro", source.clone());
+        let (image, layout) = builder.build().unwrap();
+        let rect = layout.find("cuadro").unwrap();
+        // Las esquinas del relleno toman la esquina de la imagen y los lados su borde
+        assert_eq!(image.get_pixel(rect.x - 3, rect.y - 3), source.get_pixel(0, 0));
+        assert_eq!(image.get_pixel(rect.x + 4, rect.y - 1), source.get_pixel(1, 0));
+        assert_eq!(image.get_pixel(rect.x - 2, rect.y + 4), source.get_pixel(0, 1));
+        assert_eq!(image.get_pixel(rect.x + 1, rect.y + 1), source.get_pixel(1, 1));
+        assert_eq!(image.get_pixel(rect.x - 1, rect.y + 1), source.get_pixel(0, 1));
+    }
+
+    #[test]
+    fn uv_rects_exclude_padding() {
+        let (_, layout) = builder(4, &[(32, 16)]).build().unwrap();
+        let rect = layout.find("img0").unwrap();
+        let uv = layout.uv_rect("img0").unwrap();
+        assert_eq!(uv.min, [rect.x as f32 / layout.width as f32, rect.y as f32 / layout.height as f32]);
+        assert_eq!((rect.x, rect.y), (4, 4));
+        assert_eq!(uv.max[0], 36.0 / layout.width as f32);
+    }
+
+    #[test]
+    fn rejects_images_wider_than_the_maximum() {
+        assert!(builder(4, &[(60, 10)]).max_width(64).build().is_err());
+        assert!(builder(2, &[(60, 10)]).max_width(64).build().is_ok());
+        assert!(AtlasBuilder::new(0).build().is_err());
+    }
+}
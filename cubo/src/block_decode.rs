@@ -0,0 +1,881 @@
+// Decodificadores de bloques en CPU, para cuando el adaptador no soporta el formato
+// comprimido. Cada bloque de 4x4 se decodifica a 16 píxeles RGBA8 en orden de filas.
+
+type Block = [[u8; 4]; 16];
+type BlockDecoder = fn(&[u8]) -> Block;
+
+// Decodifica una imagen completa a RGBA8. Los bloques del borde se recortan al
+// tamaño de la imagen. Los formatos SNORM quedan en bytes con signo, para subirlos
+// como Rgba8Snorm.
+pub(crate) fn decode(format: wgpu::TextureFormat, width: u32, height: u32, data: &[u8]) -> Result<Vec<u8>, String> {
+    let (block_bytes, decode_block) =
+        block_decoder(format).ok_or_else(|| format!("no hay decodificador en CPU para {:?}", format))?;
+
+    let (blocks_x, blocks_y) = (width.div_ceil(4) as usize, height.div_ceil(4) as usize);
+    if data.len() < blocks_x * blocks_y * block_bytes {
+        return Err(format!("faltan datos: {} bytes para {} x {} en {:?}", data.len(), width, height, format));
+    }
+
+    let (width, height) = (width as usize, height as usize);
+    let mut pixels = vec![0; width * height * 4];
+    for (i, chunk) in data.chunks_exact(block_bytes).take(blocks_x * blocks_y).enumerate() {
+        let (bx, by) = (i % blocks_x * 4, i / blocks_x * 4);
+        for (j, texel) in decode_block(chunk).iter().enumerate() {
+            let (x, y) = (bx + j % 4, by + j / 4);
+            if x < width && y < height {
+                let offset = (y * width + x) * 4;
+                pixels[offset..offset + 4].copy_from_slice(texel);
+            }
+        }
+    }
+    Ok(pixels)
+}
+
+// Indica si el formato se puede descomprimir en CPU. ASTC y BC6H no: sin soporte
+// en el adaptador no se cargan.
+pub(crate) fn can_decode(format: wgpu::TextureFormat) -> bool {
+    block_decoder(format).is_some()
+}
+
+pub(crate) fn is_snorm(format: wgpu::TextureFormat) -> bool {
+    use wgpu::TextureFormat as F;
+    matches!(format, F::Bc4RSnorm | F::Bc5RgSnorm | F::EacR11Snorm | F::EacRg11Snorm)
+}
+
+fn block_decoder(format: wgpu::TextureFormat) -> Option<(usize, BlockDecoder)> {
+    use wgpu::TextureFormat as F;
+    Some(match format {
+        F::Bc1RgbaUnorm | F::Bc1RgbaUnormSrgb => (8, bc1),
+        F::Bc2RgbaUnorm | F::Bc2RgbaUnormSrgb => (16, bc2),
+        F::Bc3RgbaUnorm | F::Bc3RgbaUnormSrgb => (16, bc3),
+        F::Bc4RUnorm => (8, bc4),
+        F::Bc4RSnorm => (8, bc4_snorm),
+        F::Bc5RgUnorm => (16, bc5),
+        F::Bc5RgSnorm => (16, bc5_snorm),
+        F::Bc7RgbaUnorm | F::Bc7RgbaUnormSrgb => (16, bc7),
+        F::Etc2Rgb8Unorm | F::Etc2Rgb8UnormSrgb => (8, etc2_rgb),
+        F::Etc2Rgb8A1Unorm | F::Etc2Rgb8A1UnormSrgb => (8, etc2_rgb_a1),
+        F::Etc2Rgba8Unorm | F::Etc2Rgba8UnormSrgb => (16, etc2_rgba),
+        F::EacR11Unorm => (8, eac_r11),
+        F::EacR11Snorm => (8, eac_r11_snorm),
+        F::EacRg11Unorm => (16, eac_rg11),
+        F::EacRg11Snorm => (16, eac_rg11_snorm),
+        _ => return None,
+    })
+}
+
+// --- BC1 a BC5 ---
+
+pub(crate) fn rgb565(c: u16) -> [u8; 4] {
+    let (r, g, b) = ((c >> 11) as u8 & 31, (c >> 5) as u8 & 63, c as u8 & 31);
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2), 255]
+}
+
+pub(crate) fn mix(a: [u8; 4], b: [u8; 4], wa: u32, wb: u32) -> [u8; 4] {
+    std::array::from_fn(|c| ((a[c] as u32 * wa + b[c] as u32 * wb) / (wa + wb)) as u8)
+}
+
+// Bloque de color de BC1. En BC2 y BC3 siempre se usan 4 colores, sin transparencia.
+fn color_block(data: &[u8], allow_transparent: bool) -> Block {
+    let c0 = u16::from_le_bytes([data[0], data[1]]);
+    let c1 = u16::from_le_bytes([data[2], data[3]]);
+    let (e0, e1) = (rgb565(c0), rgb565(c1));
+    let palette = if c0 > c1 || !allow_transparent {
+        [e0, e1, mix(e0, e1, 2, 1), mix(e0, e1, 1, 2)]
+    } else {
+        [e0, e1, mix(e0, e1, 1, 1), [0, 0, 0, 0]]
+    };
+    let indices = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+    std::array::from_fn(|i| palette[(indices >> (2 * i)) as usize & 3])
+}
+
+// Bloque de 8 bytes con dos extremos y 16 índices de 3 bits (alfa de BC3, BC4 y BC5)
+fn alpha_block(data: &[u8]) -> [u8; 16] {
+    interpolated_block(data, data[0] as i32, data[1] as i32, 0, 255).map(|a| a as u8)
+}
+
+// Lo mismo en BC4 y BC5 SNORM: extremos con signo, -128 vale como -127
+fn signed_alpha_block(data: &[u8]) -> [i8; 16] {
+    let endpoint = |byte: u8| (byte as i8).max(-127) as i32;
+    interpolated_block(data, endpoint(data[0]), endpoint(data[1]), -127, 127).map(|a| a as i8)
+}
+
+// Con a0 > a1 hay 6 valores intermedios; si no, 4 más los extremos del rango
+fn interpolated_block(data: &[u8], a0: i32, a1: i32, min: i32, max: i32) -> [i32; 16] {
+    let mut palette = [a0, a1, 0, 0, 0, 0, 0, 0];
+    if a0 > a1 {
+        for i in 1..7 {
+            palette[i as usize + 1] = ((7 - i) * a0 + i * a1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i as usize + 1] = ((5 - i) * a0 + i * a1) / 5;
+        }
+        palette[6] = min;
+        palette[7] = max;
+    }
+    let bits = data[2..8].iter().rev().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+    std::array::from_fn(|i| palette[(bits >> (3 * i)) as usize & 7])
+}
+
+fn bc1(data: &[u8]) -> Block {
+    color_block(data, true)
+}
+
+fn bc2(data: &[u8]) -> Block {
+    let alpha = u64::from_le_bytes(data[0..8].try_into().unwrap());
+    let mut block = color_block(&data[8..16], false);
+    for (i, texel) in block.iter_mut().enumerate() {
+        texel[3] = ((alpha >> (4 * i)) & 15) as u8 * 17;
+    }
+    block
+}
+
+fn bc3(data: &[u8]) -> Block {
+    let alpha = alpha_block(&data[0..8]);
+    let mut block = color_block(&data[8..16], false);
+    for (texel, a) in block.iter_mut().zip(alpha) {
+        texel[3] = a;
+    }
+    block
+}
+
+fn bc4(data: &[u8]) -> Block {
+    alpha_block(data).map(|r| [r, 0, 0, 255])
+}
+
+fn bc5(data: &[u8]) -> Block {
+    let (r, g) = (alpha_block(&data[0..8]), alpha_block(&data[8..16]));
+    std::array::from_fn(|i| [r[i], g[i], 0, 255])
+}
+
+// En SNORM el alfa 127 es 1.0
+fn bc4_snorm(data: &[u8]) -> Block {
+    signed_alpha_block(data).map(|r| [r as u8, 0, 0, 127])
+}
+
+fn bc5_snorm(data: &[u8]) -> Block {
+    let (r, g) = (signed_alpha_block(&data[0..8]), signed_alpha_block(&data[8..16]));
+    std::array::from_fn(|i| [r[i] as u8, g[i] as u8, 0, 127])
+}
+
+// --- BC7 ---
+
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    // Un p-bit por extremo o uno compartido por subconjunto
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    // Índices separados para el alfa (modos 4 y 5)
+    index_bits2: u32,
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode {
+        subsets: 3,
+        partition_bits: 4,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 4,
+        alpha_bits: 0,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 3,
+        index_bits2: 0,
+    },
+    Bc7Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 6,
+        alpha_bits: 0,
+        endpoint_pbits: false,
+        shared_pbits: true,
+        index_bits: 3,
+        index_bits2: 0,
+    },
+    Bc7Mode {
+        subsets: 3,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 5,
+        alpha_bits: 0,
+        endpoint_pbits: false,
+        shared_pbits: false,
+        index_bits: 2,
+        index_bits2: 0,
+    },
+    Bc7Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 0,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 2,
+        index_bits2: 0,
+    },
+    Bc7Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 2,
+        index_selection_bits: 1,
+        color_bits: 5,
+        alpha_bits: 6,
+        endpoint_pbits: false,
+        shared_pbits: false,
+        index_bits: 2,
+        index_bits2: 3,
+    },
+    Bc7Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 2,
+        index_selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 8,
+        endpoint_pbits: false,
+        shared_pbits: false,
+        index_bits: 2,
+        index_bits2: 2,
+    },
+    Bc7Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 7,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 4,
+        index_bits2: 0,
+    },
+    Bc7Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 5,
+        alpha_bits: 5,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 2,
+        index_bits2: 0,
+    },
+];
+
+// Particiones de 2 subconjuntos: el bit i indica el subconjunto del píxel i
+const BC7_PARTITIONS_2: [u16; 64] = [
+    0xcccc, 0x8888, 0xeeee, 0xecc8, 0xc880, 0xfeec, 0xfec8, 0xec80, 0xc800, 0xffec, 0xfe80, 0xe800, 0xffe8, 0xff00,
+    0xfff0, 0xf000, 0xf710, 0x008e, 0x7100, 0x08ce, 0x008c, 0x7310, 0x3100, 0x8cce, 0x088c, 0x3110, 0x6666, 0x366c,
+    0x17e8, 0x0ff0, 0x718e, 0x399c, 0xaaaa, 0xf0f0, 0x5a5a, 0x33cc, 0x3c3c, 0x55aa, 0x9696, 0xa55a, 0x73ce, 0x13c8,
+    0x324c, 0x3bdc, 0x6996, 0xc33c, 0x9966, 0x0660, 0x0272, 0x04e4, 0x4e40, 0x2720, 0xc936, 0x936c, 0x39c6, 0x639c,
+    0x9336, 0x9cc6, 0x817e, 0xe718, 0xccf0, 0x0fcc, 0x7744, 0xee22,
+];
+
+// Particiones de 3 subconjuntos: dos bits por píxel, el píxel i en los bits 2i y 2i+1
+const BC7_PARTITIONS_3: [u32; 64] = [
+    0xaa685050, 0x6a5a5040, 0x5a5a4200, 0x5450a0a8, 0xa5a50000, 0xa0a05050, 0x5555a0a0, 0x5a5a5050, 0xaa550000,
+    0xaa555500, 0xaaaa5500, 0x90909090, 0x94949494, 0xa4a4a4a4, 0xa9a59450, 0x2a0a4250, 0xa5945040, 0x0a425054,
+    0xa5a5a500, 0x55a0a0a0, 0xa8a85454, 0x6a6a4040, 0xa4a45000, 0x1a1a0500, 0x0050a4a4, 0xaaa59090, 0x14696914,
+    0x69691400, 0xa08585a0, 0xaa821414, 0x50a4a450, 0x6a5a0200, 0xa9a58000, 0x5090a0a8, 0xa8a09050, 0x24242424,
+    0x00aa5500, 0x24924924, 0x24499224, 0x50a50a50, 0x500aa550, 0xaaaa4444, 0x66660000, 0xa5a0a5a0, 0x50a050a0,
+    0x69286928, 0x44aaaa44, 0x66666600, 0xaa444444, 0x54a854a8, 0x95809580, 0x96969600, 0xa85454a8, 0x80959580,
+    0xaa141414, 0x96960000, 0xaaaa1414, 0xa05050a0, 0xa0a5a5a0, 0x96000000, 0x40804080, 0xa9a8a9a8, 0xaaaaaa44,
+    0x2a4a5254,
+];
+
+// Píxel ancla (con un bit de índice menos) del segundo subconjunto en particiones de 2
+const BC7_ANCHORS_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8, 2, 2, 8, 8, 2, 2,
+    15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, 6, 2, 6, 8, 15, 15, 2, 2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+// Anclas del segundo y del tercer subconjunto en particiones de 3
+const BC7_ANCHORS_3: [[u8; 64]; 2] = [
+    [
+        3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3, 3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5, 15, 15, 8, 15,
+        3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15, 3, 15, 5, 5, 5, 8, 5, 10, 5, 10, 8, 13, 15, 12, 3, 3,
+    ],
+    [
+        15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8, 15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6, 10, 15, 15,
+        10, 8, 15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8, 15, 3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+        3, 15, 15, 8,
+    ],
+];
+
+const BC7_WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+const BC7_WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const BC7_WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+// Lector de bits desde el bit menos significativo del bloque
+struct BitReader {
+    bits: u128,
+    position: u32,
+}
+
+impl BitReader {
+    fn read(&mut self, count: u32) -> u32 {
+        let value = (self.bits >> self.position) as u32 & ((1u64 << count) - 1) as u32;
+        self.position += count;
+        value
+    }
+}
+
+fn bc7_interpolate(e0: u32, e1: u32, index_bits: u32, index: u32) -> u8 {
+    let weight = match index_bits {
+        2 => BC7_WEIGHTS_2[index as usize],
+        3 => BC7_WEIGHTS_3[index as usize],
+        _ => BC7_WEIGHTS_4[index as usize],
+    };
+    (((64 - weight) * e0 + weight * e1 + 32) >> 6) as u8
+}
+
+fn bc7(data: &[u8]) -> Block {
+    let mut reader = BitReader {
+        bits: u128::from_le_bytes(data[0..16].try_into().unwrap()),
+        position: 0,
+    };
+    // El modo es la posición del primer bit en 1; sin ninguno el bloque es inválido
+    let Some(mode) = (0..8).find(|_| reader.read(1) == 1) else {
+        return [[0; 4]; 16];
+    };
+    let mode = &BC7_MODES[mode];
+
+    let partition = reader.read(mode.partition_bits) as usize;
+    let rotation = reader.read(mode.rotation_bits);
+    let index_selection = reader.read(mode.index_selection_bits);
+
+    let endpoint_count = mode.subsets * 2;
+    let mut endpoints = [[255u32; 4]; 6];
+    let channels = if mode.alpha_bits > 0 { 4 } else { 3 };
+    for channel in 0..channels {
+        let bits = if channel < 3 { mode.color_bits } else { mode.alpha_bits };
+        for endpoint in endpoints.iter_mut().take(endpoint_count) {
+            endpoint[channel] = reader.read(bits);
+        }
+    }
+
+    let (mut color_bits, mut alpha_bits) = (mode.color_bits, mode.alpha_bits);
+    if mode.endpoint_pbits || mode.shared_pbits {
+        let pbits: Vec<u32> = if mode.endpoint_pbits {
+            (0..endpoint_count).map(|_| reader.read(1)).collect()
+        } else {
+            (0..mode.subsets).flat_map(|_| {
+                let p = reader.read(1);
+                [p, p]
+            }).collect()
+        };
+        for (endpoint, p) in endpoints.iter_mut().zip(pbits) {
+            for value in endpoint.iter_mut().take(channels) {
+                *value = (*value << 1) | p;
+            }
+        }
+        color_bits += 1;
+        if alpha_bits > 0 {
+            alpha_bits += 1;
+        }
+    }
+
+    // Extremos expandidos a 8 bits repitiendo los bits altos
+    for endpoint in endpoints.iter_mut().take(endpoint_count) {
+        for (channel, value) in endpoint.iter_mut().enumerate().take(channels) {
+            let bits = if channel < 3 { color_bits } else { alpha_bits };
+            *value = (*value << (8 - bits)) | (*value >> (2 * bits - 8));
+        }
+    }
+
+    let subset = |i: usize| match mode.subsets {
+        1 => 0,
+        2 => (BC7_PARTITIONS_2[partition] >> i) as usize & 1,
+        _ => (BC7_PARTITIONS_3[partition] >> (2 * i)) as usize & 3,
+    };
+    let is_anchor = |i: usize| {
+        i == 0
+            || match mode.subsets {
+                2 => i == BC7_ANCHORS_2[partition] as usize,
+                3 => i == BC7_ANCHORS_3[0][partition] as usize || i == BC7_ANCHORS_3[1][partition] as usize,
+                _ => false,
+            }
+    };
+
+    let indices: [u32; 16] = std::array::from_fn(|i| reader.read(mode.index_bits - is_anchor(i) as u32));
+    let indices2: [u32; 16] = if mode.index_bits2 > 0 {
+        std::array::from_fn(|i| reader.read(mode.index_bits2 - (i == 0) as u32))
+    } else {
+        [0; 16]
+    };
+
+    std::array::from_fn(|i| {
+        let s = subset(i);
+        let (e0, e1) = (endpoints[2 * s], endpoints[2 * s + 1]);
+        // En los modos 4 y 5 el color y el alfa tienen índices separados;
+        // el bit de selección del modo 4 los intercambia
+        let ((color_index, color_index_bits), (alpha_index, alpha_index_bits)) = if mode.index_bits2 == 0 {
+            ((indices[i], mode.index_bits), (indices[i], mode.index_bits))
+        } else if index_selection == 0 {
+            ((indices[i], mode.index_bits), (indices2[i], mode.index_bits2))
+        } else {
+            ((indices2[i], mode.index_bits2), (indices[i], mode.index_bits))
+        };
+        let mut texel = [0u8; 4];
+        for c in 0..3 {
+            texel[c] = bc7_interpolate(e0[c], e1[c], color_index_bits, color_index);
+        }
+        texel[3] = bc7_interpolate(e0[3], e1[3], alpha_index_bits, alpha_index);
+        match rotation {
+            1 => texel.swap(0, 3),
+            2 => texel.swap(1, 3),
+            3 => texel.swap(2, 3),
+            _ => {}
+        }
+        texel
+    })
+}
+
+// --- ETC2 ---
+
+const ETC_MODIFIERS: [[i32; 2]; 8] = [[2, 8], [5, 17], [9, 29], [13, 42], [18, 60], [24, 80], [33, 106], [47, 183]];
+const ETC_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+type Rgb = [i32; 3];
+
+fn extend(value: i32, bits: u32) -> i32 {
+    (value << (8 - bits)) | (value >> (2 * bits - 8))
+}
+
+fn offset(color: Rgb, amount: i32) -> [u8; 4] {
+    let [r, g, b] = color.map(|c| (c + amount).clamp(0, 255) as u8);
+    [r, g, b, 255]
+}
+
+fn etc2_rgb(data: &[u8]) -> Block {
+    etc2_color(data, false)
+}
+
+fn etc2_rgb_a1(data: &[u8]) -> Block {
+    etc2_color(data, true)
+}
+
+fn etc2_rgba(data: &[u8]) -> Block {
+    let alpha = eac_alpha(&data[0..8]);
+    let mut block = etc2_color(&data[8..16], false);
+    for (texel, a) in block.iter_mut().zip(alpha) {
+        texel[3] = a;
+    }
+    block
+}
+
+// Bloque de color de ETC2 (big endian). Con `punchthrough` el bit 33 indica si el
+// bloque es opaco en lugar de elegir el modo, y siempre es diferencial.
+fn etc2_color(data: &[u8], punchthrough: bool) -> Block {
+    let bits = u64::from_be_bytes(data[0..8].try_into().unwrap());
+    let bit = |i: u32| ((bits >> i) & 1) as i32;
+    let field = |high: u32, low: u32| ((bits >> low) & ((1 << (high - low + 1)) - 1)) as i32;
+    let signed3 = |v: i32| if v >= 4 { v - 8 } else { v };
+
+    let differential = punchthrough || bit(33) == 1;
+    let transparent = punchthrough && bit(33) == 0;
+    let flip = bit(32) == 1;
+    // Los índices van por columnas: el píxel (x, y) usa los bits x * 4 + y (+16 el alto)
+    let pixel_index = |x: usize, y: usize| {
+        let p = (x * 4 + y) as u32;
+        ((bit(p + 16) << 1) | bit(p)) as usize
+    };
+    let mut block = [[0u8; 4]; 16];
+
+    let (c1, c2) = if differential {
+        let (r, g, b) = (field(63, 59), field(55, 51), field(47, 43));
+        let (r2, g2, b2) = (r + signed3(field(58, 56)), g + signed3(field(50, 48)), b + signed3(field(42, 40)));
+        if !(0..32).contains(&r2) {
+            // Modo T: un color suelto y tres alrededor del segundo
+            let c1 = [(field(60, 59) << 2) | field(57, 56), field(55, 52), field(51, 48)].map(|c| extend(c, 4));
+            let c2 = [field(47, 44), field(43, 40), field(39, 36)].map(|c| extend(c, 4));
+            let d = ETC_DISTANCES[((field(35, 34) << 1) | bit(32)) as usize];
+            let paint = [offset(c1, 0), offset(c2, d), offset(c2, 0), offset(c2, -d)];
+            return etc2_paint(&paint, transparent, pixel_index);
+        }
+        if !(0..32).contains(&g2) {
+            // Modo H: dos colores, cada uno con ± distancia
+            let c1 = [field(62, 59), (field(58, 56) << 1) | bit(52), (bit(51) << 3) | field(49, 47)].map(|c| extend(c, 4));
+            let c2 = [field(46, 43), field(42, 39), field(38, 35)].map(|c| extend(c, 4));
+            let value = |c: Rgb| (c[0] << 16) | (c[1] << 8) | c[2];
+            let index = (bit(34) << 2) | (bit(32) << 1) | (value(c1) >= value(c2)) as i32;
+            let d = ETC_DISTANCES[index as usize];
+            let paint = [offset(c1, d), offset(c1, -d), offset(c2, d), offset(c2, -d)];
+            return etc2_paint(&paint, transparent, pixel_index);
+        }
+        if !(0..32).contains(&b2) {
+            // Modo planar: degradado entre el origen, el borde horizontal y el vertical
+            let o = [extend(field(62, 57), 6), extend((bit(56) << 6) | field(54, 49), 7),
+                extend((bit(48) << 5) | (field(44, 43) << 3) | field(41, 39), 6)];
+            let h = [extend((field(38, 34) << 1) | bit(32), 6), extend(field(31, 25), 7), extend(field(24, 19), 6)];
+            let v = [extend(field(18, 13), 6), extend(field(12, 6), 7), extend(field(5, 0), 6)];
+            for (i, texel) in block.iter_mut().enumerate() {
+                let (x, y) = ((i % 4) as i32, (i / 4) as i32);
+                let color: Rgb = std::array::from_fn(|c| (x * (h[c] - o[c]) + y * (v[c] - o[c]) + 4 * o[c] + 2) >> 2);
+                *texel = offset(color, 0);
+            }
+            return block;
+        }
+        ([r, g, b].map(|c| extend(c, 5)), [r2, g2, b2].map(|c| extend(c, 5)))
+    } else {
+        (
+            [field(63, 60), field(55, 52), field(47, 44)].map(|c| extend(c, 4)),
+            [field(59, 56), field(51, 48), field(43, 40)].map(|c| extend(c, 4)),
+        )
+    };
+
+    // Modo individual o diferencial: dos sub-bloques de 2x4 (o 4x2 con flip)
+    let tables = [field(39, 37) as usize, field(36, 34) as usize];
+    for (i, texel) in block.iter_mut().enumerate() {
+        let (x, y) = (i % 4, i / 4);
+        let second = if flip { y >= 2 } else { x >= 2 };
+        let (base, table) = if second { (c2, tables[1]) } else { (c1, tables[0]) };
+        let modifiers = ETC_MODIFIERS[table];
+        let index = pixel_index(x, y);
+        if transparent && index == 2 {
+            continue;
+        }
+        let amount = match index {
+            0 if transparent => 0,
+            0 => modifiers[0],
+            1 => modifiers[1],
+            2 => -modifiers[0],
+            _ => -modifiers[1],
+        };
+        *texel = offset(base, amount);
+    }
+    block
+}
+
+// Modos T y H: cada píxel elige uno de los cuatro colores; en punchthrough el 2 es transparente
+fn etc2_paint(paint: &[[u8; 4]; 4], transparent: bool, pixel_index: impl Fn(usize, usize) -> usize) -> Block {
+    std::array::from_fn(|i| {
+        let index = pixel_index(i % 4, i / 4);
+        if transparent && index == 2 {
+            [0; 4]
+        } else {
+            paint[index]
+        }
+    })
+}
+
+// Bloque de alfa EAC de ETC2 RGBA8
+fn eac_alpha(data: &[u8]) -> [u8; 16] {
+    let base = data[0] as i32;
+    let multiplier = (data[1] >> 4) as i32;
+    let table = EAC_MODIFIERS[(data[1] & 15) as usize];
+    let bits = data[2..8].iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+    std::array::from_fn(|i| {
+        let p = (i % 4) * 4 + i / 4;
+        let index = (bits >> (45 - 3 * p)) as usize & 7;
+        (base + table[index] * multiplier).clamp(0, 255) as u8
+    })
+}
+
+// Bloque EAC de 11 bits (R11 y RG11), en 0..2047 o con signo en -1023..1023.
+// Con multiplicador 0 el modificador se usa tal cual, sin escalar por 8.
+fn eac11(data: &[u8], signed: bool) -> [i32; 16] {
+    let base = if signed { (data[0] as i8).max(-127) as i32 * 8 } else { data[0] as i32 * 8 + 4 };
+    let multiplier = (data[1] >> 4) as i32;
+    let table = EAC_MODIFIERS[(data[1] & 15) as usize];
+    let bits = data[2..8].iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+    let (min, max) = if signed { (-1023, 1023) } else { (0, 2047) };
+    std::array::from_fn(|i| {
+        let p = (i % 4) * 4 + i / 4;
+        let modifier = table[(bits >> (45 - 3 * p)) as usize & 7];
+        let delta = if multiplier == 0 { modifier } else { modifier * multiplier * 8 };
+        (base + delta).clamp(min, max)
+    })
+}
+
+fn eac_unorm8(value: i32) -> u8 {
+    ((value * 255 + 1023) / 2047) as u8
+}
+
+fn eac_snorm8(value: i32) -> u8 {
+    (value as f32 * 127.0 / 1023.0).round() as i8 as u8
+}
+
+fn eac_r11(data: &[u8]) -> Block {
+    eac11(data, false).map(|r| [eac_unorm8(r), 0, 0, 255])
+}
+
+fn eac_r11_snorm(data: &[u8]) -> Block {
+    eac11(data, true).map(|r| [eac_snorm8(r), 0, 0, 127])
+}
+
+fn eac_rg11(data: &[u8]) -> Block {
+    let (r, g) = (eac11(&data[0..8], false), eac11(&data[8..16], false));
+    std::array::from_fn(|i| [eac_unorm8(r[i]), eac_unorm8(g[i]), 0, 255])
+}
+
+fn eac_rg11_snorm(data: &[u8]) -> Block {
+    let (r, g) = (eac11(&data[0..8], true), eac11(&data[8..16], true));
+    std::array::from_fn(|i| [eac_snorm8(r[i]), eac_snorm8(g[i]), 0, 127])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED_565: [u8; 2] = [0x00, 0xf8];
+    const BLUE_565: [u8; 2] = [0x1f, 0x00];
+
+    // Bloque de 16 bytes con los bits escritos del menos significativo al más
+    fn bits_le(fields: &[(u32, u32)]) -> [u8; 16] {
+        let mut bits = 0u128;
+        let mut position = 0;
+        for &(value, count) in fields {
+            bits |= (value as u128) << position;
+            position += count;
+        }
+        bits.to_le_bytes()
+    }
+
+    #[test]
+    fn bc1_opaque_palette() {
+        let data = [RED_565[0], RED_565[1], BLUE_565[0], BLUE_565[1], 0b11_10_01_00, 0, 0, 0];
+        let block = bc1(&data);
+        assert_eq!(block[0], [255, 0, 0, 255]);
+        assert_eq!(block[1], [0, 0, 255, 255]);
+        assert_eq!(block[2], [170, 0, 85, 255]);
+        assert_eq!(block[3], [85, 0, 170, 255]);
+        assert_eq!(block[15], [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc1_transparent_palette() {
+        // c0 <= c1: tres colores y el índice 3 transparente
+        let data = [BLUE_565[0], BLUE_565[1], RED_565[0], RED_565[1], 0b10_00_11, 0, 0, 0];
+        let block = bc1(&data);
+        assert_eq!(block[0], [0, 0, 0, 0]);
+        assert_eq!(block[1], [0, 0, 255, 255]);
+        assert_eq!(block[2], [127, 0, 127, 255]);
+    }
+
+    #[test]
+    fn bc2_explicit_alpha_and_four_colors() {
+        let mut data = [0u8; 16];
+        data[0] = 0x8f;
+        data[8..16].copy_from_slice(&[BLUE_565[0], BLUE_565[1], RED_565[0], RED_565[1], 0b11, 0, 0, 0]);
+        let block = bc2(&data);
+        // Sin transparencia aunque c0 <= c1
+        assert_eq!(block[0], [170, 0, 85, 255]);
+        assert_eq!(block[1], [0, 0, 255, 136]);
+        assert_eq!(block[2][3], 0);
+    }
+
+    #[test]
+    fn bc3_interpolated_alpha() {
+        let mut data = [0u8; 16];
+        data[0..3].copy_from_slice(&[255, 0, 0x0a]);
+        data[8..12].copy_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+        let block = bc3(&data);
+        assert_eq!(block[0], [255, 255, 255, 218]);
+        assert_eq!(block[1], [255, 255, 255, 0]);
+        assert_eq!(block[2], [255, 255, 255, 255]);
+    }
+
+    // Índices 2, 7 y 6 en los tres primeros píxeles
+    const ALPHA_INDICES: [u8; 6] = [0xba, 0x01, 0, 0, 0, 0];
+
+    #[test]
+    fn bc4_six_value_palette() {
+        let mut data = [0, 255, 0, 0, 0, 0, 0, 0];
+        data[2..8].copy_from_slice(&ALPHA_INDICES);
+        let block = bc4(&data);
+        assert_eq!(block[0], [51, 0, 0, 255]);
+        assert_eq!(block[1], [255, 0, 0, 255]);
+        assert_eq!(block[2], [0, 0, 0, 255]);
+        assert_eq!(block[3], [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc4_snorm_signed_palette() {
+        // -128 se lee como -127
+        let mut data = [0x80, 0x7f, 0, 0, 0, 0, 0, 0];
+        data[2..8].copy_from_slice(&ALPHA_INDICES);
+        let block = bc4_snorm(&data);
+        assert_eq!(block[0], [-76i8 as u8, 0, 0, 127]);
+        assert_eq!(block[1], [127, 0, 0, 127]);
+        assert_eq!(block[2], [-127i8 as u8, 0, 0, 127]);
+        assert_eq!(block[3], [-127i8 as u8, 0, 0, 127]);
+    }
+
+    #[test]
+    fn bc5_two_channels() {
+        let mut data = [0u8; 16];
+        data[0..2].copy_from_slice(&[0, 255]);
+        data[2..8].copy_from_slice(&ALPHA_INDICES);
+        data[8..10].copy_from_slice(&[100, 100]);
+        let block = bc5(&data);
+        assert_eq!(block[0], [51, 100, 0, 255]);
+        assert_eq!(block[1], [255, 100, 0, 255]);
+    }
+
+    #[test]
+    fn bc5_snorm_two_channels() {
+        let mut data = [0u8; 16];
+        data[0..2].copy_from_slice(&[0x80, 0x7f]);
+        data[2..8].copy_from_slice(&ALPHA_INDICES);
+        data[8..10].copy_from_slice(&[16, 16]);
+        let block = bc5_snorm(&data);
+        assert_eq!(block[0], [-76i8 as u8, 16, 0, 127]);
+        assert_eq!(block[1], [127, 16, 0, 127]);
+    }
+
+    #[test]
+    fn bc7_mode_6() {
+        // Modo 6: extremos RGBA de 7 bits + p-bit, índices de 4 bits
+        let data = bits_le(&[
+            (1 << 6, 7),
+            (127, 7), (0, 7), (127, 7), (0, 7), (127, 7), (0, 7), (127, 7), (0, 7),
+            (1, 1), (0, 1),
+            (0, 3), (15, 4), (8, 4),
+        ]);
+        let block = bc7(&data);
+        assert_eq!(block[0], [255, 255, 255, 255]);
+        assert_eq!(block[1], [0, 0, 0, 0]);
+        assert_eq!(block[2], [120, 120, 120, 120]);
+        assert_eq!(block[3], [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn bc7_invalid_mode_is_transparent_black() {
+        assert_eq!(bc7(&[0; 16]), [[0; 4]; 16]);
+    }
+
+    #[test]
+    fn etc2_individual_mode() {
+        // Sub-bloques 2x4 con 0x88 y 0x00, tabla 0 (±2, ±8)
+        let data = [0x80, 0x80, 0x80, 0x00, 0x00, 0x01, 0x80, 0x01];
+        let block = etc2_rgb(&data);
+        assert_eq!(block[0], [128, 128, 128, 255]);
+        assert_eq!(block[1], [138, 138, 138, 255]);
+        assert_eq!(block[2], [2, 2, 2, 255]);
+        assert_eq!(block[15], [8, 8, 8, 255]);
+    }
+
+    #[test]
+    fn etc2_differential_mode() {
+        let data = [0x80, 0x80, 0x80, 0x02, 0, 0, 0, 0];
+        assert_eq!(etc2_rgb(&data), [[134, 134, 134, 255]; 16]);
+    }
+
+    #[test]
+    fn etc2_punchthrough_alpha() {
+        // Bit opaco en 0: el índice 2 es transparente y el 0 no modifica el color
+        let data = [0x80, 0x80, 0x80, 0x00, 0x00, 0x01, 0x00, 0x00];
+        let block = etc2_rgb_a1(&data);
+        assert_eq!(block[0], [0, 0, 0, 0]);
+        assert_eq!(block[1], [132, 132, 132, 255]);
+
+        let opaque = [0x80, 0x80, 0x80, 0x02, 0, 0, 0, 0];
+        assert_eq!(etc2_rgb_a1(&opaque), [[134, 134, 134, 255]; 16]);
+    }
+
+    #[test]
+    fn etc2_rgba_with_eac_alpha() {
+        let data = [128, 0x10, 0xe0, 0, 0, 0, 0, 0, 0x80, 0x80, 0x80, 0x02, 0, 0, 0, 0];
+        let block = etc2_rgba(&data);
+        assert_eq!(block[0], [134, 134, 134, 142]);
+        assert_eq!(block[1], [134, 134, 134, 125]);
+    }
+
+    const EAC_R11: [u8; 8] = [128, 0x10, 0xe0, 0, 0, 0, 0, 0];
+
+    #[test]
+    fn decodes_eac_r11_unorm() {
+        let block = eac_r11(&EAC_R11);
+        assert_eq!(block[0], [142, 0, 0, 255]);
+        assert_eq!(block[1], [125, 0, 0, 255]);
+
+        // Se satura en 2047
+        let bright = eac_r11(&[255, 0xf0, 0xe0, 0, 0, 0, 0, 0]);
+        assert_eq!(bright[0], [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn decodes_eac_r11_snorm() {
+        let block = eac_r11_snorm(&[0x80, 0x10, 0xe0, 0, 0, 0, 0, 0]);
+        assert_eq!(block[0], [-112i8 as u8, 0, 0, 127]);
+        // -1040 se satura en -1023
+        assert_eq!(block[1], [-127i8 as u8, 0, 0, 127]);
+
+        let positive = eac_r11_snorm(&[64, 0x10, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(positive[0], [61, 0, 0, 127]);
+    }
+
+    #[test]
+    fn decodes_eac_rg11_unorm() {
+        // Multiplicador 0 en el verde: el modificador no se escala
+        let mut data = [0u8; 16];
+        data[0..8].copy_from_slice(&EAC_R11);
+        data[8..10].copy_from_slice(&[128, 0x00]);
+        let block = eac_rg11(&data);
+        assert_eq!(block[0], [142, 128, 0, 255]);
+        assert_eq!(block[1], [125, 128, 0, 255]);
+    }
+
+    #[test]
+    fn decodes_eac_rg11_snorm() {
+        let mut data = [0u8; 16];
+        data[0..3].copy_from_slice(&[0x80, 0x10, 0xe0]);
+        data[8..10].copy_from_slice(&[64, 0x10]);
+        let block = eac_rg11_snorm(&data);
+        assert_eq!(block[0], [-112i8 as u8, 61, 0, 127]);
+        assert_eq!(block[1], [-127i8 as u8, 61, 0, 127]);
+    }
+
+    #[test]
+    fn decode_crops_edge_blocks() {
+        let data = [RED_565[0], RED_565[1], BLUE_565[0], BLUE_565[1], 0b01_00, 0b01, 0, 0];
+        let pixels = decode(wgpu::TextureFormat::Bc1RgbaUnorm, 2, 2, &data).unwrap();
+        assert_eq!(pixels.len(), 2 * 2 * 4);
+        assert_eq!(pixels[4..8], [0, 0, 255, 255]);
+        assert_eq!(pixels[8..12], [0, 0, 255, 255]);
+        assert_eq!(pixels[12..16], [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn decode_rejects_short_data_and_unknown_formats() {
+        assert!(decode(wgpu::TextureFormat::Bc1RgbaUnorm, 8, 4, &[0; 8]).is_err());
+        let astc = wgpu::TextureFormat::Astc { block: wgpu::AstcBlock::B4x4, channel: wgpu::AstcChannel::Unorm };
+        assert!(!can_decode(astc));
+        assert!(!can_decode(wgpu::TextureFormat::Bc6hRgbUfloat));
+        assert!(decode(astc, 4, 4, &[0; 16]).is_err());
+        assert!(can_decode(wgpu::TextureFormat::EacRg11Snorm));
+        assert!(is_snorm(wgpu::TextureFormat::Bc5RgSnorm));
+    }
+}
@@ -0,0 +1,213 @@
+// Compresores de bloques BC1 y BC3 para cubo-cook. Son rápidos y sencillos: los
+// extremos salen del eje principal de los colores del bloque, sin refinamiento.
+
+use image::RgbaImage;
+
+use crate::block_decode::{mix, rgb565};
+
+type Block = [[u8; 4]; 16];
+
+// Comprime una imagen completa; los bloques del borde repiten el último píxel
+pub(crate) fn encode(image: &RgbaImage, with_alpha: bool) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+    let (blocks_x, blocks_y) = (width.div_ceil(4), height.div_ceil(4));
+    let mut out = Vec::with_capacity((blocks_x * blocks_y) as usize * if with_alpha { 16 } else { 8 });
+    for by in 0..blocks_y {
+        for bx in 0..blocks_x {
+            let block: Block = std::array::from_fn(|i| {
+                let x = (bx * 4 + i as u32 % 4).min(width - 1);
+                let y = (by * 4 + i as u32 / 4).min(height - 1);
+                image.get_pixel(x, y).0
+            });
+            if with_alpha {
+                out.extend(encode_alpha(&block));
+                out.extend(encode_color(&block, false));
+            } else {
+                out.extend(encode_color(&block, true));
+            }
+        }
+    }
+    out
+}
+
+fn to565(c: [f32; 3]) -> u16 {
+    let r = (c[0].clamp(0.0, 255.0) * 31.0 / 255.0).round() as u16;
+    let g = (c[1].clamp(0.0, 255.0) * 63.0 / 255.0).round() as u16;
+    let b = (c[2].clamp(0.0, 255.0) * 31.0 / 255.0).round() as u16;
+    (r << 11) | (g << 5) | b
+}
+
+fn distance(a: [u8; 4], b: [u8; 4]) -> u32 {
+    (0..3).map(|c| (a[c] as i32 - b[c] as i32).pow(2) as u32).sum()
+}
+
+// Bloque de color de BC1. Con `punchthrough` los píxeles con alfa < 128 usan el
+// índice transparente del modo de 3 colores.
+fn encode_color(block: &Block, punchthrough: bool) -> [u8; 8] {
+    let transparent = |texel: &[u8; 4]| punchthrough && texel[3] < 128;
+    let opaque: Vec<[f32; 3]> = block
+        .iter()
+        .filter(|texel| !transparent(texel))
+        .map(|t| [t[0] as f32, t[1] as f32, t[2] as f32])
+        .collect();
+    if opaque.is_empty() {
+        // Todo transparente: modo de 3 colores con todos los índices en 3
+        return [0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff];
+    }
+
+    // Eje principal por iteración de potencia sobre la covarianza
+    let n = opaque.len() as f32;
+    let mean: [f32; 3] = std::array::from_fn(|c| opaque.iter().map(|p| p[c]).sum::<f32>() / n);
+    let mut covariance = [[0.0f32; 3]; 3];
+    for p in &opaque {
+        for i in 0..3 {
+            for j in 0..3 {
+                covariance[i][j] += (p[i] - mean[i]) * (p[j] - mean[j]);
+            }
+        }
+    }
+    let mut axis = [1.0f32, 1.0, 1.0];
+    for _ in 0..8 {
+        let next: [f32; 3] = std::array::from_fn(|i| (0..3).map(|j| covariance[i][j] * axis[j]).sum());
+        let length = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length < 1e-6 {
+            break;
+        }
+        axis = next.map(|v| v / length);
+    }
+    let project = |p: &[f32; 3]| (0..3).map(|c| (p[c] - mean[c]) * axis[c]).sum::<f32>();
+    let (min, max) = opaque.iter().map(project).fold((f32::MAX, f32::MIN), |(lo, hi), t| (lo.min(t), hi.max(t)));
+    let endpoint = |t: f32| to565(std::array::from_fn(|c| mean[c] + axis[c] * t));
+
+    let has_transparent = block.iter().any(transparent);
+    let (mut c0, mut c1) = (endpoint(max), endpoint(min));
+    // c0 > c1 elige 4 colores, c0 <= c1 elige 3 colores más transparente
+    if (c0 < c1) != has_transparent && c0 != c1 {
+        std::mem::swap(&mut c0, &mut c1);
+    }
+    if !has_transparent && c0 == c1 {
+        // Un solo color: con c0 == c1 el decodificador usaría 3 colores, da igual el índice
+        let bytes = [c0.to_le_bytes(), c1.to_le_bytes()].concat();
+        return [bytes[0], bytes[1], bytes[2], bytes[3], 0, 0, 0, 0];
+    }
+
+    let (e0, e1) = (rgb565(c0), rgb565(c1));
+    let palette = if c0 > c1 {
+        vec![e0, e1, mix(e0, e1, 2, 1), mix(e0, e1, 1, 2)]
+    } else {
+        vec![e0, e1, mix(e0, e1, 1, 1)]
+    };
+    let mut indices = 0u32;
+    for (i, texel) in block.iter().enumerate() {
+        let index = if transparent(texel) {
+            3
+        } else {
+            (0..palette.len()).min_by_key(|&k| distance(palette[k], *texel)).unwrap() as u32
+        };
+        indices |= index << (2 * i);
+    }
+
+    let mut out = [0u8; 8];
+    out[0..2].copy_from_slice(&c0.to_le_bytes());
+    out[2..4].copy_from_slice(&c1.to_le_bytes());
+    out[4..8].copy_from_slice(&indices.to_le_bytes());
+    out
+}
+
+// Bloque de alfa de BC3 con los extremos en el mínimo y el máximo (modo de 8 valores)
+fn encode_alpha(block: &Block) -> [u8; 8] {
+    let a0 = block.iter().map(|t| t[3]).max().unwrap();
+    let a1 = block.iter().map(|t| t[3]).min().unwrap();
+    let mut out = [a0, a1, 0, 0, 0, 0, 0, 0];
+    if a0 == a1 {
+        return out;
+    }
+    let palette: [u32; 8] = std::array::from_fn(|i| match i {
+        0 => a0 as u32,
+        1 => a1 as u32,
+        i => ((8 - i as u32) * a0 as u32 + (i as u32 - 1) * a1 as u32) / 7,
+    });
+    let mut bits = 0u64;
+    for (i, texel) in block.iter().enumerate() {
+        let index = (0..8).min_by_key(|&k| palette[k].abs_diff(texel[3] as u32)).unwrap() as u64;
+        bits |= index << (3 * i);
+    }
+    out[2..8].copy_from_slice(&bits.to_le_bytes()[0..6]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_decode;
+
+    fn round_trip(image: &RgbaImage, with_alpha: bool) -> RgbaImage {
+        let format = if with_alpha { wgpu::TextureFormat::Bc3RgbaUnorm } else { wgpu::TextureFormat::Bc1RgbaUnorm };
+        let data = encode(image, with_alpha);
+        let pixels = block_decode::decode(format, image.width(), image.height(), &data).unwrap();
+        RgbaImage::from_raw(image.width(), image.height(), pixels).unwrap()
+    }
+
+    #[test]
+    fn solid_565_colors_are_exact() {
+        for color in [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255], [0, 0, 0, 255]] {
+            let image = RgbaImage::from_pixel(8, 8, image::Rgba(color));
+            assert_eq!(round_trip(&image, false), image);
+            assert_eq!(round_trip(&image, true), image);
+        }
+    }
+
+    #[test]
+    fn two_color_blocks_are_exact() {
+        let image = RgbaImage::from_fn(8, 4, |x, y| {
+            if (x + y) % 2 == 0 { image::Rgba([255, 255, 255, 255]) } else { image::Rgba([0, 0, 0, 255]) }
+        });
+        assert_eq!(round_trip(&image, false), image);
+    }
+
+    #[test]
+    fn partial_blocks_keep_the_image_size() {
+        let image = RgbaImage::from_pixel(6, 5, image::Rgba([0, 0, 255, 255]));
+        let data = encode(&image, false);
+        assert_eq!(data.len(), 2 * 2 * 8);
+        assert_eq!(round_trip(&image, false), image);
+    }
+
+    #[test]
+    fn bc1_punchthrough_alpha() {
+        let image = RgbaImage::from_fn(4, 4, |x, _| {
+            if x < 2 { image::Rgba([255, 0, 0, 255]) } else { image::Rgba([0, 0, 255, 10]) }
+        });
+        let decoded = round_trip(&image, false);
+        assert_eq!(decoded.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(decoded.get_pixel(3, 3).0, [0, 0, 0, 0]);
+
+        let transparent = RgbaImage::from_pixel(4, 4, image::Rgba([10, 20, 30, 0]));
+        assert!(round_trip(&transparent, false).pixels().all(|p| p.0 == [0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn bc3_alpha_endpoints_are_exact_and_steps_are_close() {
+        let image = RgbaImage::from_fn(4, 4, |x, y| image::Rgba([255, 255, 255, ((y * 4 + x) * 17) as u8]));
+        let decoded = round_trip(&image, true);
+        assert_eq!(decoded.get_pixel(0, 0).0[3], 0);
+        assert_eq!(decoded.get_pixel(3, 3).0[3], 255);
+        for (original, decoded) in image.pixels().zip(decoded.pixels()) {
+            // Ocho niveles entre 0 y 255: a lo sumo medio paso de error
+            assert!(original.0[3].abs_diff(decoded.0[3]) <= 19);
+            assert_eq!(decoded.0[0..3], [255, 255, 255]);
+        }
+    }
+
+    #[test]
+    fn gradients_stay_close() {
+        let image = RgbaImage::from_fn(16, 16, |x, y| image::Rgba([(x * 16) as u8, (y * 16) as u8, 128, 255]));
+        let decoded = round_trip(&image, false);
+        let error: u32 = image
+            .pixels()
+            .zip(decoded.pixels())
+            .map(|(a, b)| (0..3).map(|c| a.0[c].abs_diff(b.0[c]) as u32).sum::<u32>())
+            .sum();
+        assert!(error / (16 * 16 * 3) <= 8, "error medio {}", error / (16 * 16 * 3));
+    }
+}
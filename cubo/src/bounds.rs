@@ -0,0 +1,246 @@
+use cgmath::{InnerSpace, Matrix4, Point3, Vector3, Vector4};
+
+// Caja alineada a los ejes (AABB)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    pub fn from_points<I>(points: I) -> Self
+    where
+        I: IntoIterator<Item = [f32; 3]>,
+    {
+        let mut min = Point3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = Point3::new(f32::MIN, f32::MIN, f32::MIN);
+        for [x, y, z] in points {
+            min.x = min.x.min(x);
+            min.y = min.y.min(y);
+            min.z = min.z.min(z);
+            max.x = max.x.max(x);
+            max.y = max.y.max(y);
+            max.z = max.z.max(z);
+        }
+        Self { min, max }
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        Point3::new(
+            (self.min.x + self.max.x) * 0.5,
+            (self.min.y + self.max.y) * 0.5,
+            (self.min.z + self.max.z) * 0.5,
+        )
+    }
+
+    pub fn extents(&self) -> Vector3<f32> {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn corners(&self) -> [Point3<f32>; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Point3::new(a.x, a.y, a.z),
+            Point3::new(b.x, a.y, a.z),
+            Point3::new(a.x, b.y, a.z),
+            Point3::new(b.x, b.y, a.z),
+            Point3::new(a.x, a.y, b.z),
+            Point3::new(b.x, a.y, b.z),
+            Point3::new(a.x, b.y, b.z),
+            Point3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    // AABB que contiene a esta caja después de aplicar la transformación
+    pub fn transformed(&self, model: &Matrix4<f32>) -> Self {
+        Self::from_points(self.corners().iter().map(|c| {
+            let p = model * c.to_homogeneous();
+            [p.x / p.w, p.y / p.w, p.z / p.w]
+        }))
+    }
+}
+
+// Esfera envolvente
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Point3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    // Esfera centrada en el AABB de los puntos, con el radio del punto más lejano
+    pub fn from_points<I>(points: I) -> Self
+    where
+        I: IntoIterator<Item = [f32; 3]> + Clone,
+    {
+        let center = Aabb::from_points(points.clone()).center();
+        let radius = points
+            .into_iter()
+            .map(|p| (Point3::from(p) - center).magnitude())
+            .fold(0.0, f32::max);
+        Self { center, radius }
+    }
+
+    pub fn transformed(&self, model: &Matrix4<f32>) -> Self {
+        let c = model * self.center.to_homogeneous();
+        // El radio se escala con el mayor factor de escala de la matriz
+        let scale = model.x.truncate().magnitude()
+            .max(model.y.truncate().magnitude())
+            .max(model.z.truncate().magnitude());
+        Self {
+            center: Point3::new(c.x / c.w, c.y / c.w, c.z / c.w),
+            radius: self.radius * scale,
+        }
+    }
+}
+
+// Volumen de visión definido por 6 planos (a, b, c, d) con normales hacia adentro
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum {
+    pub planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    // Extrae los planos de la matriz view_proj (Gribb-Hartmann).
+    // wgpu recorta con 0 <= z <= w, así que el plano cercano es la tercera fila.
+    pub fn from_view_proj(view_proj: &Matrix4<f32>) -> Self {
+        let row = |i: usize| Vector4::new(view_proj.x[i], view_proj.y[i], view_proj.z[i], view_proj.w[i]);
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+        let mut planes = [
+            r3 + r0, // izquierda
+            r3 - r0, // derecha
+            r3 + r1, // abajo
+            r3 - r1, // arriba
+            r2,      // cerca
+            r3 - r2, // lejos
+        ];
+        for plane in planes.iter_mut() {
+            let len = plane.truncate().magnitude();
+            if len > 0.0 {
+                *plane /= len;
+            }
+        }
+        Self { planes }
+    }
+
+    fn distance(plane: &Vector4<f32>, p: Point3<f32>) -> f32 {
+        plane.x * p.x + plane.y * p.y + plane.z * p.z + plane.w
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| Self::distance(plane, sphere.center) >= -sphere.radius)
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let center = aabb.center();
+        let extents = aabb.extents();
+        self.planes.iter().all(|plane| {
+            // Radio proyectado de la caja sobre la normal del plano
+            let r = extents.x * plane.x.abs() + extents.y * plane.y.abs() + extents.z * plane.z.abs();
+            Self::distance(plane, center) >= -r
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{ortho, perspective, Deg};
+
+    // cgmath proyecta z a -1..1; wgpu espera 0..1
+    #[rustfmt::skip]
+    const OPENGL_TO_WGPU: Matrix4<f32> = Matrix4::new(
+        1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 0.5, 0.0,
+        0.0, 0.0, 0.5, 1.0,
+    );
+
+    // Cámara en el origen mirando hacia -Z: ve x e y en [-1, 1] y z en [-10, -1]
+    fn box_frustum() -> Frustum {
+        Frustum::from_view_proj(&(OPENGL_TO_WGPU * ortho(-1.0, 1.0, -1.0, 1.0, 1.0, 10.0)))
+    }
+
+    fn sphere(center: [f32; 3], radius: f32) -> BoundingSphere {
+        BoundingSphere { center: Point3::from(center), radius }
+    }
+
+    fn aabb(center: [f32; 3], half: f32) -> Aabb {
+        let [x, y, z] = center;
+        Aabb { min: Point3::new(x - half, y - half, z - half), max: Point3::new(x + half, y + half, z + half) }
+    }
+
+    // Un punto justo afuera de cada plano: izquierda, derecha, abajo, arriba, cerca, lejos
+    const OUTSIDE: [[f32; 3]; 6] = [
+        [-1.5, 0.0, -5.0],
+        [1.5, 0.0, -5.0],
+        [0.0, -1.5, -5.0],
+        [0.0, 1.5, -5.0],
+        [0.0, 0.0, -0.5],
+        [0.0, 0.0, -10.5],
+    ];
+
+    #[test]
+    fn points_inside_and_outside_each_plane() {
+        let frustum = box_frustum();
+        assert!(frustum.intersects_sphere(&sphere([0.0, 0.0, -5.0], 0.0)));
+        assert!(frustum.intersects_sphere(&sphere([0.9, -0.9, -9.9], 0.0)));
+        for point in OUTSIDE {
+            assert!(!frustum.intersects_sphere(&sphere(point, 0.0)), "{:?}", point);
+            assert!(!frustum.intersects_aabb(&aabb(point, 0.0)), "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn spheres_and_boxes_straddling_each_plane_are_kept() {
+        let frustum = box_frustum();
+        for point in OUTSIDE {
+            // A 0.5 del plano: con radio 1 lo cruzan, con 0.25 quedan afuera
+            assert!(frustum.intersects_sphere(&sphere(point, 1.0)), "{:?}", point);
+            assert!(!frustum.intersects_sphere(&sphere(point, 0.25)), "{:?}", point);
+            assert!(frustum.intersects_aabb(&aabb(point, 1.0)), "{:?}", point);
+            assert!(!frustum.intersects_aabb(&aabb(point, 0.25)), "{:?}", point);
+        }
+        // Más grande que todo el frustum
+        assert!(frustum.intersects_aabb(&aabb([0.0, 0.0, -5.0], 100.0)));
+    }
+
+    #[test]
+    fn perspective_near_plane_uses_zero_to_one_depth() {
+        let frustum = Frustum::from_view_proj(&(OPENGL_TO_WGPU * perspective(Deg(90.0), 1.0, 1.0, 10.0)));
+        assert!(frustum.intersects_sphere(&sphere([0.0, 0.0, -1.5], 0.0)));
+        assert!(frustum.intersects_sphere(&sphere([4.5, 0.0, -5.0], 0.0)));
+        assert!(!frustum.intersects_sphere(&sphere([0.0, 0.0, -0.5], 0.0)));
+        assert!(!frustum.intersects_sphere(&sphere([5.5, 0.0, -5.0], 0.0)));
+        assert!(!frustum.intersects_sphere(&sphere([0.0, 0.0, -11.0], 0.0)));
+    }
+
+    #[test]
+    fn transformed_aabb_contains_the_moved_corners() {
+        let unit = Aabb::from_points([[-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]]);
+        let moved = unit.transformed(&(Matrix4::from_translation(Vector3::new(2.0, 0.0, -3.0)) * Matrix4::from_scale(2.0)));
+        assert_eq!(moved, aabb([2.0, 0.0, -3.0], 2.0));
+
+        // Girada 45° en Y la caja crece a media diagonal en X y Z
+        let rotated = unit.transformed(&Matrix4::from_angle_y(Deg(45.0)));
+        let half = 2.0f32.sqrt();
+        assert!((rotated.max.x - half).abs() < 1e-5 && (rotated.min.z + half).abs() < 1e-5);
+        assert!((rotated.max.y - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn bounding_sphere_covers_the_points_and_scales() {
+        let points = [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [1.0, 1.0, 0.0]];
+        let s = BoundingSphere::from_points(points);
+        assert_eq!(s.center, Point3::new(1.0, 0.5, 0.0));
+        assert!(points.iter().all(|p| (Point3::from(*p) - s.center).magnitude() <= s.radius + 1e-6));
+
+        let model = Matrix4::from_translation(Vector3::new(0.0, 3.0, 0.0)) * Matrix4::from_nonuniform_scale(1.0, 3.0, 2.0);
+        let t = s.transformed(&model);
+        assert_eq!(t.center, Point3::new(1.0, 4.5, 0.0));
+        assert!((t.radius - s.radius * 3.0).abs() < 1e-6);
+    }
+}
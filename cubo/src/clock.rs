@@ -0,0 +1,249 @@
+use std::time::Instant;
+
+// Delta máximo en tiempo real: evita saltos después de suspender la máquina
+// o de un frame muy lento
+const MAX_REAL_TIME_DELTA: f64 = 0.25;
+// Paso usado al avanzar un frame manualmente si el reloj no es de paso fijo
+const DEFAULT_STEP: f64 = 1.0 / 60.0;
+// Escala de tiempo mínima: con 0 las teclas [ y ], que multiplican, no podrían
+// recuperarla. Para detener el tiempo está la pausa.
+pub const MIN_TIME_SCALE: f64 = 1.0 / 64.0;
+pub const MAX_TIME_SCALE: f64 = 64.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClockMode {
+    // Avanza con el tiempo de pared entre frames
+    RealTime,
+    // Avanza exactamente `step` segundos por frame, reproducible
+    FixedStep { step: f64 },
+    // No avanza salvo con step()
+    Paused,
+}
+
+// Fuente de tiempo de las animaciones, separada del reloj del sistema
+pub struct AnimationClock {
+    mode: ClockMode,
+    // Modo al que vuelve resume() después de pause()
+    resume_mode: ClockMode,
+    scale: f64,
+    time: f64,
+    frame: u64,
+    last_instant: Option<Instant>,
+    pending_steps: u32,
+    // Con paso fijo el tiempo se calcula como anchor + pasos * paso en lugar de
+    // sumar el paso en cada frame, así no acumula error de redondeo. Se vuelve a
+    // anclar cada vez que cambian el modo o la escala.
+    anchor: f64,
+    fixed_steps: u64,
+}
+
+impl AnimationClock {
+    pub fn new(mode: ClockMode) -> Self {
+        Self {
+            mode,
+            resume_mode: if mode == ClockMode::Paused { ClockMode::RealTime } else { mode },
+            scale: 1.0,
+            time: 0.0,
+            frame: 0,
+            last_instant: None,
+            pending_steps: 0,
+            anchor: 0.0,
+            fixed_steps: 0,
+        }
+    }
+
+    pub fn real_time() -> Self {
+        Self::new(ClockMode::RealTime)
+    }
+
+    // fps tiene que ser mayor que 0; quien lee la opción avisa si no lo es
+    pub fn fixed_step(fps: u32) -> Self {
+        Self::new(ClockMode::FixedStep { step: 1.0 / fps.max(1) as f64 })
+    }
+
+    // Avanza un frame y devuelve cuántos segundos de animación pasaron
+    pub fn tick(&mut self) -> f64 {
+        let now = Instant::now();
+        let real_delta = self
+            .last_instant
+            .map_or(0.0, |last| (now - last).as_secs_f64().min(MAX_REAL_TIME_DELTA));
+        self.last_instant = Some(now);
+
+        let delta = match self.mode {
+            ClockMode::RealTime => real_delta,
+            ClockMode::FixedStep { step } => {
+                self.fixed_steps += 1;
+                let time = self.anchor + self.fixed_steps as f64 * step * self.scale;
+                let delta = time - self.time;
+                self.time = time;
+                self.frame += 1;
+                return delta;
+            }
+            ClockMode::Paused if self.pending_steps > 0 => {
+                self.pending_steps -= 1;
+                match self.resume_mode {
+                    ClockMode::FixedStep { step } => step,
+                    _ => DEFAULT_STEP,
+                }
+            }
+            ClockMode::Paused => return 0.0,
+        } * self.scale;
+
+        self.time += delta;
+        self.frame += 1;
+        self.reanchor();
+        delta
+    }
+
+    fn reanchor(&mut self) {
+        self.anchor = self.time;
+        self.fixed_steps = 0;
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn mode(&self) -> ClockMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ClockMode) {
+        if mode != ClockMode::Paused {
+            self.resume_mode = mode;
+        }
+        self.mode = mode;
+        self.reanchor();
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = if scale.is_nan() { 1.0 } else { scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE) };
+        self.reanchor();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.mode == ClockMode::Paused
+    }
+
+    pub fn pause(&mut self) {
+        self.mode = ClockMode::Paused;
+        self.reanchor();
+    }
+
+    pub fn resume(&mut self) {
+        self.mode = self.resume_mode;
+        self.reanchor();
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.is_paused() {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    // Avanza un solo frame en el próximo tick; pausa el reloj si estaba corriendo
+    pub fn step(&mut self) {
+        self.pause();
+        self.pending_steps += 1;
+    }
+
+    // Vuelve al instante 0 sin cambiar de modo
+    pub fn reset(&mut self) {
+        self.time = 0.0;
+        self.frame = 0;
+        self.last_instant = None;
+        self.pending_steps = 0;
+        self.reanchor();
+    }
+}
+
+impl Default for AnimationClock {
+    fn default() -> Self {
+        Self::real_time()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(clock: &mut AnimationClock, frames: u32) {
+        for _ in 0..frames {
+            clock.tick();
+        }
+    }
+
+    #[test]
+    fn fixed_steps_advance_exactly_n_over_fps() {
+        for fps in [24, 30, 60, 144] {
+            let mut clock = AnimationClock::fixed_step(fps);
+            run(&mut clock, fps * 60);
+            assert_eq!(clock.time(), 60.0);
+            assert_eq!(clock.frame(), fps as u64 * 60);
+
+            let mut clock = AnimationClock::fixed_step(fps);
+            run(&mut clock, 7);
+            assert_eq!(clock.time(), 7.0 * (1.0 / fps as f64));
+        }
+    }
+
+    #[test]
+    fn minimum_scale_is_clamped_and_does_not_drift() {
+        let mut clock = AnimationClock::fixed_step(60);
+        clock.set_scale(0.0);
+        assert_eq!(clock.scale(), MIN_TIME_SCALE);
+        run(&mut clock, 60 * 64);
+        assert_eq!(clock.time(), 1.0);
+
+        clock.set_scale(f64::NAN);
+        assert_eq!(clock.scale(), 1.0);
+        clock.set_scale(1000.0);
+        assert_eq!(clock.scale(), MAX_TIME_SCALE);
+    }
+
+    #[test]
+    fn pause_and_single_steps_do_not_drift() {
+        let mut clock = AnimationClock::fixed_step(60);
+        run(&mut clock, 30);
+        clock.pause();
+        run(&mut clock, 100);
+        assert_eq!(clock.time(), 0.5);
+        assert_eq!(clock.frame(), 30);
+
+        // Cada step() avanza exactamente un paso del modo al que se vuelve
+        for _ in 0..30 {
+            clock.step();
+            clock.tick();
+        }
+        assert!(clock.is_paused());
+        assert_eq!(clock.frame(), 60);
+        assert!((clock.time() - 1.0).abs() < 1e-12);
+
+        clock.resume();
+        assert_eq!(clock.mode(), ClockMode::FixedStep { step: 1.0 / 60.0 });
+        let resumed_at = clock.time();
+        run(&mut clock, 60 * 60);
+        assert_eq!(clock.time(), resumed_at + 60.0);
+    }
+
+    #[test]
+    fn reset_goes_back_to_zero_and_keeps_the_mode() {
+        let mut clock = AnimationClock::fixed_step(30);
+        run(&mut clock, 45);
+        clock.reset();
+        assert_eq!(clock.time(), 0.0);
+        assert_eq!(clock.frame(), 0);
+        run(&mut clock, 30);
+        assert_eq!(clock.time(), 1.0);
+    }
+}
@@ -0,0 +1,312 @@
+use std::io::Read;
+use std::path::Path;
+
+use image::RgbaImage;
+use wgpu::util::DeviceExt;
+
+use crate::block_decode;
+
+// Tamaños de bloque ASTC en el orden de los formatos de Vulkan/KTX2
+const ASTC_BLOCKS: [wgpu::AstcBlock; 14] = [
+    wgpu::AstcBlock::B4x4,
+    wgpu::AstcBlock::B5x4,
+    wgpu::AstcBlock::B5x5,
+    wgpu::AstcBlock::B6x5,
+    wgpu::AstcBlock::B6x6,
+    wgpu::AstcBlock::B8x5,
+    wgpu::AstcBlock::B8x6,
+    wgpu::AstcBlock::B8x8,
+    wgpu::AstcBlock::B10x5,
+    wgpu::AstcBlock::B10x6,
+    wgpu::AstcBlock::B10x8,
+    wgpu::AstcBlock::B10x10,
+    wgpu::AstcBlock::B12x10,
+    wgpu::AstcBlock::B12x12,
+];
+
+// Textura ya comprimida (BCn, ETC2/EAC, ASTC o RGBA8 sin comprimir) leída de un
+// archivo KTX2 o DDS, con su cadena de mipmaps. Solo se usa la primera capa/cara.
+pub struct CompressedImage {
+    pub format: wgpu::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    // Datos de cada nivel, del más grande al más chico
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImage {
+    // Indica si la extensión es de un contenedor que se carga con este módulo
+    pub fn is_container(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("ktx2") || ext.eq_ignore_ascii_case("dds"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let bytes = std::fs::read(path)?;
+        match path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_ascii_lowercase()).as_deref() {
+            Some("ktx2") => Self::from_ktx2(&bytes),
+            Some("dds") => Self::from_dds(&bytes),
+            _ => Err(format!("{} no es un archivo .ktx2 ni .dds", path.display()).into()),
+        }
+    }
+
+    pub fn from_ktx2(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let reader = ktx2::Reader::new(bytes)?;
+        let header = reader.header();
+
+        // BasisLZ (ETC1S) y UASTC necesitan transcodificarse con Basis Universal
+        if header.supercompression_scheme == Some(ktx2::SupercompressionScheme::BasisLZ) || header.format.is_none() {
+            return Err("KTX2 con Basis Universal (ETC1S/UASTC): hace falta un transcodificador, conviértelo a BCn/ETC2/ASTC".into());
+        }
+        if header.pixel_depth > 1 {
+            return Err("las texturas 3D en KTX2 no están soportadas".into());
+        }
+        let format = header.format.and_then(ktx2_format).ok_or_else(|| format!("formato KTX2 no soportado: {:?}", header.format))?;
+
+        let mut image = Self {
+            format,
+            width: header.pixel_width,
+            height: header.pixel_height.max(1),
+            levels: Vec::new(),
+        };
+        for (level, data) in reader.levels().enumerate() {
+            let data = match header.supercompression_scheme {
+                None => data.data.to_vec(),
+                Some(ktx2::SupercompressionScheme::Zstandard) => {
+                    let mut decoded = Vec::with_capacity(data.uncompressed_byte_length as usize);
+                    ruzstd::decoding::StreamingDecoder::new(data.data)?.read_to_end(&mut decoded)?;
+                    decoded
+                }
+                Some(scheme) => return Err(format!("supercompresión KTX2 no soportada: {:?}", scheme).into()),
+            };
+            // Cada nivel guarda todas las capas y caras seguidas; la primera va al principio
+            image.levels.push(image.take_level(level as u32, &data)?);
+        }
+        Ok(image)
+    }
+
+    pub fn from_dds(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let dds = ddsfile::Dds::read(bytes)?;
+        if dds.get_depth() > 1 {
+            return Err("las texturas 3D en DDS no están soportadas".into());
+        }
+        let format = dds
+            .get_dxgi_format()
+            .and_then(dxgi_format)
+            .or_else(|| dds.get_d3d_format().and_then(d3d_format))
+            .ok_or_else(|| format!("formato DDS no soportado: {:?} / {:?}", dds.get_dxgi_format(), dds.get_d3d_format()))?;
+
+        let mut image = Self {
+            format,
+            width: dds.get_width(),
+            height: dds.get_height(),
+            levels: Vec::new(),
+        };
+        // En DDS los niveles de la primera capa van uno detrás del otro
+        let mut data = dds.get_data(0)?;
+        for level in 0..dds.get_num_mipmap_levels().max(1) {
+            let level_data = image.take_level(level, data)?;
+            data = &data[level_data.len()..];
+            image.levels.push(level_data);
+        }
+        Ok(image)
+    }
+
+    pub fn level_count(&self) -> u32 {
+        self.levels.len() as u32
+    }
+
+    pub fn level_dimensions(&self, level: u32) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    // Bytes de un nivel de mip, contando bloques enteros
+    pub fn level_size(&self, level: u32) -> usize {
+        let (width, height) = self.level_dimensions(level);
+        let (block_width, block_height) = self.format.block_dimensions();
+        let block_size = self.format.block_copy_size(None).unwrap_or(4);
+        width.div_ceil(block_width) as usize * height.div_ceil(block_height) as usize * block_size as usize
+    }
+
+    fn take_level(&self, level: u32, data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let size = self.level_size(level);
+        data.get(..size)
+            .map(|bytes| bytes.to_vec())
+            .ok_or_else(|| format!("el nivel {} tiene {} bytes, se esperaban {}", level, data.len(), size).into())
+    }
+
+    // El adaptador puede muestrear el formato tal cual. wgpu además pide que el
+    // tamaño base sea múltiplo del bloque.
+    pub fn is_supported(&self, features: wgpu::Features) -> bool {
+        let (block_width, block_height) = self.format.block_dimensions();
+        features.contains(self.format.required_features())
+            && self.width.is_multiple_of(block_width)
+            && self.height.is_multiple_of(block_height)
+    }
+
+    // Descomprime todos los niveles a RGBA8 en CPU. En los formatos SNORM los bytes
+    // tienen signo.
+    pub fn decompress(&self) -> Result<Vec<RgbaImage>, Box<dyn std::error::Error>> {
+        use wgpu::TextureFormat as F;
+        self.levels
+            .iter()
+            .enumerate()
+            .map(|(level, data)| {
+                let (width, height) = self.level_dimensions(level as u32);
+                let pixels = match self.format {
+                    F::Rgba8Unorm | F::Rgba8UnormSrgb => data.clone(),
+                    F::Bgra8Unorm | F::Bgra8UnormSrgb => {
+                        data.chunks_exact(4).flat_map(|bgra| [bgra[2], bgra[1], bgra[0], bgra[3]]).collect()
+                    }
+                    format => block_decode::decode(format, width, height, data)?,
+                };
+                RgbaImage::from_raw(width, height, pixels).ok_or_else(|| "nivel de mip incompleto".into())
+            })
+            .collect()
+    }
+
+    // Sube la textura con todos sus mipmaps. Si el adaptador soporta el formato se
+    // copian los bloques directamente; si no, se descomprime a RGBA8 en CPU.
+    // Devuelve la textura y si se subió comprimida.
+    pub fn upload(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+    ) -> Result<(wgpu::Texture, bool), Box<dyn std::error::Error>> {
+        let compressed = self.is_supported(device.features());
+        if !compressed && !block_decode::can_decode(self.format) {
+            return Err(format!(
+                "el adaptador no soporta {:?} y no hay decodificador en CPU para ese formato (ASTC y BC6H necesitan soporte de la GPU)",
+                self.format
+            )
+            .into());
+        }
+        let (format, data) = if compressed {
+            (self.format, self.levels.concat())
+        } else {
+            let format = if block_decode::is_snorm(self.format) {
+                wgpu::TextureFormat::Rgba8Snorm
+            } else if self.format.is_srgb() {
+                wgpu::TextureFormat::Rgba8UnormSrgb
+            } else {
+                wgpu::TextureFormat::Rgba8Unorm
+            };
+            let levels = self.decompress()?;
+            (format, levels.into_iter().flat_map(|level| level.into_raw()).collect())
+        };
+
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: self.width,
+                    height: self.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: self.level_count(),
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &data,
+        );
+        Ok((texture, compressed))
+    }
+}
+
+fn ktx2_format(format: ktx2::Format) -> Option<wgpu::TextureFormat> {
+    use ktx2::Format as K;
+    use wgpu::TextureFormat as F;
+    Some(match format {
+        K::R8G8B8A8_UNORM => F::Rgba8Unorm,
+        K::R8G8B8A8_SRGB => F::Rgba8UnormSrgb,
+        K::B8G8R8A8_UNORM => F::Bgra8Unorm,
+        K::B8G8R8A8_SRGB => F::Bgra8UnormSrgb,
+        K::BC1_RGB_UNORM_BLOCK | K::BC1_RGBA_UNORM_BLOCK => F::Bc1RgbaUnorm,
+        K::BC1_RGB_SRGB_BLOCK | K::BC1_RGBA_SRGB_BLOCK => F::Bc1RgbaUnormSrgb,
+        K::BC2_UNORM_BLOCK => F::Bc2RgbaUnorm,
+        K::BC2_SRGB_BLOCK => F::Bc2RgbaUnormSrgb,
+        K::BC3_UNORM_BLOCK => F::Bc3RgbaUnorm,
+        K::BC3_SRGB_BLOCK => F::Bc3RgbaUnormSrgb,
+        K::BC4_UNORM_BLOCK => F::Bc4RUnorm,
+        K::BC4_SNORM_BLOCK => F::Bc4RSnorm,
+        K::BC5_UNORM_BLOCK => F::Bc5RgUnorm,
+        K::BC5_SNORM_BLOCK => F::Bc5RgSnorm,
+        K::BC6H_UFLOAT_BLOCK => F::Bc6hRgbUfloat,
+        K::BC6H_SFLOAT_BLOCK => F::Bc6hRgbFloat,
+        K::BC7_UNORM_BLOCK => F::Bc7RgbaUnorm,
+        K::BC7_SRGB_BLOCK => F::Bc7RgbaUnormSrgb,
+        K::ETC2_R8G8B8_UNORM_BLOCK => F::Etc2Rgb8Unorm,
+        K::ETC2_R8G8B8_SRGB_BLOCK => F::Etc2Rgb8UnormSrgb,
+        K::ETC2_R8G8B8A1_UNORM_BLOCK => F::Etc2Rgb8A1Unorm,
+        K::ETC2_R8G8B8A1_SRGB_BLOCK => F::Etc2Rgb8A1UnormSrgb,
+        K::ETC2_R8G8B8A8_UNORM_BLOCK => F::Etc2Rgba8Unorm,
+        K::ETC2_R8G8B8A8_SRGB_BLOCK => F::Etc2Rgba8UnormSrgb,
+        K::EAC_R11_UNORM_BLOCK => F::EacR11Unorm,
+        K::EAC_R11_SNORM_BLOCK => F::EacR11Snorm,
+        K::EAC_R11G11_UNORM_BLOCK => F::EacRg11Unorm,
+        K::EAC_R11G11_SNORM_BLOCK => F::EacRg11Snorm,
+        _ => {
+            // ASTC: pares UNORM/SRGB desde 157 y los SFLOAT (HDR) desde 1000066000
+            let value = format.value();
+            let (index, channel) = match value {
+                157..=184 => (
+                    (value - 157) / 2,
+                    if value % 2 == 1 { wgpu::AstcChannel::Unorm } else { wgpu::AstcChannel::UnormSrgb },
+                ),
+                1000066000..=1000066013 => (value - 1000066000, wgpu::AstcChannel::Hdr),
+                _ => return None,
+            };
+            F::Astc {
+                block: ASTC_BLOCKS[index as usize],
+                channel,
+            }
+        }
+    })
+}
+
+fn dxgi_format(format: ddsfile::DxgiFormat) -> Option<wgpu::TextureFormat> {
+    use ddsfile::DxgiFormat as D;
+    use wgpu::TextureFormat as F;
+    Some(match format {
+        D::R8G8B8A8_UNorm => F::Rgba8Unorm,
+        D::R8G8B8A8_UNorm_sRGB => F::Rgba8UnormSrgb,
+        D::B8G8R8A8_UNorm => F::Bgra8Unorm,
+        D::B8G8R8A8_UNorm_sRGB => F::Bgra8UnormSrgb,
+        D::BC1_Typeless | D::BC1_UNorm => F::Bc1RgbaUnorm,
+        D::BC1_UNorm_sRGB => F::Bc1RgbaUnormSrgb,
+        D::BC2_Typeless | D::BC2_UNorm => F::Bc2RgbaUnorm,
+        D::BC2_UNorm_sRGB => F::Bc2RgbaUnormSrgb,
+        D::BC3_Typeless | D::BC3_UNorm => F::Bc3RgbaUnorm,
+        D::BC3_UNorm_sRGB => F::Bc3RgbaUnormSrgb,
+        D::BC4_Typeless | D::BC4_UNorm => F::Bc4RUnorm,
+        D::BC4_SNorm => F::Bc4RSnorm,
+        D::BC5_Typeless | D::BC5_UNorm => F::Bc5RgUnorm,
+        D::BC5_SNorm => F::Bc5RgSnorm,
+        D::BC6H_Typeless | D::BC6H_UF16 => F::Bc6hRgbUfloat,
+        D::BC6H_SF16 => F::Bc6hRgbFloat,
+        D::BC7_Typeless | D::BC7_UNorm => F::Bc7RgbaUnorm,
+        D::BC7_UNorm_sRGB => F::Bc7RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+// Formatos de los DDS antiguos, sin cabecera DX10
+fn d3d_format(format: ddsfile::D3DFormat) -> Option<wgpu::TextureFormat> {
+    use ddsfile::D3DFormat as D;
+    use wgpu::TextureFormat as F;
+    Some(match format {
+        D::DXT1 => F::Bc1RgbaUnorm,
+        D::DXT2 | D::DXT3 => F::Bc2RgbaUnorm,
+        D::DXT4 | D::DXT5 => F::Bc3RgbaUnorm,
+        D::A8R8G8B8 => F::Bgra8Unorm,
+        D::A8B8G8R8 => F::Rgba8Unorm,
+        _ => return None,
+    })
+}
@@ -0,0 +1,143 @@
+// cubo-cook: convierte imágenes y mallas a los formatos binarios que el visor lee
+// sin decodificar (ver cooked.rs).
+//
+//   cargo run --bin cubo-cook -- assets/texture.jpg --compress bc1
+//   cargo run --bin cubo-cook -- modelo.glb --out assets/cooked
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use textured_cube::cooked::{self, TextureCompression, TextureCookSettings, MESH_EXTENSION, TEXTURE_EXTENSION};
+
+const USAGE: &str = "Uso: cubo-cook [opciones] <entrada>...
+
+Imágenes (.png, .jpg, ...) -> .cubotex con mipmaps
+Mallas (.gltf, .glb)       -> .cubomesh
+
+Opciones:
+  --out carpeta               carpeta de salida (por defecto, junto a cada entrada)
+  --compress none|bc1|bc3     compresión de las texturas (por defecto none)
+  --linear                    texturas con datos lineales en lugar de colores sRGB
+  --no-mips                   no generar mipmaps";
+
+struct Options {
+    inputs: Vec<PathBuf>,
+    out: Option<PathBuf>,
+    texture: TextureCookSettings,
+}
+
+impl Options {
+    fn from_args() -> Result<Self, String> {
+        let mut options = Self {
+            inputs: Vec::new(),
+            out: None,
+            texture: TextureCookSettings::default(),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--out" => options.out = Some(args.next().ok_or("falta la carpeta de --out")?.into()),
+                "--compress" => {
+                    options.texture.compression = match args.next().as_deref() {
+                        Some("none") => TextureCompression::None,
+                        Some("bc1") => TextureCompression::Bc1,
+                        Some("bc3") => TextureCompression::Bc3,
+                        other => return Err(format!("compresión desconocida: {:?}", other)),
+                    }
+                }
+                "--linear" => options.texture.srgb = false,
+                "--no-mips" => options.texture.mipmaps = false,
+                "-h" | "--help" => return Err(String::new()),
+                _ if arg.starts_with("--") => return Err(format!("opción desconocida: {}", arg)),
+                _ => options.inputs.push(arg.into()),
+            }
+        }
+        if options.inputs.is_empty() {
+            return Err("no se indicó ningún archivo".into());
+        }
+        Ok(options)
+    }
+
+    fn output_path(&self, input: &Path, extension: &str) -> PathBuf {
+        let name = Path::new(input.file_name().unwrap_or_default()).with_extension(extension);
+        match &self.out {
+            Some(dir) => dir.join(name),
+            None => input.with_extension(extension),
+        }
+    }
+}
+
+fn cook(input: &Path, options: &Options) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let extension = input.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    if extension == "gltf" || extension == "glb" {
+        let mesh = cooked::load_gltf_mesh(input)?;
+        let output = options.output_path(input, MESH_EXTENSION);
+        cooked::write_mesh(&output, &mesh)?;
+        println!(
+            "✅ {} -> {} ({} vértices, {} triángulos)",
+            input.display(),
+            output.display(),
+            mesh.vertices.len(),
+            mesh.triangle_count()
+        );
+        Ok(output)
+    } else if image::ImageFormat::from_path(input).is_ok() {
+        let image = image::open(input)?.to_rgba8();
+        if options.texture.compression != TextureCompression::None
+            && !(image.width().is_multiple_of(4) && image.height().is_multiple_of(4))
+        {
+            println!(
+                "⚠️  {} mide {} x {}: wgpu solo sube bloques BC con lados múltiplos de 4, el visor la descomprimirá en CPU",
+                input.display(),
+                image.width(),
+                image.height()
+            );
+        }
+        let texture = cooked::cook_texture(&image, &options.texture);
+        let output = options.output_path(input, TEXTURE_EXTENSION);
+        cooked::write_texture(&output, &texture)?;
+        println!(
+            "✅ {} -> {} ({} x {}, {:?}, {} mipmaps)",
+            input.display(),
+            output.display(),
+            texture.width,
+            texture.height,
+            texture.format,
+            texture.level_count()
+        );
+        Ok(output)
+    } else {
+        Err("tipo de archivo desconocido".into())
+    }
+}
+
+fn main() {
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(error) => {
+            if !error.is_empty() {
+                eprintln!("⚠️  {}\n", error);
+            }
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let start = Instant::now();
+    let mut failed = 0;
+    for input in &options.inputs {
+        if let Err(e) = cook(input, &options) {
+            eprintln!("⚠️  No se pudo cocinar {}: {}", input.display(), e);
+            failed += 1;
+        }
+    }
+    println!(
+        "{} de {} archivos cocinados en {:.2} s",
+        options.inputs.len() - failed,
+        options.inputs.len(),
+        start.elapsed().as_secs_f64()
+    );
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,484 @@
+use std::path::Path;
+
+use image::{imageops, RgbaImage};
+use memmap2::Mmap;
+
+use crate::block_encode;
+use crate::compressed_texture::CompressedImage;
+use crate::primitives::{MeshData, MeshVertex};
+
+// Formato binario de los assets cocinados por cubo-cook. Todos los archivos empiezan
+// con la misma cabecera de 32 bytes, en little endian:
+//
+//   magic     [u8; 4]  b"CUBO"
+//   kind      [u8; 4]  b"TEX\0" o b"MESH"
+//   version   u32      COOKED_VERSION
+//   flags     u32      reservado (0)
+//   length    u64      bytes del contenido que sigue a la cabecera
+//   checksum  u32      CRC32 del contenido
+//   reserved  u32
+//
+// Textura: formato, ancho, alto y número de niveles (u32) y luego los niveles de
+// mip seguidos, del más grande al más chico, con el tamaño que da CompressedImage.
+// Malla: número de vértices e índices (u32), los vértices (MeshVertex) y los
+// índices u32. El contenido queda alineado a 4 bytes para leerlo directamente
+// desde el archivo mapeado en memoria.
+
+pub const COOKED_MAGIC: [u8; 4] = *b"CUBO";
+pub const COOKED_VERSION: u32 = 1;
+pub const TEXTURE_EXTENSION: &str = "cubotex";
+pub const MESH_EXTENSION: &str = "cubomesh";
+
+const TEXTURE_KIND: [u8; 4] = *b"TEX\0";
+const MESH_KIND: [u8; 4] = *b"MESH";
+const HEADER_SIZE: usize = 32;
+
+// Código de cada formato dentro del archivo; no cambia aunque cambie wgpu
+const TEXTURE_FORMATS: [(u32, wgpu::TextureFormat); 6] = [
+    (1, wgpu::TextureFormat::Rgba8Unorm),
+    (2, wgpu::TextureFormat::Rgba8UnormSrgb),
+    (3, wgpu::TextureFormat::Bc1RgbaUnorm),
+    (4, wgpu::TextureFormat::Bc1RgbaUnormSrgb),
+    (5, wgpu::TextureFormat::Bc3RgbaUnorm),
+    (6, wgpu::TextureFormat::Bc3RgbaUnormSrgb),
+];
+
+// Compresión de las texturas al cocinarlas
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextureCompression {
+    None,
+    // 4 bits por píxel, alfa de 1 bit
+    Bc1,
+    // 8 bits por píxel, alfa completo
+    Bc3,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct TextureCookSettings {
+    pub compression: TextureCompression,
+    pub mipmaps: bool,
+    // Colores en sRGB (texturas de color) o datos lineales (normales, máscaras)
+    pub srgb: bool,
+}
+
+impl Default for TextureCookSettings {
+    fn default() -> Self {
+        Self {
+            compression: TextureCompression::None,
+            mipmaps: true,
+            srgb: true,
+        }
+    }
+}
+
+// Genera los mipmaps y comprime la imagen según los ajustes
+pub fn cook_texture(image: &RgbaImage, settings: &TextureCookSettings) -> CompressedImage {
+    let (width, height) = image.dimensions();
+    let mut mips = vec![image.clone()];
+    if settings.mipmaps {
+        while let Some(last) = mips.last().filter(|mip| mip.width() > 1 || mip.height() > 1) {
+            let (w, h) = ((last.width() / 2).max(1), (last.height() / 2).max(1));
+            let next = imageops::resize(last, w, h, imageops::FilterType::Triangle);
+            mips.push(next);
+        }
+    }
+
+    use wgpu::TextureFormat as F;
+    let format = match (settings.compression, settings.srgb) {
+        (TextureCompression::None, false) => F::Rgba8Unorm,
+        (TextureCompression::None, true) => F::Rgba8UnormSrgb,
+        (TextureCompression::Bc1, false) => F::Bc1RgbaUnorm,
+        (TextureCompression::Bc1, true) => F::Bc1RgbaUnormSrgb,
+        (TextureCompression::Bc3, false) => F::Bc3RgbaUnorm,
+        (TextureCompression::Bc3, true) => F::Bc3RgbaUnormSrgb,
+    };
+    let levels = mips
+        .iter()
+        .map(|mip| match settings.compression {
+            TextureCompression::None => mip.as_raw().clone(),
+            TextureCompression::Bc1 => block_encode::encode(mip, false),
+            TextureCompression::Bc3 => block_encode::encode(mip, true),
+        })
+        .collect();
+    CompressedImage { format, width, height, levels }
+}
+
+pub fn write_texture(path: &Path, image: &CompressedImage) -> Result<(), Box<dyn std::error::Error>> {
+    let code = TEXTURE_FORMATS
+        .iter()
+        .find(|(_, format)| *format == image.format)
+        .map(|(code, _)| *code)
+        .ok_or_else(|| format!("el formato {:?} no se puede guardar cocinado", image.format))?;
+    let mut payload = Vec::new();
+    for value in [code, image.width, image.height, image.level_count()] {
+        payload.extend(value.to_le_bytes());
+    }
+    for (level, data) in image.levels.iter().enumerate() {
+        if data.len() != image.level_size(level as u32) {
+            return Err(format!("el nivel {} tiene {} bytes, se esperaban {}", level, data.len(), image.level_size(level as u32)).into());
+        }
+        payload.extend(data);
+    }
+    write_cooked(path, TEXTURE_KIND, &payload)
+}
+
+pub fn write_mesh(path: &Path, mesh: &MeshData) -> Result<(), Box<dyn std::error::Error>> {
+    let mut payload = Vec::new();
+    payload.extend((mesh.vertices.len() as u32).to_le_bytes());
+    payload.extend((mesh.indices.len() as u32).to_le_bytes());
+    payload.extend(bytemuck::cast_slice(&mesh.vertices));
+    payload.extend(bytemuck::cast_slice(&mesh.indices));
+    write_cooked(path, MESH_KIND, &payload)
+}
+
+fn write_cooked(path: &Path, kind: [u8; 4], payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len());
+    bytes.extend(COOKED_MAGIC);
+    bytes.extend(kind);
+    bytes.extend(COOKED_VERSION.to_le_bytes());
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend((payload.len() as u64).to_le_bytes());
+    bytes.extend(crc32fast::hash(payload).to_le_bytes());
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend(payload);
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+// Mapea el archivo en memoria y valida la cabecera y el checksum. Devuelve el mapa
+// completo; el contenido empieza en HEADER_SIZE.
+fn open_cooked(path: &Path, kind: [u8; 4]) -> Result<Mmap, Box<dyn std::error::Error>> {
+    let file = std::fs::File::open(path)?;
+    // SAFETY: el archivo solo se lee; si otro proceso lo modifica mientras está
+    // mapeado el contenido puede cambiar, pero cubo-cook escribe archivos nuevos
+    let mmap = unsafe { Mmap::map(&file)? };
+
+    let header = mmap.get(..HEADER_SIZE).ok_or("el archivo es más corto que la cabecera")?;
+    let u32_at = |offset: usize| u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap());
+    if header[0..4] != COOKED_MAGIC {
+        return Err(format!("{} no es un asset cocinado", path.display()).into());
+    }
+    if header[4..8] != kind {
+        return Err(format!("{} es de otro tipo ({:?})", path.display(), String::from_utf8_lossy(&header[4..8])).into());
+    }
+    let version = u32_at(8);
+    if version != COOKED_VERSION {
+        return Err(format!("{} tiene la versión {}, se esperaba {}; vuelve a cocinarlo", path.display(), version, COOKED_VERSION).into());
+    }
+    let length = u64::from_le_bytes(header[16..24].try_into().unwrap());
+    if mmap.len() as u64 != HEADER_SIZE as u64 + length {
+        return Err(format!("{} está truncado", path.display()).into());
+    }
+    if crc32fast::hash(&mmap[HEADER_SIZE..]) != u32_at(24) {
+        return Err(format!("el checksum de {} no coincide", path.display()).into());
+    }
+    Ok(mmap)
+}
+
+// Textura cocinada, leída desde el archivo mapeado en memoria
+pub struct CookedTexture {
+    mmap: Mmap,
+    pub format: wgpu::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    pub level_count: u32,
+}
+
+impl CookedTexture {
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mmap = open_cooked(path, TEXTURE_KIND)?;
+        let field = |i: usize| {
+            mmap.get(HEADER_SIZE + i * 4..HEADER_SIZE + i * 4 + 4)
+                .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+                .ok_or("cabecera de textura incompleta")
+        };
+        let code = field(0)?;
+        let format = TEXTURE_FORMATS
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, format)| *format)
+            .ok_or_else(|| format!("formato de textura desconocido: {}", code))?;
+        let (width, height, level_count) = (field(1)?, field(2)?, field(3)?);
+        if width == 0 || height == 0 || level_count == 0 || level_count > 32 {
+            return Err(format!("textura inválida: {} x {} con {} niveles", width, height, level_count).into());
+        }
+        let texture = Self {
+            mmap,
+            format,
+            width,
+            height,
+            level_count,
+        };
+
+        let payload_len = texture.mmap.len() - HEADER_SIZE;
+        let expected: usize = 16 + (0..texture.level_count).map(|level| texture.level_size(level)).sum::<usize>();
+        if payload_len != expected {
+            return Err(format!("la textura tiene {} bytes, se esperaban {}", payload_len, expected).into());
+        }
+        Ok(texture)
+    }
+
+    fn level_size(&self, level: u32) -> usize {
+        CompressedImage {
+            format: self.format,
+            width: self.width,
+            height: self.height,
+            levels: Vec::new(),
+        }
+        .level_size(level)
+    }
+
+    // Datos de un nivel de mip, directamente desde el archivo
+    pub fn level(&self, level: u32) -> &[u8] {
+        let start = HEADER_SIZE + 16 + (0..level).map(|l| self.level_size(l)).sum::<usize>();
+        &self.mmap[start..start + self.level_size(level)]
+    }
+
+    pub fn to_image(&self) -> CompressedImage {
+        CompressedImage {
+            format: self.format,
+            width: self.width,
+            height: self.height,
+            levels: (0..self.level_count).map(|level| self.level(level).to_vec()).collect(),
+        }
+    }
+}
+
+// Malla cocinada; los vértices e índices se leen sin copiar desde el archivo mapeado
+pub struct CookedMesh {
+    mmap: Mmap,
+    vertex_count: usize,
+    index_count: usize,
+}
+
+impl CookedMesh {
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mmap = open_cooked(path, MESH_KIND)?;
+        let payload = &mmap[HEADER_SIZE..];
+        if payload.len() < 8 {
+            return Err("cabecera de malla incompleta".into());
+        }
+        let vertex_count = u32::from_le_bytes(payload[0..4].try_into().unwrap()) as usize;
+        let index_count = u32::from_le_bytes(payload[4..8].try_into().unwrap()) as usize;
+        let expected = 8 + vertex_count * std::mem::size_of::<MeshVertex>() + index_count * 4;
+        if payload.len() != expected {
+            return Err(format!("la malla tiene {} bytes, se esperaban {}", payload.len(), expected).into());
+        }
+        let mesh = Self { mmap, vertex_count, index_count };
+        if mesh.indices().iter().any(|&i| i as usize >= vertex_count) {
+            return Err("la malla tiene índices fuera de rango".into());
+        }
+        Ok(mesh)
+    }
+
+    pub fn vertices(&self) -> &[MeshVertex] {
+        let start = HEADER_SIZE + 8;
+        bytemuck::cast_slice(&self.mmap[start..start + self.vertex_count * std::mem::size_of::<MeshVertex>()])
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        let start = HEADER_SIZE + 8 + self.vertex_count * std::mem::size_of::<MeshVertex>();
+        bytemuck::cast_slice(&self.mmap[start..start + self.index_count * 4])
+    }
+
+    pub fn to_mesh_data(&self) -> MeshData {
+        MeshData {
+            vertices: self.vertices().to_vec(),
+            indices: self.indices().to_vec(),
+        }
+    }
+}
+
+// Junta todas las mallas de la escena de un glTF en una sola, con las transformaciones
+// de sus nodos aplicadas. Si faltan normales se calculan promediando las caras.
+pub fn load_gltf_mesh(path: &Path) -> Result<MeshData, Box<dyn std::error::Error>> {
+    use cgmath::{InnerSpace, Matrix, Matrix3, Matrix4, SquareMatrix, Vector3, Vector4};
+
+    let gltf = gltf::Gltf::open(path)?;
+    let document = &gltf.document;
+    let buffers = gltf::import_buffers(document, path.parent(), gltf.blob.clone())?;
+    let buffer_data = |buffer: gltf::Buffer| Some(buffers[buffer.index()].0.as_slice());
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or("el archivo no tiene escenas")?;
+
+    let mut mesh = MeshData::default();
+    let mut stack: Vec<(gltf::Node, Matrix4<f32>)> = scene.nodes().map(|node| (node, Matrix4::identity())).collect();
+    while let Some((node, parent)) = stack.pop() {
+        let world = parent * Matrix4::from(node.transform().matrix());
+        stack.extend(node.children().map(|child| (child, world)));
+        let Some(node_mesh) = node.mesh() else { continue };
+        let rotation = Matrix3::from_cols(world.x.truncate(), world.y.truncate(), world.z.truncate());
+        let normal_matrix = rotation.invert().map(|m| m.transpose()).unwrap_or(rotation);
+
+        for primitive in node_mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+            let reader = primitive.reader(buffer_data);
+            let positions: Vec<[f32; 3]> = reader.read_positions().ok_or("Primitiva sin posiciones")?.collect();
+            let count = positions.len() as u32;
+            let normals: Option<Vec<[f32; 3]>> = reader.read_normals().map(|n| n.collect());
+            if normals.as_ref().is_some_and(|n| n.len() != positions.len()) {
+                return Err("la primitiva tiene distinta cantidad de normales que de posiciones".into());
+            }
+            let mut tex_coords = reader.read_tex_coords(0).map(|t| t.into_f32());
+
+            // Un índice fuera de rango haría fallar el cálculo de normales y el dibujo
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(read) => read.into_u32().collect(),
+                None => (0..count).collect(),
+            };
+            if let Some(index) = indices.iter().find(|&&i| i >= count) {
+                return Err(format!("índice {} fuera de rango: la primitiva tiene {} vértices", index, count).into());
+            }
+            let base = mesh.vertices.len() as u32;
+            let first_index = mesh.indices.len();
+            mesh.indices.extend(indices.iter().map(|i| base + i));
+            for (i, p) in positions.iter().enumerate() {
+                let position = world * Vector4::new(p[0], p[1], p[2], 1.0);
+                let normal = normals
+                    .as_ref()
+                    .map(|n| (normal_matrix * Vector3::from(n[i])).normalize())
+                    .unwrap_or(Vector3::new(0.0, 0.0, 0.0));
+                mesh.vertices.push(MeshVertex {
+                    position: position.truncate().into(),
+                    tex_coords: tex_coords.as_mut().and_then(|t| t.next()).unwrap_or([0.0, 0.0]),
+                    normal: normal.into(),
+                });
+            }
+
+            if normals.is_none() {
+                for triangle in mesh.indices[first_index..].chunks_exact(3) {
+                    let [a, b, c] = [0, 1, 2].map(|k| Vector3::from(mesh.vertices[triangle[k] as usize].position));
+                    let face = (b - a).cross(c - a);
+                    for &index in triangle {
+                        let normal = &mut mesh.vertices[index as usize].normal;
+                        *normal = (Vector3::from(*normal) + face).into();
+                    }
+                }
+                for vertex in &mut mesh.vertices[base as usize..] {
+                    let normal = Vector3::from(vertex.normal);
+                    if normal.magnitude2() > 0.0 {
+                        vertex.normal = normal.normalize().into();
+                    }
+                }
+            }
+        }
+    }
+
+    if mesh.vertices.is_empty() {
+        return Err("la escena no tiene mallas de triángulos".into());
+    }
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives;
+
+    // Archivo en un directorio temporal propio de este proceso
+    fn scratch(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("cubo-cooked-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    fn gradient(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| image::Rgba([(x * 8) as u8, (y * 8) as u8, 64, 255 - x as u8]))
+    }
+
+    #[test]
+    fn texture_round_trip() {
+        for (compression, srgb) in [(TextureCompression::None, false), (TextureCompression::Bc1, true), (TextureCompression::Bc3, false)] {
+            let settings = TextureCookSettings { compression, mipmaps: true, srgb };
+            let image = cook_texture(&gradient(20, 12), &settings);
+            let path = scratch(&format!("texture-{:?}.{}", compression, TEXTURE_EXTENSION));
+            write_texture(&path, &image).unwrap();
+
+            let cooked = CookedTexture::open(&path).unwrap();
+            assert_eq!((cooked.format, cooked.width, cooked.height), (image.format, 20, 12));
+            assert_eq!(cooked.level_count, 5);
+            assert_eq!(cooked.to_image().levels, image.levels);
+        }
+    }
+
+    #[test]
+    fn mesh_round_trip() {
+        let mesh = primitives::uv_sphere(1.0, 8, 4);
+        let path = scratch(&format!("sphere.{}", MESH_EXTENSION));
+        write_mesh(&path, &mesh).unwrap();
+
+        let cooked = CookedMesh::open(&path).unwrap();
+        assert_eq!(cooked.vertices(), mesh.vertices.as_slice());
+        assert_eq!(cooked.indices(), mesh.indices.as_slice());
+    }
+
+    #[test]
+    fn rejects_corrupted_and_mismatched_files() {
+        let path = scratch(&format!("corrupt.{}", MESH_EXTENSION));
+        write_mesh(&path, &primitives::plane(1.0, 1.0, 1, 1)).unwrap();
+        assert!(CookedTexture::open(&path).is_err());
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        let error = |path: &Path| CookedMesh::open(path).err().map(|e| e.to_string()).unwrap_or_default();
+        assert!(error(&path).contains("checksum"));
+
+        std::fs::write(&path, &bytes[..bytes.len() - 4]).unwrap();
+        assert!(error(&path).contains("truncado"));
+    }
+
+    // Triángulo en un .gltf con el buffer aparte; `last_index` es el tercer índice
+    fn write_triangle_gltf(name: &str, last_index: u16) -> std::path::PathBuf {
+        let mut buffer: Vec<u8> = [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        buffer.extend([0u16, 1, last_index].iter().flat_map(|i| i.to_le_bytes()));
+        buffer.extend([0, 0]);
+        std::fs::write(scratch(&format!("{}.bin", name)), &buffer).unwrap();
+
+        let json = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scene": 0,
+                "scenes": [{{ "nodes": [0] }}],
+                "nodes": [{{ "mesh": 0 }}],
+                "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "indices": 1 }}] }}],
+                "buffers": [{{ "uri": "{name}.bin", "byteLength": 44 }}],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+                ],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+                ]
+            }}"#
+        );
+        let path = scratch(&format!("{}.gltf", name));
+        std::fs::write(&path, json).unwrap();
+        path
+    }
+
+    #[test]
+    fn gltf_triangle_loads_with_flat_normals() {
+        let mesh = load_gltf_mesh(&write_triangle_gltf("triangle", 2)).unwrap();
+        assert_eq!(mesh.indices, [0, 1, 2]);
+        assert!(mesh.vertices.iter().all(|v| v.normal == [0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn gltf_out_of_range_index_is_an_error() {
+        let error = load_gltf_mesh(&write_triangle_gltf("bad-index", 7)).unwrap_err();
+        assert!(error.to_string().contains("fuera de rango"), "{}", error);
+    }
+}
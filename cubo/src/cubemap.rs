@@ -0,0 +1,208 @@
+use std::path::Path;
+
+use image::{imageops, RgbaImage};
+
+// Nombres de archivo aceptados para cada capa del cubemap, en el orden de wgpu
+// (+X, -X, +Y, -Y, +Z, -Z). Los segundos coinciden con las caras del cubo.
+pub const CUBEMAP_FACE_NAMES: [[&str; 2]; 6] = [
+    ["px", "right"],
+    ["nx", "left"],
+    ["py", "top"],
+    ["ny", "bottom"],
+    ["pz", "front"],
+    ["nz", "back"],
+];
+
+// Distribución de las seis caras dentro de una sola imagen
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CubemapLayout {
+    //     +Y
+    // -X  +Z  +X  -Z
+    //     -Y
+    HorizontalCross,
+    //     +Y
+    // -X  +Z  +X
+    //     -Y
+    //     -Z  (girada 180°)
+    VerticalCross,
+    // +X -X +Y -Y +Z -Z en una fila
+    HorizontalStrip,
+    // +X -X +Y -Y +Z -Z en una columna
+    VerticalStrip,
+}
+
+impl CubemapLayout {
+    // Reconoce la distribución por la proporción de la imagen
+    pub fn detect(width: u32, height: u32) -> Option<Self> {
+        [Self::HorizontalCross, Self::VerticalCross, Self::HorizontalStrip, Self::VerticalStrip]
+            .into_iter()
+            .find(|layout| {
+                let (columns, rows) = layout.grid();
+                width.is_multiple_of(columns) && height.is_multiple_of(rows) && width / columns == height / rows
+            })
+    }
+
+    fn grid(self) -> (u32, u32) {
+        match self {
+            Self::HorizontalCross => (4, 3),
+            Self::VerticalCross => (3, 4),
+            Self::HorizontalStrip => (6, 1),
+            Self::VerticalStrip => (1, 6),
+        }
+    }
+
+    // Celda (columna, fila) de cada capa y si hay que girarla 180°
+    fn cells(self) -> [(u32, u32, bool); 6] {
+        match self {
+            Self::HorizontalCross => [(2, 1, false), (0, 1, false), (1, 0, false), (1, 2, false), (1, 1, false), (3, 1, false)],
+            Self::VerticalCross => [(2, 1, false), (0, 1, false), (1, 0, false), (1, 2, false), (1, 1, false), (1, 3, true)],
+            Self::HorizontalStrip => std::array::from_fn(|i| (i as u32, 0, false)),
+            Self::VerticalStrip => std::array::from_fn(|i| (0, i as u32, false)),
+        }
+    }
+}
+
+// Las seis caras de un cubemap, cuadradas y del mismo tamaño
+pub struct CubemapImages {
+    pub size: u32,
+    pub faces: [RgbaImage; 6],
+}
+
+impl CubemapImages {
+    pub fn new(faces: [RgbaImage; 6]) -> Result<Self, Box<dyn std::error::Error>> {
+        let size = faces[0].width();
+        if size == 0 || faces.iter().any(|face| face.width() != size || face.height() != size) {
+            return Err("las caras del cubemap deben ser cuadradas y del mismo tamaño".into());
+        }
+        Ok(Self { size, faces })
+    }
+
+    // Una carpeta con seis imágenes (px/nx/... o right/left/...) o una imagen en cruz o tira
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if path.is_dir() {
+            Self::from_dir(path)
+        } else {
+            let image = image::open(path)?.to_rgba8();
+            let layout = CubemapLayout::detect(image.width(), image.height())
+                .ok_or("la imagen no es una cruz (4x3, 3x4) ni una tira (6x1, 1x6)")?;
+            Self::from_image(&image, layout)
+        }
+    }
+
+    pub fn from_dir(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let paths: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| image::ImageFormat::from_path(path).is_ok())
+            .collect();
+        let mut faces = Vec::with_capacity(6);
+        for names in CUBEMAP_FACE_NAMES {
+            let path = paths
+                .iter()
+                .find(|path| path.file_stem().is_some_and(|stem| names.iter().any(|name| stem == *name)))
+                .ok_or_else(|| format!("falta la cara {} ({})", names[0], names[1]))?;
+            faces.push(image::open(path)?.to_rgba8());
+        }
+        Self::new(faces.try_into().unwrap())
+    }
+
+    // Recorta las caras de una imagen en cruz o en tira
+    pub fn from_image(image: &RgbaImage, layout: CubemapLayout) -> Result<Self, Box<dyn std::error::Error>> {
+        let (columns, rows) = layout.grid();
+        let size = image.width() / columns;
+        if image.width() != size * columns || image.height() != size * rows {
+            return Err(format!("una imagen de {} x {} no tiene la distribución {:?}", image.width(), image.height(), layout).into());
+        }
+        let faces = layout.cells().map(|(column, row, rotated)| {
+            let face = imageops::crop_imm(image, column * size, row * size, size, size).to_image();
+            if rotated {
+                imageops::rotate180(&face)
+            } else {
+                face
+            }
+        });
+        Self::new(faces)
+    }
+}
+
+// Textura de 6 capas con una vista TextureViewDimension::Cube. En el shader se
+// muestrea con la dirección desde el centro del objeto.
+pub struct Cubemap {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub size: u32,
+}
+
+impl Cubemap {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, images: &CubemapImages, label: &str) -> Self {
+        let size = wgpu::Extent3d {
+            width: images.size,
+            height: images.size,
+            depth_or_array_layers: 6,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        for (layer, face) in images.faces.iter().enumerate() {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: 0, y: 0, z: layer as u32 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                face,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * images.size),
+                    rows_per_image: Some(images.size),
+                },
+                wgpu::Extent3d { depth_or_array_layers: 1, ..size },
+            );
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some(label),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            view,
+            size: images.size,
+        }
+    }
+
+    // Igual que el layout de la textura 2D (textura en 0, sampler en 1) pero con vista Cube
+    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("cubemap_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+}
@@ -0,0 +1,75 @@
+// Culling en GPU: prueba la esfera envolvente de cada objeto contra el frustum,
+// compacta los visibles y escribe los argumentos de draw_indexed_indirect.
+struct Object {
+    model: mat4x4<f32>,
+    mesh: u32,
+    // Capa de la textura array: (base, incremento por cara)
+    layer: vec2<u32>,
+}
+
+// Instancia visible, con el mismo formato que InstanceRaw en el visor
+struct Instance {
+    model: mat4x4<f32>,
+    layer: vec2<u32>,
+    face_vertices: u32,
+}
+
+struct MeshInfo {
+    // xyz = centro local, w = radio
+    sphere: vec4<f32>,
+    // Primera posición de la malla dentro del buffer de instancias visibles
+    base_instance: u32,
+    face_vertices: u32,
+}
+
+struct DrawArgs {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+
+struct Frustum {
+    planes: array<vec4<f32>, 6>,
+    object_count: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> frustum: Frustum;
+@group(0) @binding(1)
+var<storage, read> objects: array<Object>;
+@group(0) @binding(2)
+var<storage, read> meshes: array<MeshInfo>;
+@group(0) @binding(3)
+var<storage, read_write> visible: array<Instance>;
+@group(0) @binding(4)
+var<storage, read_write> draws: array<DrawArgs>;
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if (index >= frustum.object_count) {
+        return;
+    }
+
+    let object = objects[index];
+    let mesh = meshes[object.mesh];
+
+    let center = object.model * vec4<f32>(mesh.sphere.xyz, 1.0);
+    let scale = max(
+        length(object.model[0].xyz),
+        max(length(object.model[1].xyz), length(object.model[2].xyz)),
+    );
+    let radius = mesh.sphere.w * scale;
+
+    for (var i = 0u; i < 6u; i = i + 1u) {
+        let plane = frustum.planes[i];
+        if (dot(plane.xyz, center.xyz / center.w) + plane.w < -radius) {
+            return;
+        }
+    }
+
+    let slot = atomicAdd(&draws[object.mesh].instance_count, 1u);
+    visible[mesh.base_instance + slot] = Instance(object.model, object.layer, mesh.face_vertices);
+}
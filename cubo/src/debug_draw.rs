@@ -0,0 +1,294 @@
+use cgmath::{InnerSpace, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+
+use crate::bounds::Aabb;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct DebugVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
+const CIRCLE_SEGMENTS: usize = 32;
+const INITIAL_CAPACITY: usize = 1024;
+
+// Dibujo inmediato de líneas de ayuda. Cada frame se acumulan primitivas en
+// espacio de mundo, `prepare` las sube a un vertex buffer que crece según haga
+// falta y `draw` las dibuja dentro del pass principal. Las líneas con depth test
+// quedan tapadas por la escena; las demás se dibujan siempre encima.
+pub struct DebugDraw {
+    depth_pipeline: wgpu::RenderPipeline,
+    overlay_pipeline: wgpu::RenderPipeline,
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    depth_vertices: Vec<DebugVertex>,
+    overlay_vertices: Vec<DebugVertex>,
+    // Vértices subidos en el último prepare: primero los de depth test
+    depth_count: u32,
+    overlay_count: u32,
+    depth_test: bool,
+}
+
+impl DebugDraw {
+    pub fn new(device: &wgpu::Device, color_format: wgpu::TextureFormat, depth_format: wgpu::TextureFormat) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("debug_draw_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Debug Draw Uniforms"),
+            size: std::mem::size_of::<[[f32; 4]; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("debug_draw_bind_group"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniforms.as_entire_binding(),
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Debug Draw Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("debug_draw.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Debug Draw Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        // Las dos variantes comparten todo salvo la comparación de profundidad
+        let pipeline = |label: &str, depth_compare: wgpu::CompareFunction| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    compilation_options: Default::default(),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<DebugVertex>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4],
+                    }],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: color_format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::LineList,
+                    ..Default::default()
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: depth_format,
+                    depth_write_enabled: false,
+                    depth_compare,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        };
+        let depth_pipeline = pipeline("Debug Draw Pipeline", wgpu::CompareFunction::LessEqual);
+        let overlay_pipeline = pipeline("Debug Draw Overlay Pipeline", wgpu::CompareFunction::Always);
+
+        Self {
+            depth_pipeline,
+            overlay_pipeline,
+            uniforms,
+            bind_group,
+            vertex_buffer: create_vertex_buffer(device, INITIAL_CAPACITY),
+            depth_vertices: Vec::new(),
+            overlay_vertices: Vec::new(),
+            depth_count: 0,
+            overlay_count: 0,
+            depth_test: true,
+        }
+    }
+
+    // Afecta a las primitivas que se agreguen después, hasta el próximo prepare
+    pub fn set_depth_test(&mut self, enabled: bool) {
+        self.depth_test = enabled;
+    }
+
+    pub fn line(&mut self, from: Point3<f32>, to: Point3<f32>, color: [f32; 4]) {
+        let vertices = if self.depth_test { &mut self.depth_vertices } else { &mut self.overlay_vertices };
+        vertices.push(DebugVertex { position: from.into(), color });
+        vertices.push(DebugVertex { position: to.into(), color });
+    }
+
+    // Caja transformada por `model`; sigue orientada con el objeto
+    pub fn aabb(&mut self, aabb: &Aabb, model: &Matrix4<f32>, color: [f32; 4]) {
+        let corners = aabb.corners().map(|corner| transform_point(model, corner));
+        self.box_edges(&corners, color);
+    }
+
+    // Tres círculos máximos, uno por plano
+    pub fn sphere(&mut self, center: Point3<f32>, radius: f32, color: [f32; 4]) {
+        let (x, y, z) = (Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z());
+        self.circle(center, x * radius, y * radius, color);
+        self.circle(center, y * radius, z * radius, color);
+        self.circle(center, z * radius, x * radius, color);
+    }
+
+    // Círculo de centro `center` en el plano de `u` y `v`, que dan también el radio
+    pub fn circle(&mut self, center: Point3<f32>, u: Vector3<f32>, v: Vector3<f32>, color: [f32; 4]) {
+        let point = |i: usize| {
+            let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + u * angle.cos() + v * angle.sin()
+        };
+        for i in 0..CIRCLE_SEGMENTS {
+            self.line(point(i), point(i + 1), color);
+        }
+    }
+
+    // Ejes X, Y, Z de `model` en rojo, verde y azul
+    pub fn axes(&mut self, model: &Matrix4<f32>, size: f32) {
+        let origin = transform_point(model, Point3::new(0.0, 0.0, 0.0));
+        let colors = [[1.0, 0.2, 0.2, 1.0], [0.2, 1.0, 0.2, 1.0], [0.3, 0.5, 1.0, 1.0]];
+        for (axis, color) in [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()].into_iter().zip(colors) {
+            let tip = transform_point(model, Point3::new(0.0, 0.0, 0.0) + axis * size);
+            self.arrow(origin, tip, color);
+        }
+    }
+
+    // Grilla en el plano XZ centrada en `center`, de `cells` celdas por lado
+    pub fn grid(&mut self, center: Point3<f32>, cell_size: f32, cells: u32, color: [f32; 4]) {
+        let half = cell_size * cells as f32 * 0.5;
+        for i in 0..=cells {
+            let offset = -half + i as f32 * cell_size;
+            self.line(
+                center + Vector3::new(offset, 0.0, -half),
+                center + Vector3::new(offset, 0.0, half),
+                color,
+            );
+            self.line(
+                center + Vector3::new(-half, 0.0, offset),
+                center + Vector3::new(half, 0.0, offset),
+                color,
+            );
+        }
+    }
+
+    // Frustum de una cámara a partir de su view_proj (profundidad de 0 a 1, como wgpu)
+    pub fn frustum(&mut self, view_proj: &Matrix4<f32>, color: [f32; 4]) {
+        let Some(inverse) = view_proj.invert() else { return };
+        let corners: [Point3<f32>; 8] = std::array::from_fn(|i| {
+            let sign = |bit: usize| if i & bit != 0 { 1.0 } else { -1.0 };
+            let depth = if i & 4 != 0 { 1.0 } else { 0.0 };
+            let p = inverse * Vector4::new(sign(1), sign(2), depth, 1.0);
+            Point3::new(p.x / p.w, p.y / p.w, p.z / p.w)
+        });
+        self.box_edges(&corners, color);
+    }
+
+    // Segmento con punta de flecha en `to`
+    pub fn arrow(&mut self, from: Point3<f32>, to: Point3<f32>, color: [f32; 4]) {
+        self.line(from, to, color);
+        let direction = to - from;
+        let length = direction.magnitude();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let forward = direction / length;
+        // Cualquier vector que no sea paralelo sirve para armar la base de la punta
+        let helper = if forward.y.abs() < 0.9 { Vector3::unit_y() } else { Vector3::unit_x() };
+        let side = forward.cross(helper).normalize();
+        let up = forward.cross(side);
+        let base = to - forward * (length * 0.15);
+        let radius = length * 0.05;
+        for offset in [side, -side, up, -up] {
+            self.line(to, base + offset * radius, color);
+        }
+    }
+
+    // Aristas de una caja cuyas esquinas siguen el orden de Aabb::corners
+    fn box_edges(&mut self, corners: &[Point3<f32>; 8], color: [f32; 4]) {
+        for i in 0..8 {
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    self.line(corners[i], corners[i | bit], color);
+                }
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.depth_vertices.is_empty() && self.overlay_vertices.is_empty()
+    }
+
+    // Sube las primitivas acumuladas y las descarta para el frame siguiente
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, view_proj: &Matrix4<f32>) {
+        self.depth_test = true;
+        self.depth_count = self.depth_vertices.len() as u32;
+        self.overlay_count = self.overlay_vertices.len() as u32;
+        let mut vertices = std::mem::take(&mut self.depth_vertices);
+        vertices.append(&mut self.overlay_vertices);
+        if vertices.is_empty() {
+            return;
+        }
+
+        let needed = (vertices.len() * std::mem::size_of::<DebugVertex>()) as wgpu::BufferAddress;
+        if needed > self.vertex_buffer.size() {
+            self.vertex_buffer = create_vertex_buffer(device, vertices.len().next_power_of_two());
+        }
+        let view_proj: [[f32; 4]; 4] = (*view_proj).into();
+        queue.write_buffer(&self.uniforms, 0, bytemuck::cast_slice(&[view_proj]));
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+
+        // Se reutiliza la memoria del vector para el frame siguiente
+        vertices.clear();
+        self.depth_vertices = vertices;
+    }
+
+    // Cambia el pipeline y el grupo 0, así que conviene llamarlo al final del pass
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.depth_count + self.overlay_count == 0 {
+            return;
+        }
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        if self.depth_count > 0 {
+            render_pass.set_pipeline(&self.depth_pipeline);
+            render_pass.draw(0..self.depth_count, 0..1);
+        }
+        if self.overlay_count > 0 {
+            render_pass.set_pipeline(&self.overlay_pipeline);
+            render_pass.draw(self.depth_count..self.depth_count + self.overlay_count, 0..1);
+        }
+    }
+}
+
+fn create_vertex_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Debug Draw Vertex Buffer"),
+        size: (capacity * std::mem::size_of::<DebugVertex>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn transform_point(model: &Matrix4<f32>, point: Point3<f32>) -> Point3<f32> {
+    let p = model * point.to_homogeneous();
+    Point3::new(p.x / p.w, p.y / p.w, p.z / p.w)
+}
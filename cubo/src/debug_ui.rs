@@ -0,0 +1,91 @@
+use winit::window::Window;
+
+// Overlay de egui que se dibuja encima de la escena en su propio pass
+pub struct DebugUi {
+    context: egui::Context,
+    state: egui_winit::State,
+    renderer: egui_wgpu::Renderer,
+    pub visible: bool,
+}
+
+impl DebugUi {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, window: &Window) -> Self {
+        let context = egui::Context::default();
+        let state = egui_winit::State::new(
+            context.clone(),
+            egui::ViewportId::ROOT,
+            window,
+            Some(window.scale_factor() as f32),
+            Some(device.limits().max_texture_dimension_2d as usize),
+        );
+        let renderer = egui_wgpu::Renderer::new(device, format, None, 1);
+
+        Self {
+            context,
+            state,
+            renderer,
+            visible: true,
+        }
+    }
+
+    // Devuelve true si egui consumió el evento y la escena no debe procesarlo
+    pub fn handle_event(&mut self, window: &Window, event: &winit::event::WindowEvent) -> bool {
+        if !self.visible {
+            return false;
+        }
+        self.state.on_window_event(window, event).consumed
+    }
+
+    // Construye la interfaz con `run_ui` y la dibuja sobre `view` sin borrarla
+    pub fn render(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        window: &Window,
+        run_ui: impl FnOnce(&egui::Context),
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        let raw_input = self.state.take_egui_input(window);
+        let output = self.context.run(raw_input, run_ui);
+        self.state.handle_platform_output(window, output.platform_output);
+
+        let paint_jobs = self.context.tessellate(output.shapes, output.pixels_per_point);
+        let size = window.inner_size();
+        let screen = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [size.width, size.height],
+            pixels_per_point: output.pixels_per_point,
+        };
+
+        for (id, delta) in &output.textures_delta.set {
+            self.renderer.update_texture(device, queue, *id, delta);
+        }
+        self.renderer.update_buffers(device, queue, encoder, &paint_jobs, &screen);
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Debug UI Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            self.renderer.render(&mut render_pass, &paint_jobs, &screen);
+        }
+
+        for id in &output.textures_delta.free {
+            self.renderer.free_texture(id);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Cantidad de frames que se guardan para los promedios y el 1% low
+const WINDOW: usize = 1000;
+
+// Mide el tiempo entre frames y mantiene estadísticas sobre una ventana móvil
+pub struct FrameTimer {
+    last_frame: Instant,
+    samples: VecDeque<Duration>,
+    frame_count: u64,
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameTimer {
+    pub fn new() -> Self {
+        Self {
+            last_frame: Instant::now(),
+            samples: VecDeque::with_capacity(WINDOW),
+            frame_count: 0,
+        }
+    }
+
+    // Registra un frame nuevo y devuelve su duración
+    pub fn tick(&mut self) -> Duration {
+        let now = Instant::now();
+        let dt = now - self.last_frame;
+        self.last_frame = now;
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(dt);
+        self.frame_count += 1;
+        dt
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn average_ms(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let total: Duration = self.samples.iter().sum();
+        total.as_secs_f32() * 1000.0 / self.samples.len() as f32
+    }
+
+    pub fn min_ms(&self) -> f32 {
+        self.samples.iter().min().map_or(0.0, |d| d.as_secs_f32() * 1000.0)
+    }
+
+    pub fn max_ms(&self) -> f32 {
+        self.samples.iter().max().map_or(0.0, |d| d.as_secs_f32() * 1000.0)
+    }
+
+    pub fn fps(&self) -> f32 {
+        let avg = self.average_ms();
+        if avg > 0.0 { 1000.0 / avg } else { 0.0 }
+    }
+
+    // FPS promedio del 1% de frames más lentos
+    pub fn one_percent_low_fps(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+        let count = (sorted.len() / 100).max(1);
+        let slowest: Duration = sorted[..count].iter().sum();
+        let avg = slowest.as_secs_f32() / count as f32;
+        if avg > 0.0 { 1.0 / avg } else { 0.0 }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{:.1} FPS | {:.2} ms (min {:.2} / max {:.2}) | 1% low {:.1} FPS",
+            self.fps(),
+            self.average_ms(),
+            self.min_ms(),
+            self.max_ms(),
+            self.one_percent_low_fps()
+        )
+    }
+}
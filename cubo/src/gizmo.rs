@@ -0,0 +1,452 @@
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Quaternion, Rad, Rotation3, Vector3};
+
+use crate::animation::Transform;
+use crate::picking::Ray;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+    Scale,
+}
+
+impl GizmoMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            GizmoMode::Translate => "Mover (W)",
+            GizmoMode::Rotate => "Rotar (E)",
+            GizmoMode::Scale => "Escalar (R)",
+        }
+    }
+}
+
+// Ejes del gizmo: los del mundo o los del objeto. La escala siempre usa los del
+// objeto, porque Transform no puede representar una escala en otros ejes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GizmoSpace {
+    World,
+    Local,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GizmoHandle {
+    Axis(usize),
+    // Plano perpendicular al eje indicado
+    Plane(usize),
+    // Escala uniforme desde el centro
+    Uniform,
+}
+
+// Incrementos del snapping
+#[derive(Copy, Clone, Debug)]
+pub struct Snapping {
+    pub translate: f32,
+    pub rotate_degrees: f32,
+    pub scale: f32,
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Self {
+            translate: 0.25,
+            rotate_degrees: 15.0,
+            scale: 0.1,
+        }
+    }
+}
+
+// Segmento para dibujar el gizmo
+#[derive(Copy, Clone, Debug)]
+pub struct GizmoLine {
+    pub from: Point3<f32>,
+    pub to: Point3<f32>,
+    pub color: [f32; 4],
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Drag {
+    handle: GizmoHandle,
+    start: Transform,
+    axes: [Vector3<f32>; 3],
+    size: f32,
+    // Parámetro sobre el eje, punto sobre el plano o vector desde el centro al empezar
+    start_param: f32,
+    start_point: Point3<f32>,
+}
+
+// Fracciones del tamaño del gizmo
+const PLANE_MIN: f32 = 0.2;
+const PLANE_MAX: f32 = 0.45;
+const RING_RADIUS: f32 = 0.8;
+const UNIFORM_RADIUS: f32 = 0.12;
+const PICK_TOLERANCE: f32 = 0.07;
+
+const AXIS_COLORS: [[f32; 4]; 3] = [[0.95, 0.2, 0.2, 1.0], [0.3, 0.9, 0.3, 1.0], [0.25, 0.45, 1.0, 1.0]];
+const ACTIVE_COLOR: [f32; 4] = [1.0, 0.9, 0.1, 1.0];
+const UNIFORM_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+
+// Gizmo de traslación, rotación y escala. Trabaja en el espacio de los modelos: el
+// que llama pasa los rayos del cursor y aplica el Transform que devuelve `drag`.
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    pub space: GizmoSpace,
+    pub snap: bool,
+    pub snapping: Snapping,
+    pub hovered: Option<GizmoHandle>,
+    drag: Option<Drag>,
+}
+
+impl Default for Gizmo {
+    fn default() -> Self {
+        Self {
+            mode: GizmoMode::Translate,
+            space: GizmoSpace::World,
+            snap: false,
+            snapping: Snapping::default(),
+            hovered: None,
+            drag: None,
+        }
+    }
+}
+
+impl Gizmo {
+    // Largo de los ejes para que el gizmo ocupe siempre lo mismo en pantalla
+    pub fn screen_size(center: Point3<f32>, eye: Point3<f32>, fovy_degrees: f32) -> f32 {
+        (center - eye).magnitude() * (fovy_degrees.to_radians() * 0.5).tan() * 0.3
+    }
+
+    pub fn axes(&self, transform: &Transform) -> [Vector3<f32>; 3] {
+        let unit = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+        if self.space == GizmoSpace::Local || self.mode == GizmoMode::Scale {
+            unit.map(|axis| transform.rotation * axis)
+        } else {
+            unit
+        }
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    // Manija bajo el rayo, la más cercana a la cámara
+    pub fn pick_handle(&self, ray: &Ray, transform: &Transform, size: f32) -> Option<GizmoHandle> {
+        let center = Point3::from_vec(transform.translation);
+        let axes = self.axes(transform);
+        let tolerance = PICK_TOLERANCE * size;
+        let mut candidates: Vec<(f32, GizmoHandle)> = Vec::new();
+
+        match self.mode {
+            GizmoMode::Translate | GizmoMode::Scale => {
+                for (k, axis) in axes.iter().enumerate() {
+                    if let Some((t, distance)) = ray_segment(ray, center, *axis, size) {
+                        if distance < tolerance {
+                            candidates.push((t, GizmoHandle::Axis(k)));
+                        }
+                    }
+                }
+                if self.mode == GizmoMode::Translate {
+                    for k in 0..3 {
+                        let (a, b) = (axes[(k + 1) % 3], axes[(k + 2) % 3]);
+                        let Some((t, point)) = ray_plane(ray, center, axes[k]) else { continue };
+                        let (u, v) = ((point - center).dot(a), (point - center).dot(b));
+                        let range = PLANE_MIN * size..=PLANE_MAX * size;
+                        if range.contains(&u) && range.contains(&v) {
+                            candidates.push((t, GizmoHandle::Plane(k)));
+                        }
+                    }
+                } else if let Some(t) = ray_sphere(ray, center, UNIFORM_RADIUS * size) {
+                    candidates.push((t, GizmoHandle::Uniform));
+                }
+            }
+            GizmoMode::Rotate => {
+                for (k, axis) in axes.iter().enumerate() {
+                    let Some((t, point)) = ray_plane(ray, center, *axis) else { continue };
+                    if ((point - center).magnitude() - RING_RADIUS * size).abs() < tolerance {
+                        candidates.push((t, GizmoHandle::Axis(k)));
+                    }
+                }
+            }
+        }
+        candidates.into_iter().min_by(|a, b| a.0.total_cmp(&b.0)).map(|(_, handle)| handle)
+    }
+
+    // Empieza a arrastrar la manija bajo el rayo; false si no hay ninguna
+    pub fn begin(&mut self, ray: &Ray, transform: &Transform, size: f32) -> bool {
+        let Some(handle) = self.pick_handle(ray, transform, size) else {
+            return false;
+        };
+        let axes = self.axes(transform);
+        let center = Point3::from_vec(transform.translation);
+        let (start_param, start_point) = match (self.mode, handle) {
+            (GizmoMode::Translate | GizmoMode::Scale, GizmoHandle::Axis(k)) => {
+                (line_param(ray, center, axes[k]).unwrap_or(0.0), center)
+            }
+            (_, GizmoHandle::Plane(k)) | (GizmoMode::Rotate, GizmoHandle::Axis(k)) => {
+                match ray_plane(ray, center, axes[k]) {
+                    Some((_, point)) => (0.0, point),
+                    None => return false,
+                }
+            }
+            (_, GizmoHandle::Uniform) => match ray_plane(ray, center, ray.direction) {
+                Some((_, point)) => ((point - center).magnitude(), point),
+                None => return false,
+            },
+        };
+        self.drag = Some(Drag {
+            handle,
+            start: *transform,
+            axes,
+            size,
+            start_param,
+            start_point,
+        });
+        self.hovered = Some(handle);
+        true
+    }
+
+    // Transformación que resulta de mover el cursor hasta `ray`
+    pub fn drag(&self, ray: &Ray, snap: bool) -> Option<Transform> {
+        let drag = self.drag.as_ref()?;
+        let center = Point3::from_vec(drag.start.translation);
+        let snapped = |value: f32, step: f32| {
+            if snap && step > 0.0 {
+                (value / step).round() * step
+            } else {
+                value
+            }
+        };
+        let mut result = drag.start;
+
+        match (self.mode, drag.handle) {
+            (GizmoMode::Translate, GizmoHandle::Axis(k)) => {
+                let delta = line_param(ray, center, drag.axes[k])? - drag.start_param;
+                result.translation += drag.axes[k] * snapped(delta, self.snapping.translate);
+            }
+            (GizmoMode::Translate, GizmoHandle::Plane(k)) => {
+                let (_, point) = ray_plane(ray, center, drag.axes[k])?;
+                let delta = point - drag.start_point;
+                for axis in [drag.axes[(k + 1) % 3], drag.axes[(k + 2) % 3]] {
+                    result.translation += axis * snapped(delta.dot(axis), self.snapping.translate);
+                }
+            }
+            (GizmoMode::Rotate, GizmoHandle::Axis(k)) => {
+                let normal = drag.axes[k];
+                let (_, point) = ray_plane(ray, center, normal)?;
+                let (from, to) = (drag.start_point - center, point - center);
+                let angle = from.cross(to).dot(normal).atan2(from.dot(to));
+                let angle = snapped(angle.to_degrees(), self.snapping.rotate_degrees).to_radians();
+                result.rotation = (Quaternion::from_axis_angle(normal, Rad(angle)) * drag.start.rotation).normalize();
+            }
+            (GizmoMode::Scale, GizmoHandle::Axis(k)) => {
+                if drag.start_param.abs() < drag.size * 0.01 {
+                    return None;
+                }
+                let ratio = line_param(ray, center, drag.axes[k])? / drag.start_param;
+                let factor = 1.0 + snapped(ratio - 1.0, self.snapping.scale);
+                result.scale[k] = clamp_scale(drag.start.scale[k] * factor);
+            }
+            (GizmoMode::Scale, GizmoHandle::Uniform) => {
+                let (_, point) = ray_plane(ray, center, ray.direction)?;
+                let ratio = (point - center).magnitude() / drag.start_param.max(1e-6);
+                let factor = 1.0 + snapped(ratio - 1.0, self.snapping.scale);
+                result.scale = drag.start.scale.map(|s| clamp_scale(s * factor));
+            }
+            _ => return None,
+        }
+        Some(result)
+    }
+
+    // Termina el arrastre y devuelve la transformación del principio, para el historial
+    pub fn end(&mut self) -> Option<Transform> {
+        self.drag.take().map(|drag| drag.start)
+    }
+
+    // Segmentos del gizmo; la manija activa o bajo el cursor se pinta de amarillo
+    pub fn lines(&self, transform: &Transform, size: f32) -> Vec<GizmoLine> {
+        let center = Point3::from_vec(transform.translation);
+        let axes = self.axes(transform);
+        let active = self.drag.map(|drag| drag.handle).or(self.hovered);
+        let color = |handle: GizmoHandle, base: [f32; 4]| if active == Some(handle) { ACTIVE_COLOR } else { base };
+        let mut lines = Vec::new();
+        let mut line = |from: Point3<f32>, to: Point3<f32>, color: [f32; 4]| lines.push(GizmoLine { from, to, color });
+
+        match self.mode {
+            GizmoMode::Translate | GizmoMode::Scale => {
+                for k in 0..3 {
+                    let c = color(GizmoHandle::Axis(k), AXIS_COLORS[k]);
+                    let (axis, a, b) = (axes[k], axes[(k + 1) % 3], axes[(k + 2) % 3]);
+                    let tip = center + axis * size;
+                    line(center, tip, c);
+                    if self.mode == GizmoMode::Translate {
+                        // Punta de flecha
+                        let base = center + axis * size * 0.85;
+                        let r = size * 0.05;
+                        for side in [a * r, -a * r, b * r, -b * r] {
+                            line(tip, base + side, c);
+                        }
+                        // Cuadrado del plano perpendicular al eje
+                        let c = color(GizmoHandle::Plane(k), AXIS_COLORS[k]);
+                        let corner = |u: f32, v: f32| center + a * (u * size) + b * (v * size);
+                        let square = [
+                            corner(PLANE_MIN, PLANE_MIN),
+                            corner(PLANE_MAX, PLANE_MIN),
+                            corner(PLANE_MAX, PLANE_MAX),
+                            corner(PLANE_MIN, PLANE_MAX),
+                        ];
+                        for i in 0..4 {
+                            line(square[i], square[(i + 1) % 4], c);
+                        }
+                    } else {
+                        for (from, to) in box_edges(tip, axes, size * 0.05) {
+                            line(from, to, c);
+                        }
+                    }
+                }
+                if self.mode == GizmoMode::Scale {
+                    let c = color(GizmoHandle::Uniform, UNIFORM_COLOR);
+                    for (from, to) in box_edges(center, axes, size * UNIFORM_RADIUS * 0.6) {
+                        line(from, to, c);
+                    }
+                }
+            }
+            GizmoMode::Rotate => {
+                const SEGMENTS: usize = 48;
+                for k in 0..3 {
+                    let c = color(GizmoHandle::Axis(k), AXIS_COLORS[k]);
+                    let (a, b) = (axes[(k + 1) % 3], axes[(k + 2) % 3]);
+                    let point = |i: usize| {
+                        let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+                        center + (a * angle.cos() + b * angle.sin()) * (RING_RADIUS * size)
+                    };
+                    for i in 0..SEGMENTS {
+                        line(point(i), point(i + 1), c);
+                    }
+                }
+            }
+        }
+        lines
+    }
+}
+
+fn clamp_scale(scale: f32) -> f32 {
+    if scale.abs() < 1e-3 {
+        1e-3f32.copysign(scale)
+    } else {
+        scale
+    }
+}
+
+// Las 12 aristas de un cubo de medio lado `half` orientado con `axes`
+fn box_edges(center: Point3<f32>, axes: [Vector3<f32>; 3], half: f32) -> Vec<(Point3<f32>, Point3<f32>)> {
+    let corner = |i: usize| {
+        let sign = |bit: usize| if i & bit != 0 { half } else { -half };
+        center + axes[0] * sign(1) + axes[1] * sign(2) + axes[2] * sign(4)
+    };
+    let mut edges = Vec::with_capacity(12);
+    for i in 0..8 {
+        for bit in [1, 2, 4] {
+            if i & bit == 0 {
+                edges.push((corner(i), corner(i | bit)));
+            }
+        }
+    }
+    edges
+}
+
+// Intersección con el plano que pasa por `point` con normal `normal`
+fn ray_plane(ray: &Ray, point: Point3<f32>, normal: Vector3<f32>) -> Option<(f32, Point3<f32>)> {
+    let denom = ray.direction.dot(normal);
+    if denom.abs() < 1e-6 * ray.direction.magnitude() * normal.magnitude() {
+        return None;
+    }
+    let t = (point - ray.origin).dot(normal) / denom;
+    (t >= 0.0).then(|| (t, ray.at(t)))
+}
+
+fn ray_sphere(ray: &Ray, center: Point3<f32>, radius: f32) -> Option<f32> {
+    let offset = ray.origin - center;
+    let a = ray.direction.magnitude2();
+    let b = offset.dot(ray.direction);
+    let c = offset.magnitude2() - radius * radius;
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / a;
+    (t >= 0.0).then_some(t)
+}
+
+// Parámetro s del punto de la recta `origin + s * axis` más cercano al rayo
+fn line_param(ray: &Ray, origin: Point3<f32>, axis: Vector3<f32>) -> Option<f32> {
+    let w = ray.origin - origin;
+    let (a, b, c) = (ray.direction.magnitude2(), ray.direction.dot(axis), axis.magnitude2());
+    let (d, e) = (ray.direction.dot(w), axis.dot(w));
+    let denom = a * c - b * b;
+    // El rayo es casi paralelo al eje
+    if denom.abs() < 1e-6 * a * c {
+        return None;
+    }
+    Some((a * e - b * d) / denom)
+}
+
+// (t del rayo, distancia) entre el rayo y el segmento de `origin` a `origin + axis * length`
+fn ray_segment(ray: &Ray, origin: Point3<f32>, axis: Vector3<f32>, length: f32) -> Option<(f32, f32)> {
+    let s = line_param(ray, origin, axis)?.clamp(0.0, length);
+    let point = origin + axis * s;
+    let t = (point - ray.origin).dot(ray.direction) / ray.direction.magnitude2();
+    (t >= 0.0).then(|| (t, (ray.at(t) - point).magnitude()))
+}
+
+// Cambio de transformación de un objeto, para deshacer y rehacer
+#[derive(Copy, Clone, Debug)]
+pub struct TransformEdit {
+    pub object: usize,
+    pub before: Transform,
+    pub after: Transform,
+}
+
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<TransformEdit>,
+    redo: Vec<TransformEdit>,
+}
+
+impl History {
+    // Una edición nueva descarta lo que se podía rehacer
+    pub fn push(&mut self, edit: TransformEdit) {
+        self.redo.clear();
+        self.undo.push(edit);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    // La edición deshecha; el que llama aplica `before`
+    pub fn undo(&mut self) -> Option<TransformEdit> {
+        let edit = self.undo.pop()?;
+        self.redo.push(edit);
+        Some(edit)
+    }
+
+    // La edición rehecha; el que llama aplica `after`
+    pub fn redo(&mut self) -> Option<TransformEdit> {
+        let edit = self.redo.pop()?;
+        self.undo.push(edit);
+        Some(edit)
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
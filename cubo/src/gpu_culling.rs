@@ -0,0 +1,363 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+
+use crate::bounds::{BoundingSphere, Frustum};
+
+const WORKGROUP_SIZE: u32 = 64;
+
+// Objeto tal como lo lee el compute shader (ver culling.wgsl)
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GpuObject {
+    pub model: [[f32; 4]; 4],
+    pub mesh: u32,
+    _pad: u32,
+    // Capa de la textura array (base, incremento por cara), se copia a la instancia
+    pub layer: [u32; 2],
+}
+
+impl GpuObject {
+    pub fn new(model: [[f32; 4]; 4], mesh: u32, layer: [u32; 2]) -> Self {
+        Self { model, mesh, _pad: 0, layer }
+    }
+}
+
+// Tamaño de cada instancia visible: matriz, capa, vértices por cara y relleno
+// (ver Instance en culling.wgsl)
+const INSTANCE_SIZE: usize = std::mem::size_of::<[u32; 20]>();
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuMeshInfo {
+    sphere: [f32; 4],
+    base_instance: u32,
+    face_vertices: u32,
+    _pad: [u32; 2],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct FrustumUniform {
+    planes: [[f32; 4]; 6],
+    object_count: u32,
+    _pad: [u32; 3],
+}
+
+// Datos de una malla necesarios para el culling y para armar su draw indirecto
+pub struct CullMesh {
+    pub sphere: BoundingSphere,
+    pub num_indices: u32,
+    // Se copia a cada instancia visible para numerar las caras en el shader
+    pub face_vertices: u32,
+}
+
+pub struct GpuCuller {
+    pipeline: wgpu::ComputePipeline,
+    bind_group: wgpu::BindGroup,
+    frustum_buffer: wgpu::Buffer,
+    object_buffer: wgpu::Buffer,
+    visible_buffer: wgpu::Buffer,
+    indirect_buffer: wgpu::Buffer,
+    // Argumentos con instance_count = 0, se copian al inicio de cada frame
+    reset_args: Vec<u8>,
+    base_instances: Vec<u32>,
+    mesh_object_counts: Vec<u32>,
+    object_count: u32,
+    // Copia de los argumentos indirectos para contar los visibles en la CPU. Igual
+    // que en GpuTimer, la lectura es asíncrona y llega con algún frame de retraso.
+    readback_buffer: wgpu::Buffer,
+    copy_pending: bool,
+    map_pending: bool,
+    mapped: Arc<AtomicBool>,
+    visible: Option<u32>,
+}
+
+impl GpuCuller {
+    // El adaptador necesita compute shaders y ejecución indirecta
+    pub fn is_supported(adapter: &wgpu::Adapter) -> bool {
+        let flags = adapter.get_downlevel_capabilities().flags;
+        flags.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS | wgpu::DownlevelFlags::INDIRECT_EXECUTION)
+    }
+
+    pub fn new(device: &wgpu::Device, meshes: &[CullMesh], objects: &[GpuObject]) -> Self {
+        // Cada malla recibe un tramo contiguo del buffer de visibles, del tamaño
+        // de todos sus objetos, así first_instance siempre es 0.
+        let mesh_object_counts: Vec<u32> = (0..meshes.len() as u32)
+            .map(|mesh| objects.iter().filter(|o| o.mesh == mesh).count() as u32)
+            .collect();
+        let mut base_instances = Vec::with_capacity(meshes.len());
+        let mut base = 0;
+        for count in &mesh_object_counts {
+            base_instances.push(base);
+            base += count;
+        }
+
+        let mesh_infos: Vec<GpuMeshInfo> = meshes
+            .iter()
+            .zip(&base_instances)
+            .map(|(mesh, &base_instance)| GpuMeshInfo {
+                sphere: [
+                    mesh.sphere.center.x,
+                    mesh.sphere.center.y,
+                    mesh.sphere.center.z,
+                    mesh.sphere.radius,
+                ],
+                base_instance,
+                face_vertices: mesh.face_vertices,
+                _pad: [0; 2],
+            })
+            .collect();
+
+        let reset_args: Vec<u8> = meshes
+            .iter()
+            .flat_map(|mesh| {
+                wgpu::util::DrawIndexedIndirectArgs {
+                    index_count: mesh.num_indices,
+                    instance_count: 0,
+                    first_index: 0,
+                    base_vertex: 0,
+                    first_instance: 0,
+                }
+                .as_bytes()
+                .to_vec()
+            })
+            .collect();
+
+        let frustum_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Culling Frustum Buffer"),
+            size: std::mem::size_of::<FrustumUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let object_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Culling Object Buffer"),
+            contents: bytemuck::cast_slice(objects),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let mesh_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Culling Mesh Buffer"),
+            contents: bytemuck::cast_slice(&mesh_infos),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        let visible_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Visible Instance Buffer"),
+            size: (objects.len().max(1) * INSTANCE_SIZE) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        });
+
+        let indirect_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Indirect Draw Buffer"),
+            contents: &reset_args,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::INDIRECT
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+        });
+
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Indirect Readback Buffer"),
+            size: reset_args.len() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, true),
+                storage_entry(3, false),
+                storage_entry(4, false),
+            ],
+            label: Some("culling_bind_group_layout"),
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: frustum_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: object_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: mesh_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: visible_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: indirect_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("culling_bind_group"),
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Culling Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("culling.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Culling Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Culling Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "cs_main",
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        });
+
+        Self {
+            pipeline,
+            bind_group,
+            frustum_buffer,
+            object_buffer,
+            visible_buffer,
+            indirect_buffer,
+            reset_args,
+            base_instances,
+            mesh_object_counts,
+            object_count: objects.len() as u32,
+            readback_buffer,
+            copy_pending: false,
+            map_pending: false,
+            mapped: Arc::new(AtomicBool::new(false)),
+            visible: None,
+        }
+    }
+
+    // Sube las matrices de objetos animados. Debe recibir los mismos objetos, en el
+    // mismo orden y con las mismas mallas que en new().
+    pub fn update_objects(&self, queue: &wgpu::Queue, objects: &[GpuObject]) {
+        debug_assert_eq!(objects.len() as u32, self.object_count);
+        queue.write_buffer(&self.object_buffer, 0, bytemuck::cast_slice(objects));
+    }
+
+    // Reinicia los contadores y graba el compute pass de culling
+    pub fn cull(
+        &mut self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        frustum: &Frustum,
+        timestamp_writes: Option<wgpu::ComputePassTimestampWrites<'_>>,
+    ) {
+        let uniform = FrustumUniform {
+            planes: frustum.planes.map(|p| p.into()),
+            object_count: self.object_count,
+            _pad: [0; 3],
+        };
+        queue.write_buffer(&self.frustum_buffer, 0, bytemuck::cast_slice(&[uniform]));
+        queue.write_buffer(&self.indirect_buffer, 0, &self.reset_args);
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Culling Pass"),
+            timestamp_writes,
+        });
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, &self.bind_group, &[]);
+        compute_pass.dispatch_workgroups(self.object_count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        drop(compute_pass);
+
+        if !self.map_pending {
+            encoder.copy_buffer_to_buffer(&self.indirect_buffer, 0, &self.readback_buffer, 0, self.readback_buffer.size());
+            self.copy_pending = true;
+        }
+    }
+
+    // Se llama después de queue.submit: pide el mapeo y recoge los contadores listos
+    pub fn collect(&mut self, device: &wgpu::Device) {
+        if self.copy_pending {
+            self.copy_pending = false;
+            self.map_pending = true;
+            let mapped = Arc::clone(&self.mapped);
+            self.readback_buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    if result.is_ok() {
+                        mapped.store(true, Ordering::Release);
+                    }
+                });
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+
+        if self.mapped.swap(false, Ordering::Acquire) {
+            {
+                let data = self.readback_buffer.slice(..).get_mapped_range();
+                let size = std::mem::size_of::<wgpu::util::DrawIndexedIndirectArgs>();
+                let visible = data
+                    .chunks_exact(size)
+                    .map(|args| u32::from_ne_bytes([args[4], args[5], args[6], args[7]]))
+                    .sum();
+                self.visible = Some(visible);
+            }
+            self.readback_buffer.unmap();
+            self.map_pending = false;
+        }
+    }
+
+    // Objetos que pasaron el culling en el último frame leído; None hasta la primera lectura
+    pub fn visible_count(&self) -> Option<u32> {
+        self.visible
+    }
+
+    pub fn object_count(&self) -> u32 {
+        self.object_count
+    }
+
+    // Tramo del buffer de instancias visibles que corresponde a la malla,
+    // None si ningún objeto la usa
+    pub fn instances(&self, mesh: usize) -> Option<wgpu::BufferSlice<'_>> {
+        if self.mesh_object_counts[mesh] == 0 {
+            return None;
+        }
+        let offset = self.base_instances[mesh] as u64 * std::mem::size_of::<[[f32; 4]; 4]>() as u64;
+        Some(self.visible_buffer.slice(offset..))
+    }
+
+    pub fn indirect_buffer(&self) -> &wgpu::Buffer {
+        &self.indirect_buffer
+    }
+
+    // Desplazamiento de los argumentos indirectos de la malla
+    pub fn indirect_offset(&self, mesh: usize) -> wgpu::BufferAddress {
+        (mesh * std::mem::size_of::<wgpu::util::DrawIndexedIndirectArgs>()) as wgpu::BufferAddress
+    }
+}
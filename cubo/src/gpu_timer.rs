@@ -0,0 +1,146 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// Mide el tiempo de GPU de cada pass con timestamp queries.
+// Requiere Features::TIMESTAMP_QUERY; la lectura de resultados es asíncrona,
+// así que los tiempos llegan con uno o dos frames de retraso.
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    pass_names: Vec<&'static str>,
+    period_ns: f32,
+    // Se copió el resolve al readback en el frame actual
+    copy_pending: bool,
+    // Se pidió map_async y todavía no terminó
+    map_pending: bool,
+    mapped: Arc<AtomicBool>,
+    last_ms: Vec<f32>,
+}
+
+impl GpuTimer {
+    pub fn is_supported(features: wgpu::Features) -> bool {
+        features.contains(wgpu::Features::TIMESTAMP_QUERY)
+    }
+
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, pass_names: &[&'static str]) -> Self {
+        let count = pass_names.len() as u32 * 2;
+        let size = count as wgpu::BufferAddress * std::mem::size_of::<u64>() as wgpu::BufferAddress;
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Timestamp Query Set"),
+            ty: wgpu::QueryType::Timestamp,
+            count,
+        });
+
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            pass_names: pass_names.to_vec(),
+            period_ns: queue.get_timestamp_period(),
+            copy_pending: false,
+            map_pending: false,
+            mapped: Arc::new(AtomicBool::new(false)),
+            last_ms: vec![0.0; pass_names.len()],
+        }
+    }
+
+    fn pass_index(&self, name: &str) -> u32 {
+        self.pass_names
+            .iter()
+            .position(|n| *n == name)
+            .expect("Pass sin timestamp registrado") as u32
+    }
+
+    pub fn render_pass_writes(&self, name: &str) -> wgpu::RenderPassTimestampWrites<'_> {
+        let i = self.pass_index(name);
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(i * 2),
+            end_of_pass_write_index: Some(i * 2 + 1),
+        }
+    }
+
+    pub fn compute_pass_writes(&self, name: &str) -> wgpu::ComputePassTimestampWrites<'_> {
+        let i = self.pass_index(name);
+        wgpu::ComputePassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(i * 2),
+            end_of_pass_write_index: Some(i * 2 + 1),
+        }
+    }
+
+    // Se llama al final del encoder, después de todos los passes medidos
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let count = self.pass_names.len() as u32 * 2;
+        encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve_buffer, 0);
+        if !self.map_pending {
+            encoder.copy_buffer_to_buffer(
+                &self.resolve_buffer,
+                0,
+                &self.readback_buffer,
+                0,
+                self.resolve_buffer.size(),
+            );
+            self.copy_pending = true;
+        }
+    }
+
+    // Se llama después de queue.submit: pide el mapeo y recoge resultados listos
+    pub fn collect(&mut self, device: &wgpu::Device) {
+        if self.copy_pending {
+            self.copy_pending = false;
+            self.map_pending = true;
+            let mapped = Arc::clone(&self.mapped);
+            self.readback_buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    if result.is_ok() {
+                        mapped.store(true, Ordering::Release);
+                    }
+                });
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+
+        if self.mapped.swap(false, Ordering::Acquire) {
+            {
+                let data = self.readback_buffer.slice(..).get_mapped_range();
+                let timestamps: &[u64] = bytemuck::cast_slice(&data);
+                for (i, ms) in self.last_ms.iter_mut().enumerate() {
+                    let ticks = timestamps[i * 2 + 1].saturating_sub(timestamps[i * 2]);
+                    *ms = ticks as f32 * self.period_ns / 1_000_000.0;
+                }
+            }
+            self.readback_buffer.unmap();
+            self.map_pending = false;
+        }
+    }
+
+    // Último tiempo medido de cada pass, en milisegundos
+    pub fn pass_times(&self) -> impl Iterator<Item = (&'static str, f32)> + '_ {
+        self.pass_names.iter().copied().zip(self.last_ms.iter().copied())
+    }
+
+    pub fn summary(&self) -> String {
+        self.pass_times()
+            .map(|(name, ms)| format!("{} {:.3} ms", name, ms))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
@@ -0,0 +1,150 @@
+use cgmath::{Matrix4, Point3, SquareMatrix};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct GridUniforms {
+    view_proj: [[f32; 4]; 4],
+    inv_view_proj: [[f32; 4]; 4],
+    fade: [f32; 4],
+    params: [f32; 4],
+}
+
+// Parámetros de la grilla para un frame
+#[derive(Copy, Clone, Debug)]
+pub struct GridSettings {
+    // Altura del plano del suelo
+    pub height: f32,
+    // Lado de la celda menor
+    pub cell_size: f32,
+    // Cada cuántas celdas menores hay una línea mayor
+    pub major_every: u32,
+    // Ejes X (rojo) y Z (azul) sobre el plano
+    pub axes: bool,
+    // La grilla se desvanece a partir de `fade_center` hasta `fade_radius`
+    pub fade_center: Point3<f32>,
+    pub fade_radius: f32,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            height: 0.0,
+            cell_size: 0.25,
+            major_every: 4,
+            axes: true,
+            fade_center: Point3::new(0.0, 0.0, 0.0),
+            fade_radius: 20.0,
+        }
+    }
+}
+
+// Grilla infinita con antialiasing en el plano del suelo. Se dibuja con un triángulo
+// a pantalla completa después de la geometría opaca: escribe su profundidad en
+// el fragment shader para quedar detrás de los objetos, sin escribir el depth buffer.
+pub struct InfiniteGrid {
+    pipeline: wgpu::RenderPipeline,
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl InfiniteGrid {
+    pub fn new(device: &wgpu::Device, color_format: wgpu::TextureFormat, depth_format: wgpu::TextureFormat) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("grid_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Grid Uniforms"),
+            size: std::mem::size_of::<GridUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("grid_bind_group"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniforms.as_entire_binding(),
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Grid Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("grid.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Grid Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Grid Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            uniforms,
+            bind_group,
+        }
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, view_proj: &Matrix4<f32>, settings: &GridSettings) {
+        let Some(inverse) = view_proj.invert() else { return };
+        let center = settings.fade_center;
+        let uniforms = GridUniforms {
+            view_proj: (*view_proj).into(),
+            inv_view_proj: inverse.into(),
+            fade: [center.x, center.y, center.z, settings.fade_radius],
+            params: [
+                settings.height,
+                settings.cell_size,
+                settings.major_every.max(1) as f32,
+                if settings.axes { 1.0 } else { 0.0 },
+            ],
+        };
+        queue.write_buffer(&self.uniforms, 0, bytemuck::cast_slice(&[uniforms]));
+    }
+
+    // Cambia el pipeline y el grupo 0
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
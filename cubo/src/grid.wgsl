@@ -0,0 +1,98 @@
+// Grilla infinita en el plano y = altura, dibujada con un triángulo que cubre la pantalla.
+// Cada píxel reconstruye su rayo con la inversa de view_proj y lo corta con el plano,
+// así que sirve igual para perspectiva y ortográfica.
+struct GridUniforms {
+    view_proj: mat4x4<f32>,
+    inv_view_proj: mat4x4<f32>,
+    // xyz: centro del desvanecido, w: radio
+    fade: vec4<f32>,
+    // x: altura, y: celda menor, z: celdas por línea mayor, w: 1 = dibujar ejes X y Z
+    params: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> grid: GridUniforms;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Puntos del rayo del píxel en los planos cercano y lejano
+    @location(0) near_point: vec3<f32>,
+    @location(1) far_point: vec3<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @builtin(frag_depth) depth: f32,
+}
+
+fn unproject(xy: vec2<f32>, z: f32) -> vec3<f32> {
+    let p = grid.inv_view_proj * vec4<f32>(xy, z, 1.0);
+    return p.xyz / p.w;
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    // (-1, -1), (3, -1), (-1, 3)
+    let xy = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u)) * 2.0 - 1.0;
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(xy, 0.0, 1.0);
+    out.near_point = unproject(xy, 0.0);
+    out.far_point = unproject(xy, 1.0);
+    return out;
+}
+
+// Cobertura de las líneas de una grilla de celdas `cell`, con antialiasing según
+// cuánto cambia la coordenada por píxel. Se apaga cuando las líneas se amontonan.
+fn grid_lines(coord: vec2<f32>, cell: f32) -> f32 {
+    let c = coord / cell;
+    let width = fwidth(c);
+    let distance = abs(fract(c - 0.5) - 0.5) / width;
+    let coverage = 1.0 - min(min(distance.x, distance.y), 1.0);
+    let density = max(width.x, width.y);
+    return coverage * (1.0 - smoothstep(0.2, 0.5, density));
+}
+
+// Cobertura de la recta coordenada = 0
+fn axis_line(coordinate: f32) -> f32 {
+    return 1.0 - min(abs(coordinate) / (fwidth(coordinate) * 1.5), 1.0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    let ray = in.far_point - in.near_point;
+    let t = (grid.params.x - in.near_point.y) / ray.y;
+    let p = in.near_point + t * ray;
+    let clip = grid.view_proj * vec4<f32>(p, 1.0);
+    let depth = clip.z / clip.w;
+
+    let cell = grid.params.y;
+    let minor = grid_lines(p.xz, cell);
+    let major = grid_lines(p.xz, cell * grid.params.z);
+    var color = vec4<f32>(0.6, 0.6, 0.6, max(minor * 0.3, major * 0.6));
+    if (grid.params.w > 0.5) {
+        // El eje X corre por z = 0 y el eje Z por x = 0
+        let x_axis = axis_line(p.z);
+        let z_axis = axis_line(p.x);
+        if (x_axis > 0.0) {
+            color = vec4<f32>(0.95, 0.2, 0.2, max(color.a, x_axis));
+        }
+        if (z_axis > 0.0) {
+            color = vec4<f32>(0.25, 0.45, 1.0, max(color.a, z_axis));
+        }
+    }
+
+    // Se desvanece lejos del centro y cuando el plano se ve de canto
+    let distance = length(p.xz - grid.fade.xz);
+    let fade = 1.0 - smoothstep(grid.fade.w * 0.4, grid.fade.w, distance);
+    let grazing = smoothstep(0.0, 0.1, abs(normalize(ray).y));
+    color.a *= fade * grazing;
+
+    // Las derivadas ya se calcularon: ahora se puede descartar
+    if (t <= 0.0 || t > 1.0 || depth < 0.0 || depth > 1.0 || color.a <= 0.001) {
+        discard;
+    }
+    var out: FragmentOutput;
+    out.color = color;
+    out.depth = depth;
+    return out;
+}
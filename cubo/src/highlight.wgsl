@@ -0,0 +1,18 @@
+// Resaltado del objeto y el triángulo elegidos con el mouse
+struct Highlight {
+    mvp: mat4x4<f32>,
+    color: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> highlight: Highlight;
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return highlight.mvp * vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return highlight.color;
+}
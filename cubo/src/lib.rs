@@ -0,0 +1,27 @@
+pub mod animation;
+pub mod asset_loader;
+pub mod assets;
+pub mod atlas;
+mod block_decode;
+mod block_encode;
+pub mod bounds;
+pub mod capture;
+pub mod clock;
+pub mod compressed_texture;
+pub mod cooked;
+pub mod cubemap;
+pub mod debug_draw;
+pub mod debug_ui;
+pub mod frame_timer;
+pub mod gizmo;
+pub mod gpu_culling;
+pub mod gpu_timer;
+pub mod grid;
+pub mod morph;
+pub mod picking;
+pub mod primitives;
+pub mod sampler;
+pub mod scene;
+pub mod skinning;
+pub mod text;
+pub mod texture_array;
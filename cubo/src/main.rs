@@ -5,6 +5,8 @@ use winit::{
     window::WindowBuilder,
 };
 use std::sync::Arc;
+use textured_cube::frame_timer::FrameTimer;
+use textured_cube::sampler::SamplerSettings;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -153,15 +155,7 @@ fn main() {
     );
 
     let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-    let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        address_mode_u: wgpu::AddressMode::ClampToEdge,
-        address_mode_v: wgpu::AddressMode::ClampToEdge,
-        address_mode_w: wgpu::AddressMode::ClampToEdge,
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Nearest,
-        mipmap_filter: wgpu::FilterMode::Nearest,
-        ..Default::default()
-    });
+    let texture_sampler = device.create_sampler(&SamplerSettings::default().descriptor());
 
     // Buffers
     let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -296,6 +290,9 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     // Solicitar el primer redraw
     window.request_redraw();
 
+    let mut frame_timer = FrameTimer::new();
+    let mut last_stats_print = std::time::Instant::now();
+
     event_loop.run(move |event, target| {
         match event {
             Event::WindowEvent { ref event, window_id } if window_id == window.id() => {
@@ -308,7 +305,12 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
                         println!("🔥 VENTANA REDIMENSIONADA: {}x{}", physical_size.width, physical_size.height);
                     },
                     WindowEvent::RedrawRequested => {
-                        println!("🔥 REDIBUJANDO...");
+                        frame_timer.tick();
+                        if last_stats_print.elapsed().as_secs_f32() >= 1.0 {
+                            println!("🔥 {}", frame_timer.summary());
+                            window.set_title(&format!("🔥 CUBO MEDIANO - {:.1} FPS 🔥", frame_timer.fps()));
+                            last_stats_print = std::time::Instant::now();
+                        }
                         let output = surface.get_current_texture().unwrap();
                         let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
 
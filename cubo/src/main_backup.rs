@@ -3,11 +3,35 @@ use winit::{
     event::*,
     event_loop::{EventLoop},
     window::{Window, WindowBuilder},
-    keyboard::{KeyCode, PhysicalKey},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
 };
-use image::{GenericImageView, ImageBuffer, Rgba, ImageFormat};
+use image::{ImageBuffer, Rgba, ImageFormat};
 use std::sync::Arc;
-use cgmath::{Matrix4, Vector3, Point3, Deg, Rad, perspective, SquareMatrix};
+use cgmath::{EuclideanSpace, InnerSpace, Matrix3, Matrix4, Vector3, Point3, Deg, Rad, ortho, perspective, SquareMatrix};
+use textured_cube::bounds::{Aabb, BoundingSphere, Frustum};
+use textured_cube::animation::{AnimationClip, AnimationPlayer, CameraPose, Interpolation, PlaybackMode, Transform};
+use textured_cube::asset_loader::{checkerboard, AssetLoader, LoadId, LoadedAsset};
+use textured_cube::assets::{format_bytes, Assets, Handle, MaterialAsset, MeshAsset, ShaderAsset, TextureAsset, TextureSettings};
+use textured_cube::atlas::AtlasBuilder;
+use textured_cube::clock::{AnimationClock, ClockMode, MIN_TIME_SCALE};
+use textured_cube::cubemap::{Cubemap, CubemapImages};
+use textured_cube::capture::{capture_texture, save_image, timestamp, timestamp_millis, PendingCapture};
+use textured_cube::debug_draw::DebugDraw;
+use textured_cube::debug_ui::DebugUi;
+use textured_cube::frame_timer::FrameTimer;
+use textured_cube::gizmo::{Gizmo, GizmoMode, GizmoSpace, History, TransformEdit};
+use textured_cube::gpu_culling::{CullMesh, GpuCuller, GpuObject};
+use textured_cube::gpu_timer::GpuTimer;
+use textured_cube::grid::{GridSettings, InfiniteGrid};
+use textured_cube::picking::{IdBuffer, IdDraw, IdHit, MeshHit, PickMesh, Ray};
+use textured_cube::morph::{MorphMesh, MorphModel, MorphTarget, MorphVertex, WeightTrack};
+use textured_cube::primitives::{self, CubeUvLayout, MeshData, MeshVertex};
+use textured_cube::sampler::{AddressMode, BorderColor, Filter, SamplerSettings};
+use textured_cube::scene::{self, Light, MeshSource, SceneCamera, SceneFile, SceneMaterial};
+use textured_cube::skinning::{SkeletalAnimator, SkinnedMesh, SkinnedModel, SkinnedVertex};
+use textured_cube::text::{GlyphAtlas, TextRenderer};
+use textured_cube::texture_array::{TextureArray, TextureArrayImages};
+use std::io::Write;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -17,9 +41,15 @@ struct Vertex {
 }
 
 impl Vertex {
-    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+    // Layout de los buffers de Mesh, que salen del registro de assets con el formato
+    // de MeshVertex. La normal va en el buffer pero el shader no la lee.
+    fn mesh_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        Self::layout(std::mem::size_of::<MeshVertex>())
+    }
+
+    fn layout<'a>(stride: usize) -> wgpu::VertexBufferLayout<'a> {
         wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            array_stride: stride as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
             attributes: &[
                 wgpu::VertexAttribute {
@@ -51,18 +81,6 @@ const VERTICES: &[Vertex] = &[
     Vertex { position: [ 1.0,  1.0, -1.0], tex_coords: [0.0, 0.0] },
     Vertex { position: [ 1.0, -1.0, -1.0], tex_coords: [0.0, 1.0] },
     
-    // Cara izquierda
-    Vertex { position: [-1.0, -1.0, -1.0], tex_coords: [0.0, 1.0] },
-    Vertex { position: [-1.0, -1.0,  1.0], tex_coords: [1.0, 1.0] },
-    Vertex { position: [-1.0,  1.0,  1.0], tex_coords: [1.0, 0.0] },
-    Vertex { position: [-1.0,  1.0, -1.0], tex_coords: [0.0, 0.0] },
-    
-    // Cara derecha
-    Vertex { position: [ 1.0, -1.0, -1.0], tex_coords: [1.0, 1.0] },
-    Vertex { position: [ 1.0,  1.0, -1.0], tex_coords: [1.0, 0.0] },
-    Vertex { position: [ 1.0,  1.0,  1.0], tex_coords: [0.0, 0.0] },
-    Vertex { position: [ 1.0, -1.0,  1.0], tex_coords: [0.0, 1.0] },
-    
     // Cara superior
     Vertex { position: [-1.0,  1.0, -1.0], tex_coords: [0.0, 1.0] },
     Vertex { position: [-1.0,  1.0,  1.0], tex_coords: [0.0, 0.0] },
@@ -74,9 +92,25 @@ const VERTICES: &[Vertex] = &[
     Vertex { position: [ 1.0, -1.0, -1.0], tex_coords: [0.0, 1.0] },
     Vertex { position: [ 1.0, -1.0,  1.0], tex_coords: [0.0, 0.0] },
     Vertex { position: [-1.0, -1.0,  1.0], tex_coords: [1.0, 0.0] },
+    
+    // Cara derecha
+    Vertex { position: [ 1.0, -1.0, -1.0], tex_coords: [1.0, 1.0] },
+    Vertex { position: [ 1.0,  1.0, -1.0], tex_coords: [1.0, 0.0] },
+    Vertex { position: [ 1.0,  1.0,  1.0], tex_coords: [0.0, 0.0] },
+    Vertex { position: [ 1.0, -1.0,  1.0], tex_coords: [0.0, 1.0] },
+    
+    // Cara izquierda
+    Vertex { position: [-1.0, -1.0, -1.0], tex_coords: [0.0, 1.0] },
+    Vertex { position: [-1.0, -1.0,  1.0], tex_coords: [1.0, 1.0] },
+    Vertex { position: [-1.0,  1.0,  1.0], tex_coords: [1.0, 0.0] },
+    Vertex { position: [-1.0,  1.0, -1.0], tex_coords: [0.0, 0.0] },
 ];
 
-const INDICES: &[u16] = &[
+// Nombres de las caras en el orden de VERTICES e INDICES, que es también el de
+// primitives::cube
+const CUBE_FACE_NAMES: [&str; 6] = ["frontal", "trasera", "superior", "inferior", "derecha", "izquierda"];
+
+const INDICES: &[u32] = &[
     0, 1, 2,  2, 3, 0,    // Front face
     4, 5, 6,  6, 7, 4,    // Back face
     8, 9, 10, 10, 11, 8,  // Top face
@@ -85,16 +119,296 @@ const INDICES: &[u16] = &[
     20, 21, 22, 22, 23, 20, // Left face
 ];
 
+// Vértice desindexado con coordenadas baricéntricas, para el wireframe
+// cuando el adaptador no soporta PolygonMode::Line
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct WireVertex {
+    position: [f32; 3],
+    tex_coords: [f32; 2],
+    barycentric: [f32; 3],
+}
+
+impl WireVertex {
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<WireVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 5]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+            ],
+        }
+    }
+
+    fn expand(vertices: &[MeshVertex], indices: &[u32]) -> Vec<WireVertex> {
+        const CORNERS: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        indices
+            .iter()
+            .enumerate()
+            .map(|(i, &index)| {
+                let v = vertices[index as usize];
+                WireVertex {
+                    position: v.position,
+                    tex_coords: v.tex_coords,
+                    barycentric: CORNERS[i % 3],
+                }
+            })
+            .collect()
+    }
+}
+
+// Matriz de modelo por instancia y capa de la textura array. Tiene el mismo
+// formato que las instancias que escribe el culling en GPU.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct InstanceRaw {
+    model: [[f32; 4]; 4],
+    // (capa base, incremento por cara)
+    layer: [u32; 2],
+    // Vértices por cara de la malla; 0 = la malla no tiene caras numeradas
+    face_vertices: u32,
+    _pad: u32,
+}
+
+impl InstanceRaw {
+    fn new(model: [[f32; 4]; 4], layer: [u32; 2], face_vertices: u32) -> Self {
+        Self { model, layer, face_vertices, _pad: 0 }
+    }
+
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 10,
+                    format: wgpu::VertexFormat::Uint32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 18]>() as wgpu::BufferAddress,
+                    shader_location: 11,
+                    format: wgpu::VertexFormat::Uint32,
+                },
+            ],
+        }
+    }
+}
+
+// Geometría en GPU junto con sus volúmenes envolventes calculados al cargarla
+struct Mesh {
+    // Vértices e índices en GPU. Salen del registro, así que las mallas con la misma
+    // clave comparten los buffers.
+    asset: Handle<MeshAsset>,
+    // Copia desindexada para el wireframe baricéntrico (un vértice por índice)
+    wire_vertex_buffer: Option<wgpu::Buffer>,
+    aabb: Aabb,
+    sphere: BoundingSphere,
+    // De dónde sale la geometría, para guardar la escena
+    source: MeshSource,
+    // Índice en State::materials; None = textura del visor
+    material: Option<usize>,
+    // Copia en CPU con BVH para el picking por rayo
+    pick: PickMesh,
+    // 4 si cada par de triángulos usa sus propios 4 vértices (los cubos), 0 si no.
+    // Sin esto el ID de cara no significa nada.
+    face_vertices: u32,
+}
+
+impl Mesh {
+    // `key` identifica la geometría en el registro de assets
+    fn new(device: &wgpu::Device, assets: &mut Assets, key: &std::path::Path, data: &MeshData, barycentric_wireframe: bool) -> Self {
+        let asset = assets.insert_mesh(device, key, data);
+        let label = key.display();
+
+        let wire_vertex_buffer = barycentric_wireframe.then(|| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{} Wire Vertex Buffer", label)),
+                contents: bytemuck::cast_slice(&WireVertex::expand(&data.vertices, &data.indices)),
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+        });
+
+        let positions = data.vertices.iter().map(|v| v.position);
+        let pick = PickMesh::new(
+            positions.clone().collect(),
+            data.vertices.iter().map(|v| v.tex_coords).collect(),
+            data.indices.clone(),
+        );
+
+        Self {
+            asset,
+            wire_vertex_buffer,
+            aabb: Aabb::from_points(positions.clone()),
+            sphere: BoundingSphere::from_points(positions),
+            source: MeshSource::Primitive("cube".to_string()),
+            material: None,
+            pick,
+            face_vertices: if has_quad_faces(&data.indices) { 4 } else { 0 },
+        }
+    }
+
+    fn from_data(
+        device: &wgpu::Device,
+        assets: &mut Assets,
+        key: &std::path::Path,
+        data: &MeshData,
+        barycentric_wireframe: bool,
+    ) -> Result<Self, String> {
+        if let Some(&index) = data.indices.iter().find(|&&i| i as usize >= data.vertices.len()) {
+            return Err(format!("{} tiene el índice {} con solo {} vértices", key.display(), index, data.vertices.len()));
+        }
+        Ok(Self::new(device, assets, key, data, barycentric_wireframe))
+    }
+
+    // El cubo de VERTICES; tiene su propia clave porque no es la primitiva "cube"
+    fn cube(device: &wgpu::Device, assets: &mut Assets, barycentric_wireframe: bool) -> Self {
+        let data = MeshData {
+            vertices: VERTICES
+                .iter()
+                .map(|v| MeshVertex { position: v.position, tex_coords: v.tex_coords, normal: [0.0; 3] })
+                .collect(),
+            indices: INDICES.to_vec(),
+        };
+        Self::new(device, assets, std::path::Path::new("viewer:cube"), &data, barycentric_wireframe)
+    }
+}
+
+// Clave de la geometría de una fuente en el registro de assets. Las primitivas
+// dependen también del atlas, pero este no cambia durante la sesión.
+fn mesh_key(source: &MeshSource) -> std::path::PathBuf {
+    match source {
+        MeshSource::Primitive(name) => format!("primitive:{}", name).into(),
+        MeshSource::File(path) => path.clone(),
+    }
+}
+
+// Cada cara son dos triángulos seguidos que usan solo los vértices 4k..4k+3
+fn has_quad_faces(indices: &[u32]) -> bool {
+    indices.len().is_multiple_of(6)
+        && indices
+            .chunks_exact(6)
+            .enumerate()
+            .all(|(face, quad)| quad.iter().all(|&i| i as usize / 4 == face))
+}
+
+struct SceneObject {
+    mesh: usize,
+    // Capa de la textura array: (base, incremento por cara)
+    layer: [u32; 2],
+    // Transformación sin animar; model es el resultado de aplicarle el clip actual
+    base: Transform,
+    model: Matrix4<f32>,
+}
+
+// Clips cargados con --animation y su reproducción
+struct AnimationState {
+    clips: Vec<AnimationClip>,
+    current: usize,
+    player: AnimationPlayer,
+}
+
+// Modelo con esqueleto cargado con --skinned, siempre en el origen y sin culling
+struct SkinnedCharacter {
+    model: SkinnedModel,
+    mesh: SkinnedMesh,
+    animator: SkeletalAnimator,
+    // Una sola instancia con la matriz identidad
+    instance_buffer: wgpu::Buffer,
+    pipeline: wgpu::RenderPipeline,
+    wireframe_pipeline: Option<wgpu::RenderPipeline>,
+    // Duración del crossfade al elegir otro clip en la interfaz
+    fade_time: f32,
+}
+
+// Malla con morph targets (--morph o --morph-cube), dibujada sobre la rejilla
+struct MorphObject {
+    model: MorphModel,
+    mesh: MorphMesh,
+    instance_buffer: wgpu::Buffer,
+    pipeline: wgpu::RenderPipeline,
+    wireframe_pipeline: Option<wgpu::RenderPipeline>,
+    player: AnimationPlayer,
+    // Pista de pesos en reproducción; None = pesos editados a mano
+    track: Option<usize>,
+    weights: Vec<f32>,
+}
+
+// Objetos dibujados y descartados en el último frame
+#[derive(Default)]
+struct RenderStats {
+    drawn: u32,
+    culled: u32,
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 struct Uniforms {
     view_proj: [[f32; 4]; 4],
+    // Luz direccional: dirección en la que viaja la luz
+    light_direction: [f32; 3],
+    ambient: f32,
+    light_color: [f32; 3],
+    light_intensity: f32,
+    // Modo de visualización (ver DebugView)
+    debug_view: u32,
+    znear: f32,
+    zfar: f32,
+    // Capas de la textura array; la capa de cada cara se repite con este módulo
+    texture_layers: u32,
+    // 1 con la cámara ortográfica, para linealizar la profundidad en VIEW_DEPTH
+    orthographic: u32,
+    _pad: [u32; 3],
 }
 
 impl Uniforms {
     fn new() -> Self {
         Self {
             view_proj: Matrix4::identity().into(),
+            light_direction: [-0.5, -1.0, -0.3],
+            ambient: 0.35,
+            light_color: [1.0, 1.0, 1.0],
+            light_intensity: 0.8,
+            debug_view: DebugView::Lit as u32,
+            znear: 0.1,
+            zfar: 100.0,
+            texture_layers: 1,
+            orthographic: 0,
+            _pad: [0; 3],
         }
     }
 
@@ -111,10 +425,412 @@ struct Camera {
     fovy: f32,
     znear: f32,
     zfar: f32,
+    orthographic: bool,
 }
 
+// cgmath usa z de -1 a 1 como OpenGL; wgpu recorta de 0 a 1
+#[rustfmt::skip]
+const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
 impl Camera {
-    // (No methods needed here as build_view_projection_matrix was unused)
+    // La ortográfica muestra a la distancia del objetivo lo mismo que la perspectiva.
+    // Las dos dejan la profundidad de 0 a 1, como la espera wgpu.
+    fn projection(&self) -> Matrix4<f32> {
+        let projection = if self.orthographic {
+            let half_height = (self.eye - self.target).magnitude() * (self.fovy.to_radians() * 0.5).tan();
+            let half_width = half_height * self.aspect;
+            ortho(-half_width, half_width, -half_height, half_height, self.znear, self.zfar)
+        } else {
+            perspective(Deg(self.fovy), self.aspect, self.znear, self.zfar)
+        };
+        OPENGL_TO_WGPU_MATRIX * projection
+    }
+}
+
+// Modos de visualización para depurar la geometría y las UVs, se eligen con las teclas 1-7
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum DebugView {
+    Lit = 0,
+    Wireframe = 1,
+    UvChecker = 2,
+    UvColor = 3,
+    Normals = 4,
+    Depth = 5,
+    FaceId = 6,
+}
+
+impl DebugView {
+    const ALL: [DebugView; 7] = [
+        DebugView::Lit,
+        DebugView::Wireframe,
+        DebugView::UvChecker,
+        DebugView::UvColor,
+        DebugView::Normals,
+        DebugView::Depth,
+        DebugView::FaceId,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            DebugView::Lit => "Textura + luz",
+            DebugView::Wireframe => "Wireframe",
+            DebugView::UvChecker => "Checker UV",
+            DebugView::UvColor => "UV como color",
+            DebugView::Normals => "Normales",
+            DebugView::Depth => "Profundidad lineal",
+            DebugView::FaceId => "ID de cara",
+        }
+    }
+
+    fn from_key(key: KeyCode) -> Option<Self> {
+        let index = match key {
+            KeyCode::Digit1 => 0,
+            KeyCode::Digit2 => 1,
+            KeyCode::Digit3 => 2,
+            KeyCode::Digit4 => 3,
+            KeyCode::Digit5 => 4,
+            KeyCode::Digit6 => 5,
+            KeyCode::Digit7 => 6,
+            _ => return None,
+        };
+        Some(Self::ALL[index])
+    }
+}
+
+// Cómo se resuelve el clic: rayo contra los triángulos en CPU, o el ID buffer que
+// dibuja la GPU (exacto al píxel; el triángulo sale del rayo contra ese objeto)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PickMode {
+    Ray,
+    IdBuffer,
+}
+
+impl PickMode {
+    fn name(self) -> &'static str {
+        match self {
+            PickMode::Ray => "Rayo (CPU)",
+            PickMode::IdBuffer => "ID buffer (GPU)",
+        }
+    }
+}
+
+// Objeto elegido con el mouse
+#[derive(Copy, Clone, Debug)]
+struct Selection {
+    object: usize,
+    triangle: Option<usize>,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct HighlightUniforms {
+    mvp: [[f32; 4]; 4],
+    color: [f32; 4],
+}
+
+// Pipeline translúcido que vuelve a dibujar el objeto elegido y su triángulo encima
+// de la escena
+struct Highlight {
+    pipeline: wgpu::RenderPipeline,
+    object_uniforms: wgpu::Buffer,
+    object_bind_group: wgpu::BindGroup,
+    triangle_uniforms: wgpu::Buffer,
+    triangle_bind_group: wgpu::BindGroup,
+    // Los tres vértices del triángulo elegido, en espacio de objeto
+    triangle_buffer: wgpu::Buffer,
+}
+
+impl Highlight {
+    fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("highlight_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let uniforms = |label: &str| {
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: std::mem::size_of::<HighlightUniforms>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(label),
+                layout: &layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+            (buffer, bind_group)
+        };
+        let (object_uniforms, object_bind_group) = uniforms("Highlight Object");
+        let (triangle_uniforms, triangle_bind_group) = uniforms("Highlight Triangle");
+        let triangle_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Highlight Triangle Buffer"),
+            size: (3 * std::mem::size_of::<MeshVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Highlight Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("highlight.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Highlight Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Highlight Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                // Lee los mismos vértices que las mallas; el triángulo también se sube así
+                buffers: &[Vertex::mesh_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            // Misma geometría que la escena: el bias la acerca para que gane el depth test
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: -2,
+                    slope_scale: -1.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            object_uniforms,
+            object_bind_group,
+            triangle_uniforms,
+            triangle_bind_group,
+            triangle_buffer,
+        }
+    }
+
+    fn update(&self, queue: &wgpu::Queue, mvp: Matrix4<f32>) {
+        let write = |buffer: &wgpu::Buffer, color: [f32; 4]| {
+            let uniforms = HighlightUniforms { mvp: mvp.into(), color };
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[uniforms]));
+        };
+        write(&self.object_uniforms, [1.0, 0.85, 0.2, 0.25]);
+        write(&self.triangle_uniforms, [1.0, 0.3, 0.1, 0.7]);
+    }
+}
+
+// Parámetros del renderer ajustables desde la interfaz de depuración
+struct RenderSettings {
+    rotation_speed: f32,
+    clear_color: [f32; 3],
+    // Sampler de la textura del visor
+    sampler: SamplerSettings,
+    view: DebugView,
+    pick_mode: PickMode,
+    helpers: Helpers,
+}
+
+// Ayudas de DebugDraw y la grilla que se muestran sobre la escena
+#[derive(Default)]
+struct Helpers {
+    boxes: bool,
+    spheres: bool,
+    axes: bool,
+    light: bool,
+    // Grilla infinita del suelo con los ejes (G)
+    grid: bool,
+    grid_settings: GridSettings,
+    // proj * view de la cámara en el momento de congelarlo
+    frozen_frustum: Option<Matrix4<f32>>,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            rotation_speed: 1.0,
+            clear_color: [0.1, 0.2, 0.3],
+            sampler: SamplerSettings::default(),
+            view: DebugView::Lit,
+            pick_mode: PickMode::Ray,
+            helpers: Helpers::default(),
+        }
+    }
+}
+
+// Opciones de línea de comandos
+struct Options {
+    // --grid N dibuja una rejilla de N x N cubos (útil para probar el culling)
+    grid: u32,
+    // --gpu-culling activa el culling en compute shader (si el adaptador lo permite)
+    gpu_culling: bool,
+    // --csv archivo.csv guarda los tiempos de cada frame
+    csv: Option<std::path::PathBuf>,
+    // --record empieza a grabar la secuencia de PNG desde el primer frame
+    record: bool,
+    // --record-fps N paso de tiempo simulado de la grabación (por defecto 60)
+    record_fps: u32,
+    // --fixed-step N avanza la animación 1/N s por frame en lugar de usar el reloj
+    fixed_step: Option<u32>,
+    // --animation archivo.ron|.json|.gltf|.glb carga clips de keyframes
+    animation: Option<std::path::PathBuf>,
+    // --playback once|loop|ping-pong (por defecto loop)
+    playback: PlaybackMode,
+    // --skinned modelo.gltf|.glb carga una malla con esqueleto y sus clips
+    skinned: Option<std::path::PathBuf>,
+    // --morph modelo.gltf|.glb carga una malla con morph targets
+    morph: Option<std::path::PathBuf>,
+    // --morph-cube usa un cubo de ejemplo con dos morph targets
+    morph_cube: bool,
+    // --shape cube|atlas-cube|sphere|icosphere|cylinder|cone|torus|plane|capsule
+    // reemplaza el cubo de la rejilla por una primitiva generada
+    shape: Option<String>,
+    // --atlas carpeta empaqueta las imágenes front/back/top/bottom/right/left de la
+    // carpeta en un atlas y lo usa como textura del cubo
+    atlas: Option<std::path::PathBuf>,
+    // --cubemap carpeta|imagen texturiza las mallas con un cubemap (seis imágenes,
+    // o una sola en cruz o en tira) muestreado con la dirección en espacio del objeto
+    cubemap: Option<std::path::PathBuf>,
+    // --texture-array carpeta|tira usa una textura array (una capa por imagen, o una
+    // tira vertical de capas cuadradas)
+    texture_array: Option<std::path::PathBuf>,
+    // --layer-mode face|instance: cada cara usa una capa distinta (por defecto) o
+    // cada objeto de la rejilla usa una capa para todo el cubo
+    layer_per_instance: bool,
+    // --texture archivo usa esa textura en lugar de assets/texture.jpg. Los .ktx2,
+    // .dds y .cubotex se suben ya comprimidos con sus mipmaps si el adaptador soporta
+    // el formato
+    texture: Option<std::path::PathBuf>,
+    // --mesh archivo.cubomesh reemplaza el cubo por una malla cocinada con cubo-cook
+    mesh: Option<std::path::PathBuf>,
+    // --scene escena.ron|.json arma la escena (mallas, materiales, luces y cámara)
+    // desde un archivo en lugar de la rejilla. F5 guarda la escena actual en él.
+    scene: Option<std::path::PathBuf>,
+    // --font fuente.ttf|.otf para el HUD (por defecto assets/fonts/DejaVuSansMono.ttf)
+    font: std::path::PathBuf,
+}
+
+impl Options {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let value = |name: &str| {
+            args.iter()
+                .position(|a| a == name)
+                .and_then(|i| args.get(i + 1))
+                .cloned()
+        };
+        Self {
+            grid: match value("--grid").map(|n| n.parse::<u32>()) {
+                Some(Ok(n)) if n > 0 => n,
+                Some(_) => {
+                    println!("⚠️  --grid necesita un entero mayor que 0, se usa 1");
+                    1
+                }
+                None => 1,
+            },
+            gpu_culling: args.iter().any(|a| a == "--gpu-culling"),
+            csv: value("--csv").map(Into::into),
+            record: args.iter().any(|a| a == "--record"),
+            record_fps: match value("--record-fps").map(|n| n.parse::<u32>()) {
+                Some(Ok(fps)) if fps > 0 => fps,
+                Some(_) => {
+                    println!("⚠️  --record-fps necesita un entero mayor que 0, se usan 60");
+                    60
+                }
+                None => 60,
+            },
+            fixed_step: match value("--fixed-step").map(|n| n.parse::<u32>()) {
+                Some(Ok(fps)) if fps > 0 => Some(fps),
+                Some(_) => {
+                    println!("⚠️  --fixed-step necesita un entero mayor que 0, se usa el reloj normal");
+                    None
+                }
+                None => None,
+            },
+            animation: value("--animation").map(Into::into),
+            playback: match value("--playback").as_deref() {
+                Some("once") => PlaybackMode::Once,
+                Some("ping-pong") => PlaybackMode::PingPong,
+                _ => PlaybackMode::Loop,
+            },
+            skinned: value("--skinned").map(Into::into),
+            morph: value("--morph").map(Into::into),
+            morph_cube: args.iter().any(|a| a == "--morph-cube"),
+            shape: value("--shape"),
+            atlas: value("--atlas").map(Into::into),
+            cubemap: value("--cubemap").map(Into::into),
+            texture_array: value("--texture-array").map(Into::into),
+            layer_per_instance: value("--layer-mode").as_deref() == Some("instance"),
+            texture: value("--texture").map(Into::into),
+            mesh: value("--mesh").map(Into::into),
+            scene: value("--scene").map(Into::into),
+            font: value("--font").map_or_else(|| "assets/fonts/DejaVuSansMono.ttf".into(), Into::into),
+        }
+    }
+}
+
+// Grabación de frames como secuencia numerada de PNG con paso de tiempo fijo
+struct Recorder {
+    dir: std::path::PathBuf,
+    frame: u32,
+    // Modo del reloj antes de grabar, se restaura al terminar
+    previous_clock_mode: ClockMode,
+}
+
+impl Recorder {
+    fn start(fps: u32, clock: &mut AnimationClock) -> Self {
+        let dir = std::path::Path::new("recordings").join(format!("grabacion_{}", timestamp()));
+        println!("🎬 Grabando a {} FPS en {}", fps, dir.display());
+        let previous_clock_mode = clock.mode();
+        clock.set_mode(ClockMode::FixedStep { step: 1.0 / fps.max(1) as f64 });
+        Self {
+            dir,
+            frame: 0,
+            previous_clock_mode,
+        }
+    }
+
+    fn next_path(&mut self) -> std::path::PathBuf {
+        let path = self.dir.join(format!("frame_{:05}.png", self.frame));
+        self.frame += 1;
+        path
+    }
 }
 
 struct State {
@@ -124,10 +840,76 @@ struct State {
     config: wgpu::SurfaceConfiguration,
     size: winit::dpi::PhysicalSize<u32>,
     render_pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
-    num_indices: u32,
+    // Solo existe si el adaptador soporta Features::POLYGON_MODE_LINE
+    wireframe_pipeline: Option<wgpu::RenderPipeline>,
+    // Alternativa al anterior con coordenadas baricéntricas
+    barycentric_pipeline: Option<wgpu::RenderPipeline>,
+    meshes: Vec<Mesh>,
+    objects: Vec<SceneObject>,
+    instance_buffer: wgpu::Buffer,
+    // Culling en compute shader + draws indirectos; None = culling en CPU
+    gpu_culler: Option<GpuCuller>,
+    stats: RenderStats,
+    last_stats_print: std::time::Instant,
+    frame_timer: FrameTimer,
+    // Tiempos de GPU por pass, solo si el adaptador soporta TIMESTAMP_QUERY
+    gpu_timer: Option<GpuTimer>,
+    csv: Option<std::io::BufWriter<std::fs::File>>,
+    diffuse_texture: Texture,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
     diffuse_bind_group: wgpu::BindGroup,
+    // Para el HUD
+    texture_name: String,
+    // Texturas y shaders compartidos, con su memoria de GPU
+    assets: Assets,
+    // Materiales de --scene, indexados por Mesh::material
+    materials: Vec<Material>,
+    // Archivo donde F5 guarda la escena
+    scene_path: std::path::PathBuf,
+    // Luces de la escena tal como se leyeron
+    scene_lights: Vec<Light>,
+    // Posición del mouse en píxeles físicos, para el picking
+    cursor: Option<(f32, f32)>,
+    selection: Option<Selection>,
+    highlight: Highlight,
+    // Gizmo del objeto elegido y el historial de sus ediciones
+    gizmo: Gizmo,
+    history: History,
+    modifiers: ModifiersState,
+    // Se crea con el primer clic en modo ID buffer
+    id_buffer: Option<IdBuffer>,
+    // Líneas de ayuda del frame: cajas, luz, frustum y el gizmo
+    debug_draw: DebugDraw,
+    grid: InfiniteGrid,
+    // HUD y etiquetas; None si no se pudo cargar la fuente
+    text: Option<TextRenderer>,
+    hud_visible: bool,
+    // Texturas y mallas que se decodifican en segundo plano
+    asset_loader: AssetLoader,
+    pending_texture: Option<PendingLoad>,
+    // Texturas a probar si falla la que se está cargando
+    texture_fallbacks: Vec<TextureCandidate>,
+    pending_mesh: Option<PendingLoad>,
+    // Texturas de materiales y mallas de --scene, una carga por ruta
+    pending_materials: Vec<PendingLoad>,
+    pending_scene_meshes: Vec<PendingLoad>,
+    settings: RenderSettings,
+    // Ajustes con los que se pidió el sampler actual
+    applied_sampler: SamplerSettings,
+    clock: AnimationClock,
+    rotation_time: f32,
+    animation: AnimationState,
+    skinned: Option<SkinnedCharacter>,
+    morph: Option<MorphObject>,
+    // F12 pide una captura del siguiente frame
+    screenshot_requested: bool,
+    recorder: Option<Recorder>,
+    record_fps: u32,
+    // El reloj no avanza hasta que el cargador queda libre (--record, --fixed-step)
+    wait_for_assets: bool,
+    // --record empieza a grabar cuando se deja de esperar los assets
+    record_after_load: bool,
+    debug_ui: DebugUi,
     camera: Camera,
     uniforms: Uniforms,
     uniform_buffer: wgpu::Buffer,
@@ -138,7 +920,7 @@ struct State {
 }
 
 impl State {
-    async fn new(window: Window) -> State {
+    async fn new(window: Window, options: &Options, clock: AnimationClock) -> State {
         let window = Arc::new(window);
         let size = window.inner_size();
 
@@ -163,7 +945,13 @@ impl State {
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
+                    required_features: adapter.features()
+                        & (wgpu::Features::TIMESTAMP_QUERY
+                            | wgpu::Features::POLYGON_MODE_LINE
+                            | wgpu::Features::TEXTURE_COMPRESSION_BC
+                            | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+                            | wgpu::Features::TEXTURE_COMPRESSION_ASTC
+                            | wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER),
                     required_limits: wgpu::Limits::default(),
                     label: None,
                 },
@@ -180,8 +968,10 @@ impl State {
             .find(|f| f.is_srgb())
             .unwrap_or(surface_caps.formats[0]);
 
+        // COPY_SRC permite copiar el frame a la CPU para capturas y grabaciones
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC),
             format: surface_format,
             width: size.width,
             height: size.height,
@@ -210,10 +1000,81 @@ impl State {
 
         let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        // Crear textura (intentar cargar desde archivo o generar una por defecto)
-        let diffuse_texture = create_or_load_texture(&device, &queue).await;
+        // Crear textura: se muestra un tablero mientras se decodifica en segundo plano.
+        // Si la textura es un atlas, cada cara del cubo usa su región
+        let mut assets = Assets::new();
+        let mut asset_loader = AssetLoader::with_default_threads();
+        let (mut diffuse_texture, mut pending_texture, mut texture_fallbacks, cube_layout) = create_or_load_texture(
+            &device,
+            &queue,
+            options.atlas.as_deref(),
+            options.texture.as_deref(),
+            &mut assets,
+            &mut asset_loader,
+        );
+
+        // El cubemap o la textura array reemplazan a la vista 2D y usan su propio
+        // layout y fragment shader. Se guardan en el registro como las demás texturas.
+        let gpu_settings = TextureSettings { srgb: true, mipmaps: false };
+        let cubemap = options.cubemap.as_ref().and_then(|path| match CubemapImages::load(path) {
+            Ok(images) => {
+                println!("✅ Cubemap de {} x {} cargado desde {}", images.size, images.size, path.display());
+                let cubemap = Cubemap::new(&device, &queue, &images, "Cubemap");
+                Some(assets.insert_gpu_texture(path, gpu_settings, cubemap.texture, cubemap.view))
+            }
+            Err(e) => {
+                println!("⚠️  No se pudo cargar el cubemap {}: {}", path.display(), e);
+                None
+            }
+        });
+        let texture_array = if cubemap.is_some() {
+            None
+        } else {
+            options.texture_array.as_ref().and_then(|path| {
+                let array = TextureArrayImages::load(path)
+                    .and_then(|images| TextureArray::new(&device, &queue, &images, "Texture Array"));
+                match array {
+                    Ok(array) => {
+                        println!("✅ Textura array con {} capas cargada desde {}", array.layers, path.display());
+                        Some(assets.insert_gpu_texture(path, gpu_settings, array.texture, array.view))
+                    }
+                    Err(e) => {
+                        println!("⚠️  No se pudo cargar la textura array {}: {}", path.display(), e);
+                        None
+                    }
+                }
+            })
+        };
+        let texture_layers = texture_array.as_ref().map_or(1, |array| array.texture.depth_or_array_layers());
+        let mut fragment_entry_point = "fs_main";
+        let mut texture_bind_group_layout = None;
+        let mut texture_name = diffuse_texture.asset.name().to_string();
+        if cubemap.is_some() || texture_array.is_some() {
+            // La textura 2D no se usa, se ignora cuando termine de cargarse
+            pending_texture = None;
+            texture_fallbacks.clear();
+        }
+        if let Some(cubemap) = cubemap {
+            diffuse_texture.view = cubemap.texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::Cube),
+                ..Default::default()
+            });
+            texture_name = format!("cubemap {}", cubemap.name());
+            diffuse_texture.asset = cubemap;
+            fragment_entry_point = "fs_cubemap";
+            texture_bind_group_layout = Some(Cubemap::bind_group_layout(&device));
+        } else if let Some(array) = texture_array {
+            diffuse_texture.view = array.texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2Array),
+                ..Default::default()
+            });
+            texture_name = format!("array {}", array.name());
+            diffuse_texture.asset = array;
+            fragment_entry_point = "fs_array";
+            texture_bind_group_layout = Some(TextureArray::bind_group_layout(&device));
+        }
 
-        let texture_bind_group_layout =
+        let texture_bind_group_layout = texture_bind_group_layout.unwrap_or_else(|| {
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 entries: &[
                     wgpu::BindGroupLayoutEntry {
@@ -234,38 +1095,79 @@ impl State {
                     },
                 ],
                 label: Some("texture_bind_group_layout"),
-            });
+            })
+        });
 
-        let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &texture_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
-                },
-            ],
-            label: Some("diffuse_bind_group"),
+        let diffuse_bind_group =
+            create_texture_bind_group(&device, &texture_bind_group_layout, &diffuse_texture);
+
+        // --scene reemplaza la rejilla, la cámara, las luces y el fondo
+        let scene_file = options.scene.as_deref().and_then(|path| match SceneFile::load(path) {
+            Ok(scene) => {
+                println!("✅ Escena con {} objetos cargada de {}", scene.objects.len(), path.display());
+                Some(scene)
+            }
+            Err(e) => {
+                println!("⚠️  No se pudo cargar la escena {}: {}", path.display(), e);
+                None
+            }
+        });
+        // Se guarda un material por cada uno declarado, cargue o no su textura, para que
+        // los índices de los objetos sigan valiendo y la escena se guarde como se leyó.
+        // Las texturas se cargan en segundo plano; mientras tanto se usa la del visor.
+        let materials: Vec<Material> = scene_file.as_ref().map_or_else(Vec::new, |scene| {
+            scene
+                .materials
+                .iter()
+                .map(|desc| Material {
+                    desc: desc.clone(),
+                    sampler: scene.material_sampler(desc),
+                    asset: None,
+                })
+                .collect()
         });
+        // Los materiales usan el layout de la textura 2D
+        let pending_materials = if fragment_entry_point == "fs_main" {
+            queue_material_textures(&mut asset_loader, &materials)
+        } else {
+            if !materials.is_empty() {
+                println!("⚠️  Con cubemap o textura array se ignoran los materiales de la escena");
+            }
+            Vec::new()
+        };
 
         // Cámara - Posición más alejada para ver el cubo completo
+        let scene_camera = scene_file.as_ref().map_or_else(SceneCamera::default, |scene| scene.camera);
         let camera = Camera {
-            eye: (0.0, 0.0, 3.0).into(),  
-            target: (0.0, 0.0, 0.0).into(),
+            eye: scene_camera.eye.into(),
+            target: scene_camera.target.into(),
             up: Vector3::unit_y(),
             aspect: config.width as f32 / config.height as f32,
-            fovy: 45.0,
-            znear: 0.1,
-            zfar: 100.0,
+            fovy: scene_camera.fovy,
+            znear: scene_camera.znear,
+            zfar: scene_camera.zfar,
+            orthographic: scene_camera.orthographic,
         };
 
         let mut uniforms = Uniforms::new();
+        uniforms.texture_layers = texture_layers;
+        if let Some(scene) = &scene_file {
+            if let Some(ambient) = scene.ambient() {
+                uniforms.ambient = ambient;
+            }
+            if let Some((direction, color, intensity)) = scene.directional() {
+                if Vector3::from(direction).magnitude2() > 0.0 {
+                    uniforms.light_direction = direction;
+                } else {
+                    println!("⚠️  La luz direccional de la escena tiene dirección nula, se usa la por defecto");
+                }
+                uniforms.light_color = color;
+                uniforms.light_intensity = intensity;
+            }
+        }
         uniforms.update_view_proj(
             Matrix4::look_at_rh(camera.eye, camera.target, camera.up),
-            perspective(Deg(camera.fovy), camera.aspect, camera.znear, camera.zfar),
+            camera.projection(),
         );
 
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -278,7 +1180,7 @@ impl State {
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -298,10 +1200,10 @@ impl State {
             label: Some("uniform_bind_group"),
         });
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
-        });
+        let shader = SceneShader {
+            shader: assets.shader_from_source(&device, "shader.wgsl", include_str!("shader.wgsl")),
+            fragment_entry_point,
+        };
 
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -310,62 +1212,293 @@ impl State {
                 push_constant_ranges: &[],
             });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[Vertex::desc()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None, // Desactivar culling para asegurar que se vean todas las caras
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
+        let render_pipeline = create_scene_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            config.format,
+            wgpu::PolygonMode::Fill,
+            "vs_main",
+            Vertex::mesh_desc(),
+        );
+
+        let polygon_mode_line = device.features().contains(wgpu::Features::POLYGON_MODE_LINE);
+        let wireframe_pipeline = polygon_mode_line.then(|| {
+            create_scene_pipeline(
+                &device,
+                &render_pipeline_layout,
+                &shader,
+                config.format,
+                wgpu::PolygonMode::Line,
+                "vs_main",
+                Vertex::mesh_desc(),
+            )
+        });
+        let barycentric_pipeline = (!polygon_mode_line).then(|| {
+            create_scene_pipeline(
+                &device,
+                &render_pipeline_layout,
+                &shader,
+                config.format,
+                wgpu::PolygonMode::Fill,
+                "vs_wire",
+                WireVertex::desc(),
+            )
+        });
+
+        let skinned = options.skinned.as_ref().and_then(|path| {
+            if !SkinnedMesh::is_supported(&adapter) {
+                println!("⚠️  El adaptador no permite storage buffers en el vertex shader, sin skinning");
+                return None;
+            }
+            let model = match SkinnedModel::load_gltf(path) {
+                Ok(model) => model,
+                Err(e) => {
+                    println!("⚠️  No se pudo cargar el modelo {}: {}", path.display(), e);
+                    return None;
+                }
+            };
+            println!(
+                "✅ Modelo con esqueleto: {} articulaciones, {} clips",
+                model.skeleton.joints.len(),
+                model.clips.len()
+            );
+
+            let joint_bind_group_layout = SkinnedMesh::bind_group_layout(&device);
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Skinned Pipeline Layout"),
+                bind_group_layouts: &[
+                    &texture_bind_group_layout,
+                    &uniform_bind_group_layout,
+                    &joint_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+            let pipeline = |polygon_mode| {
+                create_scene_pipeline(
+                    &device,
+                    &layout,
+                    &shader,
+                    config.format,
+                    polygon_mode,
+                    "vs_skinned",
+                    SkinnedVertex::desc(),
+                )
+            };
+            let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Skinned Instance Buffer"),
+                contents: bytemuck::cast_slice(&[InstanceRaw::new(Matrix4::identity().into(), [0, 0], 0)]),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+            Some(SkinnedCharacter {
+                mesh: SkinnedMesh::new(&device, &joint_bind_group_layout, &model),
+                model,
+                animator: SkeletalAnimator::new(options.playback),
+                instance_buffer,
+                pipeline: pipeline(wgpu::PolygonMode::Fill),
+                wireframe_pipeline: polygon_mode_line.then(|| pipeline(wgpu::PolygonMode::Line)),
+                fade_time: 0.3,
+            })
+        });
+
+        let morph_model = match (&options.morph, options.morph_cube) {
+            (Some(path), _) => match MorphModel::load_gltf(path) {
+                Ok(model) => Some(model),
+                Err(e) => {
+                    println!("⚠️  No se pudo cargar el modelo {}: {}", path.display(), e);
+                    None
+                }
             },
-            multiview: None,
+            (None, true) => Some(create_morph_cube()),
+            (None, false) => None,
+        };
+        let morph = morph_model.and_then(|model| {
+            if !MorphMesh::is_supported(&adapter) {
+                println!("⚠️  El adaptador no permite storage buffers en el vertex shader, sin morph targets");
+                return None;
+            }
+            println!(
+                "✅ Malla deformable: {} morph targets, {} pistas de pesos",
+                model.targets.len(),
+                model.tracks.len()
+            );
+
+            let morph_bind_group_layout = MorphMesh::bind_group_layout(&device);
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Morph Pipeline Layout"),
+                bind_group_layouts: &[
+                    &texture_bind_group_layout,
+                    &uniform_bind_group_layout,
+                    &morph_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+            let pipeline = |polygon_mode| {
+                create_scene_pipeline(
+                    &device,
+                    &layout,
+                    &shader,
+                    config.format,
+                    polygon_mode,
+                    "vs_morph",
+                    MorphVertex::desc(),
+                )
+            };
+            // Encima de la rejilla para no tapar el cubo central
+            let placement = Matrix4::from_translation(Vector3::new(0.0, 3.0, 0.0));
+            let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Morph Instance Buffer"),
+                contents: bytemuck::cast_slice(&[InstanceRaw::new(placement.into(), [0, 0], 0)]),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+            let mesh = MorphMesh::new(&device, &morph_bind_group_layout, &model);
+            mesh.set_weights(&queue, &model.default_weights);
+            Some(MorphObject {
+                mesh,
+                instance_buffer,
+                pipeline: pipeline(wgpu::PolygonMode::Fill),
+                wireframe_pipeline: polygon_mode_line.then(|| pipeline(wgpu::PolygonMode::Line)),
+                player: AnimationPlayer::new(options.playback),
+                track: (!model.tracks.is_empty()).then_some(0),
+                weights: model.default_weights.clone(),
+                model,
+            })
         });
 
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Vertex Buffer"),
-            contents: bytemuck::cast_slice(VERTICES),
-            usage: wgpu::BufferUsages::VERTEX,
+        let debug_ui = DebugUi::new(&device, config.format, &window);
+
+        // --mesh se carga en segundo plano; mientras tanto se dibuja la primitiva o el cubo
+        let pending_mesh = options.mesh.as_deref().map(|path| PendingLoad {
+            id: asset_loader.load_mesh(path),
+            path: path.to_path_buf(),
+        });
+        let (meshes, objects, pending_scene_meshes) = match &scene_file {
+            Some(scene) => build_scene(
+                &device,
+                &mut assets,
+                &mut asset_loader,
+                scene,
+                &materials,
+                cube_layout.as_ref(),
+                !polygon_mode_line,
+            ),
+            None => {
+                let (meshes, objects) = build_grid(&device, &mut assets, options, cube_layout.as_ref(), !polygon_mode_line);
+                (meshes, objects, Vec::new())
+            }
+        };
+
+        let clips = match &options.animation {
+            Some(path) => match AnimationClip::load(path) {
+                Ok(clips) => {
+                    println!("✅ {} animaciones cargadas de {}", clips.len(), path.display());
+                    clips
+                }
+                Err(e) => {
+                    println!("⚠️  No se pudo cargar la animación {}: {}", path.display(), e);
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+        let animation = AnimationState {
+            clips,
+            current: 0,
+            player: AnimationPlayer::new(options.playback),
+        };
+
+        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Instance Buffer"),
+            size: (objects.len() * std::mem::size_of::<InstanceRaw>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(INDICES),
-            usage: wgpu::BufferUsages::INDEX,
+        let gpu_culler = if options.gpu_culling && GpuCuller::is_supported(&adapter) {
+            let cull_meshes: Vec<CullMesh> = meshes
+                .iter()
+                .map(|mesh| CullMesh {
+                    sphere: mesh.sphere,
+                    num_indices: mesh.asset.index_count,
+                    face_vertices: mesh.face_vertices,
+                })
+                .collect();
+            let gpu_objects: Vec<GpuObject> = objects
+                .iter()
+                .map(|object| GpuObject::new(object.model.into(), object.mesh as u32, object.layer))
+                .collect();
+            println!("✅ Culling en GPU con draws indirectos");
+            Some(GpuCuller::new(&device, &cull_meshes, &gpu_objects))
+        } else {
+            if options.gpu_culling {
+                println!("⚠️  El adaptador no soporta compute/indirect, usando culling en CPU");
+            }
+            None
+        };
+
+        let gpu_timer = if GpuTimer::is_supported(device.features()) {
+            let passes: &[&'static str] = if gpu_culler.is_some() {
+                &["culling", "render"]
+            } else {
+                &["render"]
+            };
+            Some(GpuTimer::new(&device, &queue, passes))
+        } else {
+            println!("⚠️  El adaptador no soporta TIMESTAMP_QUERY, solo se medirá el tiempo de CPU");
+            None
+        };
+
+        let csv = options.csv.as_ref().and_then(|path| {
+            let file = match std::fs::File::create(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    println!("⚠️  No se pudo crear {}: {}", path.display(), e);
+                    return None;
+                }
+            };
+            let mut writer = std::io::BufWriter::new(file);
+            let mut header = String::from("frame,frame_ms");
+            if let Some(timer) = &gpu_timer {
+                for (name, _) in timer.pass_times() {
+                    header.push_str(&format!(",{}_gpu_ms", name));
+                }
+            }
+            writeln!(writer, "{}", header).ok()?;
+            println!("✅ Guardando tiempos de frame en {}", path.display());
+            Some(writer)
         });
 
-        let num_indices = INDICES.len() as u32;
+        // Con --record o --fixed-step el reloj y la grabación esperan a que el cargador
+        // termine; si no, los primeros frames dependerían de lo rápido que cargue
+        let record_after_load = match options.record {
+            true if config.usage.contains(wgpu::TextureUsages::COPY_SRC) => true,
+            true => {
+                println!("⚠️  La superficie no permite copiar frames (COPY_SRC), --record no puede grabar");
+                false
+            }
+            false => false,
+        };
+        let wait_for_assets = options.record || options.fixed_step.is_some();
+
+        let highlight = Highlight::new(&device, config.format);
+        let debug_draw = DebugDraw::new(&device, config.format, wgpu::TextureFormat::Depth32Float);
+        let grid = InfiniteGrid::new(&device, config.format, wgpu::TextureFormat::Depth32Float);
+        let text = match std::fs::read(&options.font).map_err(Into::into).and_then(|data| GlyphAtlas::new(data, 28.0)) {
+            Ok(atlas) => Some(TextRenderer::new(&device, &queue, config.format, atlas)),
+            Err(e) => {
+                println!("⚠️  No se pudo cargar la fuente {}: {} (sin HUD)", options.font.display(), e);
+                None
+            }
+        };
+
+        let mut settings = RenderSettings::default();
+        if let Some(scene) = &scene_file {
+            settings.clear_color = scene.background;
+            settings.rotation_speed = scene.rotation_speed;
+            settings.sampler = scene.sampler;
+        }
 
         Self {
             window,
@@ -375,10 +1508,55 @@ impl State {
             config,
             size,
             render_pipeline,
-            vertex_buffer,
-            index_buffer,
-            num_indices,
+            wireframe_pipeline,
+            barycentric_pipeline,
+            meshes,
+            objects,
+            instance_buffer,
+            gpu_culler,
+            stats: RenderStats::default(),
+            last_stats_print: std::time::Instant::now(),
+            frame_timer: FrameTimer::new(),
+            gpu_timer,
+            csv,
+            diffuse_texture,
+            texture_bind_group_layout,
             diffuse_bind_group,
+            texture_name,
+            assets,
+            materials,
+            scene_path: options.scene.clone().unwrap_or_else(|| "scene.ron".into()),
+            scene_lights: scene_file.as_ref().map_or_else(Vec::new, |scene| scene.lights.clone()),
+            cursor: None,
+            selection: None,
+            highlight,
+            gizmo: Gizmo::default(),
+            history: History::default(),
+            modifiers: ModifiersState::default(),
+            id_buffer: None,
+            debug_draw,
+            grid,
+            text,
+            hud_visible: true,
+            asset_loader,
+            pending_texture,
+            texture_fallbacks,
+            pending_mesh,
+            pending_materials,
+            pending_scene_meshes,
+            settings,
+            applied_sampler: SamplerSettings::default(),
+            clock,
+            rotation_time: 0.0,
+            animation,
+            skinned,
+            morph,
+            screenshot_requested: false,
+            recorder: None,
+            record_fps: options.record_fps,
+            wait_for_assets,
+            record_after_load,
+            debug_ui,
             camera,
             uniforms,
             uniform_buffer,
@@ -392,6 +1570,47 @@ impl State {
         &self.window
     }
 
+    fn can_capture(&self) -> bool {
+        if !self.config.usage.contains(wgpu::TextureUsages::COPY_SRC) {
+            println!("⚠️  La superficie no permite copiar frames (COPY_SRC), no se puede capturar");
+            return false;
+        }
+        true
+    }
+
+    fn request_screenshot(&mut self) {
+        self.screenshot_requested = self.can_capture();
+    }
+
+    fn toggle_recording(&mut self) {
+        match self.recorder {
+            Some(_) => self.stop_recording(),
+            None if self.can_capture() => {
+                self.recorder = Some(Recorder::start(self.record_fps, &mut self.clock));
+            }
+            None => {}
+        }
+    }
+
+    // Los eventos pasan primero por la interfaz; devuelve true si los consumió
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = Some((position.x as f32, position.y as f32));
+                self.cursor_moved();
+            }
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = modifiers.state(),
+            // Aunque se suelte encima de un panel, el arrastre del gizmo termina
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } => self.mouse_released(),
+            _ => {}
+        }
+        self.debug_ui.handle_event(&self.window, event)
+    }
+
     fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
@@ -421,261 +1640,2035 @@ impl State {
     }
 
     fn update(&mut self) {
-        // Rotación del cubo
-        let time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs_f32();
-        
-        let rotation_y = Matrix4::from_angle_y(Rad(time * 0.5));
-        let rotation_x = Matrix4::from_angle_x(Rad(time * 0.3));
-        let rotation = rotation_y * rotation_x;
-        
+        let frame_time = self.frame_timer.tick();
+        self.poll_assets();
+        if self.wait_for_assets && self.asset_loader.is_idle() {
+            self.wait_for_assets = false;
+            println!("✅ Assets cargados, empieza el reloj");
+            if std::mem::take(&mut self.record_after_load) {
+                self.recorder = Some(Recorder::start(self.record_fps, &mut self.clock));
+            }
+        }
+
+        // Rotación del cubo, acumulada para que cambiar la velocidad no produzca saltos.
+        // Se detiene mientras hay un objeto elegido para poder editarlo con el gizmo.
+        let dt = if self.wait_for_assets { 0.0 } else { self.clock.tick() as f32 };
+        if self.selection.is_none() {
+            self.rotation_time += dt * self.settings.rotation_speed;
+        }
+        self.animate(dt);
+        if let Some(character) = &mut self.skinned {
+            let joints = character.animator.update(dt, &character.model);
+            character.mesh.update_joints(&self.queue, &joints);
+        }
+        if let Some(morph) = &mut self.morph {
+            if let Some(track) = morph.track.and_then(|i| morph.model.tracks.get(i)) {
+                morph.player.advance(dt);
+                morph.weights = track.sample(morph.player.clip_time(track.duration()));
+            }
+            morph.mesh.set_weights(&self.queue, &morph.weights);
+        }
+
+        let rotation = self.scene_rotation();
+
         let view = Matrix4::look_at_rh(self.camera.eye, self.camera.target, self.camera.up);
-        let proj = perspective(
-            Deg(self.camera.fovy), 
-            self.camera.aspect, 
-            self.camera.znear, 
-            self.camera.zfar
-        );
+        let proj = self.camera.projection();
         
         self.uniforms.view_proj = (proj * view * rotation).into();
+        self.uniforms.debug_view = self.settings.view as u32;
+        if let Some(selection) = self.selection {
+            let view_proj: Matrix4<f32> = self.uniforms.view_proj.into();
+            self.highlight.update(&self.queue, view_proj * self.objects[selection.object].model);
+        }
+        if self.settings.helpers.grid {
+            self.update_grid(rotation);
+        }
+        self.draw_helpers(rotation);
+        self.debug_draw.prepare(&self.device, &self.queue, &self.uniforms.view_proj.into());
+        if self.hud_visible {
+            self.draw_hud();
+        }
+        if let Some(text) = &mut self.text {
+            text.prepare(&self.device, &self.queue, &self.uniforms.view_proj.into(), (self.config.width, self.config.height));
+        }
+        self.uniforms.znear = self.camera.znear;
+        self.uniforms.zfar = self.camera.zfar;
+        self.uniforms.orthographic = self.camera.orthographic as u32;
         self.queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniforms]));
-    }
-
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
 
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
+        if self.applied_sampler != self.settings.sampler {
+            self.applied_sampler = self.settings.sampler;
+            self.diffuse_texture.sampler = self.assets.sampler(&self.device, &color_sampler(&self.settings.sampler));
+            self.diffuse_bind_group = create_texture_bind_group(
+                &self.device,
+                &self.texture_bind_group_layout,
+                &self.diffuse_texture,
+            );
+        }
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
-                }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
+        if let Some(csv) = &mut self.csv {
+            let mut row = format!(
+                "{},{:.4}",
+                self.frame_timer.frame_count(),
+                frame_time.as_secs_f32() * 1000.0
+            );
+            if let Some(timer) = &self.gpu_timer {
+                for (_, ms) in timer.pass_times() {
+                    row.push_str(&format!(",{:.4}", ms));
+                }
+            }
+            if writeln!(csv, "{}", row).is_err() {
+                println!("⚠️  Error al escribir el CSV, se deja de guardar");
+                self.csv = None;
+            }
+        }
 
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_bind_group(0, &self.diffuse_bind_group, &[]);
-            render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+        if self.last_stats_print.elapsed().as_secs_f32() >= 1.0 {
+            let objects = if let Some(culler) = &self.gpu_culler {
+                match culler.visible_count() {
+                    Some(_) => format!(
+                        "culling en GPU | dibujados: {} | descartados: {}",
+                        self.stats.drawn, self.stats.culled
+                    ),
+                    None => format!("culling en GPU: {} objetos enviados, contadores no disponibles", self.objects.len()),
+                }
+            } else {
+                format!("dibujados: {} | descartados: {}", self.stats.drawn, self.stats.culled)
+            };
+            let gpu = self
+                .gpu_timer
+                .as_ref()
+                .map(|timer| format!(" | GPU {}", timer.summary()))
+                .unwrap_or_default();
+            log::info!("📊 {}{} | {} | assets: {}", self.frame_timer.summary(), gpu, objects, self.assets.memory());
+            let (done, total) = self.asset_loader.progress();
+            let loading = if self.asset_loader.is_idle() {
+                String::new()
+            } else {
+                format!(" - cargando {}/{}", done, total)
+            };
+            self.window.set_title(&format!(
+                "Cubo con Textura Atlas - {:.1} FPS ({:.2} ms){}",
+                self.frame_timer.fps(),
+                self.frame_timer.average_ms(),
+                loading
+            ));
+            self.last_stats_print = std::time::Instant::now();
         }
+    }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+    // Sube a la GPU lo que el cargador terminó de decodificar y reemplaza el placeholder
+    fn poll_assets(&mut self) {
+        let results = self.asset_loader.poll();
+        let replaced = !results.is_empty();
+        for result in results {
+            let (done, total) = self.asset_loader.progress();
+            let asset = match result.asset {
+                Ok(asset) => asset,
+                Err(e) => {
+                    println!("⚠️  No se pudo cargar {}: {} [{}/{}]", result.label, e, done, total);
+                    if self.pending_texture.as_ref().is_some_and(|pending| pending.id == result.id) {
+                        self.load_next_texture();
+                    }
+                    // Los materiales y mallas de la escena que fallan se quedan con la
+                    // textura del visor y el cubo
+                    self.pending_materials.retain(|pending| pending.id != result.id);
+                    self.pending_scene_meshes.retain(|pending| pending.id != result.id);
+                    continue;
+                }
+            };
+            log::info!(
+                "📦 {} decodificado en {:.0} ms [{}/{}]",
+                result.label,
+                result.elapsed.as_secs_f64() * 1000.0,
+                done,
+                total
+            );
 
-        Ok(())
+            if let Some(pending) = self.pending_texture.take_if(|pending| pending.id == result.id) {
+                // La clave es la ruta pedida, no la etiqueta de la carga
+                match self.assets.insert_texture(&self.device, &self.queue, &pending.path, TextureSettings::default(), &asset) {
+                    Ok(handle) => {
+                        let texture = texture_from_asset(handle, self.diffuse_texture.sampler.clone());
+                        // Al reemplazarla se suelta el placeholder y se libera su memoria
+                        self.diffuse_texture = texture;
+                        self.diffuse_bind_group =
+                            create_texture_bind_group(&self.device, &self.texture_bind_group_layout, &self.diffuse_texture);
+                        println!("✅ Textura {} lista | assets: {}", result.label, self.assets.memory());
+                        self.texture_name = result.label.clone();
+                    }
+                    Err(e) => {
+                        println!("⚠️  No se pudo subir la textura {}: {}", result.label, e);
+                        self.load_next_texture();
+                    }
+                }
+            } else if let Some(pending) = self.pending_mesh.take_if(|pending| pending.id == result.id) {
+                let LoadedAsset::Mesh(data) = asset else { continue };
+                let barycentric = self.barycentric_pipeline.is_some();
+                match Mesh::from_data(&self.device, &mut self.assets, &pending.path, &data, barycentric) {
+                    Ok(mut mesh) => {
+                        mesh.source = MeshSource::File(pending.path);
+                        mesh.material = self.meshes[0].material;
+                        println!(
+                            "✅ Malla {} lista: {} vértices, {} triángulos",
+                            result.label,
+                            data.vertices.len(),
+                            data.triangle_count()
+                        );
+                        self.meshes[0] = mesh;
+                        // Los triángulos de la selección ya no corresponden
+                        self.selection = None;
+                        self.gizmo.end();
+                        self.rebuild_gpu_culler();
+                    }
+                    Err(e) => println!("⚠️  {}", e),
+                }
+            } else if let Some(index) = self.pending_materials.iter().position(|pending| pending.id == result.id) {
+                let pending = self.pending_materials.remove(index);
+                self.apply_material_texture(&pending.path, &asset);
+            } else if let Some(index) = self.pending_scene_meshes.iter().position(|pending| pending.id == result.id) {
+                let pending = self.pending_scene_meshes.remove(index);
+                let LoadedAsset::Mesh(data) = asset else { continue };
+                self.apply_scene_mesh(&pending.path, &data);
+            }
+
+            if self.asset_loader.is_idle() {
+                log::info!("📦 Todos los assets cargados");
+            }
+        }
+        // Los placeholders reemplazados ya soltaron su handle; se quitan del índice
+        if replaced {
+            self.assets.purge();
+        }
     }
-}
 
-struct Texture {
-    view: wgpu::TextureView,
-    sampler: wgpu::Sampler,
-}
+    // Crea los materiales que usan la textura recién cargada. Se sube con los ajustes de
+    // cada material y assets.material la encuentra en el registro sin volver al disco.
+    fn apply_material_texture(&mut self, path: &std::path::Path, asset: &LoadedAsset) {
+        for material in self.materials.iter_mut().filter(|material| material.desc.texture == path) {
+            let desc = &material.desc;
+            let texture_settings = TextureSettings { srgb: desc.srgb, mipmaps: true };
+            let result = self
+                .assets
+                .insert_texture(&self.device, &self.queue, path, texture_settings, asset)
+                .and_then(|_texture| {
+                    self.assets.material(
+                        &self.device,
+                        &self.queue,
+                        &self.texture_bind_group_layout,
+                        path,
+                        texture_settings,
+                        &color_sampler(&material.sampler),
+                    )
+                });
+            match result {
+                Ok(asset) => {
+                    println!("✅ Material '{}' con {}", desc.name, path.display());
+                    material.asset = Some(asset);
+                }
+                Err(e) => println!("⚠️  No se pudo cargar el material '{}' ({}): {}", desc.name, path.display(), e),
+            }
+        }
+    }
 
-// Función para generar textura atlas con diferentes colores
-fn create_texture_atlas() -> Vec<u8> {
-    let width = 256;
-    let height = 128;
-    let section_width = width / 4; // 4 secciones horizontales
-    let section_height = height / 2; // 2 secciones verticales
-    
-    let img = ImageBuffer::from_fn(width, height, |x, y| {
-        let section_x = x / section_width;
-        let section_y = y / section_height;
-        
-        // Determinar el color basado en la sección
-        match (section_x, section_y) {
-            (0, 0) => Rgba([255u8, 100u8, 100u8, 255u8]), // Rojo - Front face
-            (1, 0) => Rgba([100u8, 255u8, 100u8, 255u8]), // Verde - Back face
-            (2, 0) => Rgba([100u8, 100u8, 255u8, 255u8]), // Azul - Top face
-            (3, 0) => Rgba([255u8, 255u8, 100u8, 255u8]), // Amarillo - Bottom face
-            (0, 1) => Rgba([255u8, 100u8, 255u8, 255u8]), // Magenta - Right face
-            (1, 1) => Rgba([100u8, 255u8, 255u8, 255u8]), // Cian - Left face
-            _ => Rgba([255u8, 255u8, 255u8, 255u8]),       // Blanco por defecto
+    // Reemplaza el cubo provisional de cada malla de la escena que viene de este archivo
+    fn apply_scene_mesh(&mut self, path: &std::path::Path, data: &MeshData) {
+        let barycentric = self.barycentric_pipeline.is_some();
+        let source = MeshSource::File(path.to_path_buf());
+        for index in 0..self.meshes.len() {
+            if self.meshes[index].source != source {
+                continue;
+            }
+            match Mesh::from_data(&self.device, &mut self.assets, path, data, barycentric) {
+                Ok(mut mesh) => {
+                    mesh.source = source.clone();
+                    mesh.material = self.meshes[index].material;
+                    self.meshes[index] = mesh;
+                }
+                Err(e) => {
+                    println!("⚠️  {}: {}, se mantiene el cubo", path.display(), e);
+                    return;
+                }
+            }
         }
-    });
-    
-    let mut bytes = Vec::new();
-    use std::io::Cursor;
-    img.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
-        .expect("Error al escribir imagen");
-    bytes
-}
+        println!(
+            "✅ Malla {} lista: {} vértices, {} triángulos",
+            path.display(),
+            data.vertices.len(),
+            data.triangle_count()
+        );
+        // Los triángulos de la selección ya no corresponden
+        self.selection = None;
+        self.gizmo.end();
+        self.rebuild_gpu_culler();
+    }
 
-// Función para crear textura desde bytes
-fn load_texture_from_bytes(
-    device: &wgpu::Device,
-    queue: &wgpu::Queue,
-    bytes: &[u8],
-    label: &str,
-) -> Result<Texture, Box<dyn std::error::Error>> {
-    let img = image::load_from_memory(bytes)?;
-    let rgba = img.to_rgba8();
-    let dimensions = img.dimensions();
-
-    let texture_size = wgpu::Extent3d {
-        width: dimensions.0,
-        height: dimensions.1,
-        depth_or_array_layers: 1,
-    };
+    // Pasa a la siguiente textura candidata; sin más queda el tablero
+    fn load_next_texture(&mut self) {
+        self.pending_texture = None;
+        if self.texture_fallbacks.is_empty() {
+            println!("⚠️  No quedan texturas para probar, se mantiene el tablero");
+            return;
+        }
+        let next = self.texture_fallbacks.remove(0);
+        println!("⚠️  Se usa {} en su lugar", next);
+        self.pending_texture = Some(next.load(&mut self.asset_loader));
+    }
 
-    let texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some(label),
-        size: texture_size,
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8UnormSrgb,
-        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-        view_formats: &[],
-    });
+    // Resuelve el clic bajo el cursor, lo informa y lo deja resaltado
+    fn pick(&mut self) {
+        let Some(cursor) = self.cursor else { return };
+        let view_proj: Matrix4<f32> = self.uniforms.view_proj.into();
+        let size = (self.config.width, self.config.height);
+        let Some(ray) = Ray::from_cursor(cursor, size, &view_proj) else { return };
 
-    queue.write_texture(
-        wgpu::ImageCopyTexture {
-            aspect: wgpu::TextureAspect::All,
-            texture: &texture,
-            mip_level: 0,
-            origin: wgpu::Origin3d::ZERO,
-        },
-        &rgba,
-        wgpu::ImageDataLayout {
-            offset: 0,
-            bytes_per_row: Some(4 * dimensions.0),
-            rows_per_image: Some(dimensions.1),
-        },
-        texture_size,
-    );
+        let hit = match self.settings.pick_mode {
+            PickMode::Ray => self.ray_cast(&ray).map(|(object, hit)| (object, Some(hit), hit.uv)),
+            PickMode::IdBuffer => match self.pick_id_buffer(cursor) {
+                Ok(hit) => hit.map(|id| {
+                    let object = id.object as usize;
+                    (object, self.ray_cast_object(object, &ray), id.uv)
+                }),
+                Err(e) => {
+                    println!("⚠️  No se pudo leer el ID buffer: {}", e);
+                    None
+                }
+            },
+        };
 
-    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        address_mode_u: wgpu::AddressMode::ClampToEdge,
-        address_mode_v: wgpu::AddressMode::ClampToEdge,
-        address_mode_w: wgpu::AddressMode::ClampToEdge,
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Nearest,
-        mipmap_filter: wgpu::FilterMode::Nearest,
-        ..Default::default()
-    });
+        let Some((object, mesh_hit, uv)) = hit else {
+            println!("🎯 Nada bajo el cursor");
+            self.selection = None;
+            return;
+        };
+        let mesh = &self.meshes[self.objects[object].mesh];
+        let source = match &mesh.source {
+            MeshSource::Primitive(name) => name.clone(),
+            MeshSource::File(path) => path.display().to_string(),
+        };
+        let triangle = match mesh_hit {
+            Some(hit) => {
+                let face = hit.face(&mesh.pick.indices);
+                let face_name = match (source.as_str(), CUBE_FACE_NAMES.get(face as usize)) {
+                    ("cube" | "atlas-cube", Some(name)) => format!(" ({})", name),
+                    _ => String::new(),
+                };
+                let position = self.objects[object].model * hit.position.to_homogeneous();
+                format!(
+                    "triángulo {}, cara {}{} | punto ({:.2}, {:.2}, {:.2})",
+                    hit.triangle, face, face_name, position.x, position.y, position.z
+                )
+            }
+            None => "triángulo desconocido".to_string(),
+        };
+        println!(
+            "🎯 Objeto {} ({}) | {} | UV ({:.3}, {:.3}) [{}]",
+            object,
+            source,
+            triangle,
+            uv[0],
+            uv[1],
+            self.settings.pick_mode.name()
+        );
 
-    Ok(Texture {
-        view,
-        sampler,
-    })
-}
+        if let Some(hit) = mesh_hit {
+            let vertices: [MeshVertex; 3] = std::array::from_fn(|k| {
+                let index = mesh.pick.indices[hit.triangle * 3 + k] as usize;
+                MeshVertex {
+                    position: mesh.pick.positions[index],
+                    tex_coords: mesh.pick.tex_coords[index],
+                    normal: [0.0; 3],
+                }
+            });
+            self.queue.write_buffer(&self.highlight.triangle_buffer, 0, bytemuck::cast_slice(&vertices));
+        }
+        self.selection = Some(Selection { object, triangle: mesh_hit.map(|hit| hit.triangle) });
+    }
 
-// Función principal para crear o cargar textura
-async fn create_or_load_texture(
-    device: &wgpu::Device,
-    queue: &wgpu::Queue,
-) -> Texture {
-    // Intentar cargar textura desde archivo
-    let texture_bytes = match std::fs::read("assets/texture.jpg") {
-        Ok(bytes) => {
-            println!("✅ Textura cargada desde assets/texture.jpg");
-            bytes
-        },
-        Err(_) => {
-            println!("⚠️  No se encontró assets/texture.jpg, generando textura por defecto...");
-            // Crear directorio assets si no existe
-            std::fs::create_dir_all("assets").unwrap_or_else(|_| {});
-            
-            // Generar textura y guardarla
-            let generated_bytes = create_texture_atlas();
-            std::fs::write("assets/texture.png", &generated_bytes)
-                .unwrap_or_else(|_| println!("No se pudo guardar la textura generada"));
-            
-            println!("✅ Textura atlas generada y guardada en assets/texture.png");
-            generated_bytes
+    // FPS, cámara, textura y teclas abajo a la izquierda; con un objeto elegido,
+    // etiquetas en las esquinas de su caja
+    fn draw_hud(&mut self) {
+        let Some(text) = &mut self.text else { return };
+        let camera = &self.camera;
+        let hud = format!(
+            "{:.0} FPS | {:.2} ms\n\
+             Cámara ({:.2}, {:.2}, {:.2}) -> ({:.2}, {:.2}, {:.2}) | {}\n\
+             Textura: {}\n\
+             F1 interfaz | F5 guardar | F12 captura | G grilla | O proyección | W/E/R gizmo | H ocultar HUD",
+            self.frame_timer.fps(),
+            self.frame_timer.average_ms(),
+            camera.eye.x,
+            camera.eye.y,
+            camera.eye.z,
+            camera.target.x,
+            camera.target.y,
+            camera.target.z,
+            if camera.orthographic { "ortográfica" } else { "perspectiva" },
+            self.texture_name,
+        );
+        const SIZE: f32 = 15.0;
+        let (_, height) = text.atlas().measure(&hud, SIZE);
+        text.hud(hud, [8.0, self.config.height as f32 - height - 8.0], SIZE, [1.0, 1.0, 1.0, 0.9]);
+
+        if let Some(selection) = self.selection {
+            let object = &self.objects[selection.object];
+            let mesh = &self.meshes[object.mesh];
+            for (index, corner) in mesh.aabb.corners().iter().enumerate() {
+                let point = object.model * corner.to_homogeneous();
+                let point = Point3::from_homogeneous(point);
+                text.label(index.to_string(), point, 13.0, [0.6, 1.0, 0.6, 1.0]);
+            }
+            let top = Point3::from_homogeneous(object.model * mesh.aabb.center().to_homogeneous())
+                + Vector3::unit_y() * mesh.aabb.transformed(&object.model).extents().y;
+            let name = match &mesh.source {
+                MeshSource::Primitive(name) => name.clone(),
+                MeshSource::File(path) => path.display().to_string(),
+            };
+            text.label(format!("{} #{}", name, selection.object), top, 16.0, [1.0, 0.9, 0.3, 1.0]);
         }
-    };
+    }
 
-    load_texture_from_bytes(device, queue, &texture_bytes, "texture.jpg")
-        .expect("Error al cargar textura")
-}
+    // La grilla se desvanece alrededor del objetivo de la cámara, más lejos cuanto
+    // más lejos esté la cámara
+    fn update_grid(&mut self, rotation: Matrix4<f32>) {
+        let inverse = rotation.invert().unwrap_or(Matrix4::identity());
+        let target = Point3::from_homogeneous(inverse * self.camera.target.to_homogeneous());
+        let distance = (self.camera.eye - self.camera.target).magnitude();
+        let settings = GridSettings {
+            fade_center: target,
+            fade_radius: (distance * 10.0).clamp(5.0, self.camera.zfar * 0.8),
+            ..self.settings.helpers.grid_settings
+        };
+        self.grid.update(&self.queue, &self.uniforms.view_proj.into(), &settings);
+    }
+
+    // Acumula las ayudas activas y el gizmo, en el espacio de los objetos
+    fn draw_helpers(&mut self, rotation: Matrix4<f32>) {
+        let helpers = &self.settings.helpers;
+        let debug = &mut self.debug_draw;
+        for object in &self.objects {
+            let mesh = &self.meshes[object.mesh];
+            if helpers.boxes {
+                debug.aabb(&mesh.aabb, &object.model, [1.0, 0.6, 0.1, 1.0]);
+            }
+            if helpers.spheres {
+                let sphere = mesh.sphere.transformed(&object.model);
+                debug.sphere(sphere.center, sphere.radius, [0.3, 0.8, 1.0, 0.6]);
+            }
+        }
+        // El eje Y acompaña a los ejes X y Z que dibuja la grilla
+        if helpers.grid && helpers.grid_settings.axes {
+            let length = (self.camera.eye - self.camera.target).magnitude() * 10.0;
+            debug.line(Point3::new(0.0, -length, 0.0), Point3::new(0.0, length, 0.0), [0.3, 0.9, 0.3, 1.0]);
+        }
+        // El frustum congelado no incluye el giro de la escena, así queda fijo en el mundo
+        if let Some(view_proj) = helpers.frozen_frustum {
+            debug.frustum(&(view_proj * rotation), [1.0, 1.0, 0.3, 1.0]);
+        }
+
+        debug.set_depth_test(false);
+        if helpers.axes {
+            debug.axes(&Matrix4::identity(), 1.0);
+        }
+        if helpers.light {
+            let direction = Vector3::from(self.uniforms.light_direction);
+            if direction.magnitude2() > 0.0 {
+                let direction = direction.normalize();
+                let from = Point3::new(0.0, 0.0, 0.0) - direction * 2.5;
+                let color = self.uniforms.light_color;
+                debug.arrow(from, from + direction, [color[0], color[1], color[2], 1.0]);
+            }
+        }
+        if let Some(selection) = self.selection {
+            let transform = self.objects[selection.object].base;
+            let size = self.gizmo_size(&transform);
+            for line in self.gizmo.lines(&transform, size) {
+                self.debug_draw.line(line.from, line.to, line.color);
+            }
+        }
+    }
+
+    // Giro global de la escena; los objetos y el gizmo viven antes de este giro
+    fn scene_rotation(&self) -> Matrix4<f32> {
+        let time = self.rotation_time;
+        Matrix4::from_angle_y(Rad(time * 0.5)) * Matrix4::from_angle_x(Rad(time * 0.3))
+    }
+
+    // Tamaño del gizmo según la distancia a la cámara, medida en el espacio de los objetos
+    fn gizmo_size(&self, transform: &Transform) -> f32 {
+        let inverse = self.scene_rotation().invert().unwrap_or(Matrix4::identity());
+        let eye = Point3::from_homogeneous(inverse * self.camera.eye.to_homogeneous());
+        Gizmo::screen_size(Point3::from_vec(transform.translation), eye, self.camera.fovy)
+    }
+
+    fn cursor_ray(&self) -> Option<Ray> {
+        let view_proj: Matrix4<f32> = self.uniforms.view_proj.into();
+        Ray::from_cursor(self.cursor?, (self.config.width, self.config.height), &view_proj)
+    }
+
+    // Clic izquierdo: arrastra una manija del gizmo o elige otro objeto
+    fn mouse_pressed(&mut self) {
+        if let (Some(selection), Some(ray)) = (self.selection, self.cursor_ray()) {
+            let transform = self.objects[selection.object].base;
+            let size = self.gizmo_size(&transform);
+            if self.gizmo.begin(&ray, &transform, size) {
+                return;
+            }
+        }
+        self.pick();
+    }
+
+    fn cursor_moved(&mut self) {
+        let (Some(selection), Some(ray)) = (self.selection, self.cursor_ray()) else {
+            return;
+        };
+        if self.gizmo.is_dragging() {
+            // Ctrl activa el snapping mientras se arrastra
+            let snap = self.gizmo.snap || self.modifiers.control_key();
+            if let Some(transform) = self.gizmo.drag(&ray, snap) {
+                self.set_object_transform(selection.object, transform);
+            }
+        } else {
+            let transform = self.objects[selection.object].base;
+            self.gizmo.hovered = self.gizmo.pick_handle(&ray, &transform, self.gizmo_size(&transform));
+        }
+    }
+
+    // Al soltar el botón la edición entra en el historial
+    fn mouse_released(&mut self) {
+        let (Some(selection), Some(before)) = (self.selection, self.gizmo.end()) else {
+            return;
+        };
+        let after = self.objects[selection.object].base;
+        if before != after {
+            self.history.push(TransformEdit { object: selection.object, before, after });
+        }
+    }
+
+    fn set_object_transform(&mut self, object: usize, transform: Transform) {
+        let object = &mut self.objects[object];
+        object.base = transform;
+        object.model = transform.to_matrix();
+        self.upload_objects();
+    }
+
+    fn undo(&mut self) {
+        if self.gizmo.is_dragging() {
+            return;
+        }
+        match self.history.undo() {
+            Some(edit) => {
+                self.set_object_transform(edit.object, edit.before);
+                println!("↩️  Deshecha la edición del objeto {}", edit.object);
+            }
+            None => println!("⚠️  Nada que deshacer"),
+        }
+    }
+
+    fn redo(&mut self) {
+        if self.gizmo.is_dragging() {
+            return;
+        }
+        match self.history.redo() {
+            Some(edit) => {
+                self.set_object_transform(edit.object, edit.after);
+                println!("↪️  Rehecha la edición del objeto {}", edit.object);
+            }
+            None => println!("⚠️  Nada que rehacer"),
+        }
+    }
+
+    // Objeto más cercano que corta el rayo. Los objetos con skin o morph no participan.
+    fn ray_cast(&self, ray: &Ray) -> Option<(usize, MeshHit)> {
+        (0..self.objects.len())
+            .filter_map(|object| self.ray_cast_object(object, ray).map(|hit| (object, hit)))
+            .min_by(|a, b| a.1.t.total_cmp(&b.1.t))
+    }
+
+    fn ray_cast_object(&self, object: usize, ray: &Ray) -> Option<MeshHit> {
+        let object = &self.objects[object];
+        let mesh = &self.meshes[object.mesh];
+        let local = ray.transformed(&object.model.invert()?);
+        local.intersect_aabb(&mesh.aabb, f32::INFINITY)?;
+        mesh.pick.intersect(&local)
+    }
+
+    // Dibuja el píxel del cursor en el ID buffer con todos los objetos, sin culling.
+    // La capa de cada instancia lleva el índice del objeto.
+    fn pick_id_buffer(&mut self, cursor: (f32, f32)) -> Result<Option<IdHit>, Box<dyn std::error::Error>> {
+        let mut instances = Vec::with_capacity(self.objects.len());
+        let mut ranges = Vec::with_capacity(self.meshes.len());
+        for mesh in 0..self.meshes.len() {
+            let start = instances.len() as u32;
+            for (index, object) in self.objects.iter().enumerate().filter(|(_, object)| object.mesh == mesh) {
+                instances.push(InstanceRaw::new(object.model.into(), [index as u32, 0], 0));
+            }
+            ranges.push(start..instances.len() as u32);
+        }
+        let instance_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("ID Buffer Instances"),
+            contents: bytemuck::cast_slice(&instances),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let draws: Vec<IdDraw> = self
+            .meshes
+            .iter()
+            .zip(ranges)
+            .map(|(mesh, instances)| IdDraw {
+                vertex_buffer: &mesh.asset.vertex_buffer,
+                index_buffer: &mesh.asset.index_buffer,
+                index_format: wgpu::IndexFormat::Uint32,
+                num_indices: mesh.asset.index_count,
+                instances,
+            })
+            .collect();
+
+        let id_buffer = self
+            .id_buffer
+            .get_or_insert_with(|| IdBuffer::new(&self.device, Vertex::mesh_desc(), InstanceRaw::desc()));
+        id_buffer.pick(
+            &self.device,
+            &self.queue,
+            &self.uniforms.view_proj.into(),
+            &instance_buffer,
+            &draws,
+            (cursor.0 as u32, cursor.1 as u32),
+            (self.config.width, self.config.height),
+        )
+    }
+
+    fn material_bind_group(&self, mesh: &Mesh) -> &wgpu::BindGroup {
+        match mesh.material {
+            Some(index) => match &self.materials[index].asset {
+                Some(asset) => &asset.bind_group,
+                None => &self.diffuse_bind_group,
+            },
+            None => &self.diffuse_bind_group,
+        }
+    }
+
+    // Guarda la escena actual: objetos sin animar, materiales, luces, fondo y cámara
+    fn save_scene(&self) {
+        let objects = self
+            .objects
+            .iter()
+            .map(|object| {
+                let mesh = &self.meshes[object.mesh];
+                scene::SceneObject {
+                    mesh: mesh.source.clone(),
+                    transform: object.base.into(),
+                    material: mesh.material.map(|index| self.materials[index].desc.name.clone()),
+                }
+            })
+            .collect();
+        let mut scene = SceneFile {
+            camera: SceneCamera {
+                eye: self.camera.eye.into(),
+                target: self.camera.target.into(),
+                fovy: self.camera.fovy,
+                znear: self.camera.znear,
+                zfar: self.camera.zfar,
+                orthographic: self.camera.orthographic,
+            },
+            background: self.settings.clear_color,
+            rotation_speed: self.settings.rotation_speed,
+            sampler: self.settings.sampler,
+            lights: self.scene_lights.clone(),
+            materials: self.materials.iter().map(|material| material.desc.clone()).collect(),
+            objects,
+        };
+        // Solo se editan la primera luz de cada tipo; las demás se guardan como se leyeron
+        scene.set_lights(
+            self.uniforms.ambient,
+            (self.uniforms.light_direction, self.uniforms.light_color, self.uniforms.light_intensity),
+        );
+        match scene.save(&self.scene_path) {
+            Ok(()) => println!("✅ Escena guardada en {}", self.scene_path.display()),
+            Err(e) => println!("⚠️  No se pudo guardar la escena en {}: {}", self.scene_path.display(), e),
+        }
+    }
+
+    // Las esferas de culling y la cantidad de índices por malla viven en el culler
+    fn rebuild_gpu_culler(&mut self) {
+        if self.gpu_culler.is_none() {
+            return;
+        }
+        let cull_meshes: Vec<CullMesh> = self
+            .meshes
+            .iter()
+            .map(|mesh| CullMesh {
+                sphere: mesh.sphere,
+                num_indices: mesh.asset.index_count,
+                face_vertices: mesh.face_vertices,
+            })
+            .collect();
+        let gpu_objects: Vec<GpuObject> = self
+            .objects
+            .iter()
+            .map(|object| GpuObject::new(object.model.into(), object.mesh as u32, object.layer))
+            .collect();
+        self.gpu_culler = Some(GpuCuller::new(&self.device, &cull_meshes, &gpu_objects));
+    }
+
+    // Aplica el clip actual a los objetos y a la cámara
+    fn animate(&mut self, dt: f32) {
+        let Some(clip) = self.animation.clips.get(self.animation.current) else {
+            return;
+        };
+        self.animation.player.advance(dt);
+        let time = self.animation.player.clip_time(clip.duration());
+
+        let mut transforms: Vec<Transform> = self.objects.iter().map(|o| o.base).collect();
+        let mut camera = CameraPose {
+            eye: self.camera.eye,
+            target: self.camera.target,
+        };
+        clip.apply(time, &mut transforms, Some(&mut camera));
+        self.camera.eye = camera.eye;
+        self.camera.target = camera.target;
+
+        for (object, transform) in self.objects.iter_mut().zip(&transforms) {
+            object.model = transform.to_matrix();
+        }
+        self.upload_objects();
+    }
+
+    // Las matrices de los objetos viven también en el culler de GPU
+    fn upload_objects(&self) {
+        if let Some(culler) = &self.gpu_culler {
+            let gpu_objects: Vec<GpuObject> = self
+                .objects
+                .iter()
+                .map(|object| GpuObject::new(object.model.into(), object.mesh as u32, object.layer))
+                .collect();
+            culler.update_objects(&self.queue, &gpu_objects);
+        }
+    }
+
+    // Descarta los objetos fuera del frustum y agrupa los visibles por malla.
+    // Devuelve, por cada malla, el rango de instancias a dibujar.
+    fn cull_objects(&mut self) -> Vec<std::ops::Range<u32>> {
+        let frustum = Frustum::from_view_proj(&self.uniforms.view_proj.into());
+
+        let mut visible: Vec<Vec<InstanceRaw>> = (0..self.meshes.len()).map(|_| Vec::new()).collect();
+        self.stats = RenderStats::default();
+        for object in &self.objects {
+            let mesh = &self.meshes[object.mesh];
+            let inside = frustum.intersects_sphere(&mesh.sphere.transformed(&object.model))
+                && frustum.intersects_aabb(&mesh.aabb.transformed(&object.model));
+            if inside {
+                visible[object.mesh].push(InstanceRaw::new(object.model.into(), object.layer, mesh.face_vertices));
+                self.stats.drawn += 1;
+            } else {
+                self.stats.culled += 1;
+            }
+        }
+
+        let instances: Vec<InstanceRaw> = visible.iter().flatten().copied().collect();
+        self.queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+
+        let mut start = 0;
+        visible
+            .iter()
+            .map(|v| {
+                let range = start..start + v.len() as u32;
+                start = range.end;
+                range
+            })
+            .collect()
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let instance_ranges = if self.gpu_culler.is_some() {
+            Vec::new()
+        } else {
+            self.cull_objects()
+        };
+
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        if let Some(culler) = &mut self.gpu_culler {
+            let frustum = Frustum::from_view_proj(&self.uniforms.view_proj.into());
+            let timestamp_writes = self.gpu_timer.as_ref().map(|t| t.compute_pass_writes("culling"));
+            culler.cull(&self.queue, &mut encoder, &frustum, timestamp_writes);
+        }
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: self.settings.clear_color[0] as f64,
+                            g: self.settings.clear_color[1] as f64,
+                            b: self.settings.clear_color[2] as f64,
+                            a: 1.0,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: self.gpu_timer.as_ref().map(|t| t.render_pass_writes("render")),
+                occlusion_query_set: None,
+            });
+
+            // Wireframe sin PolygonMode::Line: se dibuja la copia desindexada de cada malla
+            let barycentric = self.settings.view == DebugView::Wireframe && self.wireframe_pipeline.is_none();
+            let pipeline = match self.settings.view {
+                DebugView::Wireframe => self
+                    .wireframe_pipeline
+                    .as_ref()
+                    .or(self.barycentric_pipeline.as_ref())
+                    .unwrap(),
+                _ => &self.render_pipeline,
+            };
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+            if let Some(culler) = &self.gpu_culler {
+                for (i, mesh) in self.meshes.iter().enumerate() {
+                    let Some(instances) = culler.instances(i) else {
+                        continue;
+                    };
+                    render_pass.set_bind_group(0, self.material_bind_group(mesh), &[]);
+                    render_pass.set_vertex_buffer(1, instances);
+                    if let (true, Some(wire)) = (barycentric, &mesh.wire_vertex_buffer) {
+                        // Los argumentos indexados se leen como DrawIndirectArgs:
+                        // vertex_count = index_count y el resto queda en 0
+                        render_pass.set_vertex_buffer(0, wire.slice(..));
+                        render_pass.draw_indirect(culler.indirect_buffer(), culler.indirect_offset(i));
+                    } else {
+                        render_pass.set_vertex_buffer(0, mesh.asset.vertex_buffer.slice(..));
+                        render_pass.set_index_buffer(mesh.asset.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                        render_pass.draw_indexed_indirect(culler.indirect_buffer(), culler.indirect_offset(i));
+                    }
+                }
+            } else {
+                render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+                for (mesh, instances) in self.meshes.iter().zip(instance_ranges) {
+                    if instances.is_empty() {
+                        continue;
+                    }
+                    render_pass.set_bind_group(0, self.material_bind_group(mesh), &[]);
+                    if let (true, Some(wire)) = (barycentric, &mesh.wire_vertex_buffer) {
+                        render_pass.set_vertex_buffer(0, wire.slice(..));
+                        render_pass.draw(0..mesh.asset.index_count, instances);
+                    } else {
+                        render_pass.set_vertex_buffer(0, mesh.asset.vertex_buffer.slice(..));
+                        render_pass.set_index_buffer(mesh.asset.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                        render_pass.draw_indexed(0..mesh.asset.index_count, 0, instances);
+                    }
+                }
+            }
+
+            if let Some(character) = &self.skinned {
+                // Sin PolygonMode::Line la malla con skin no tiene wireframe
+                let pipeline = match self.settings.view {
+                    DebugView::Wireframe => character.wireframe_pipeline.as_ref(),
+                    _ => Some(&character.pipeline),
+                };
+                if let Some(pipeline) = pipeline {
+                    render_pass.set_pipeline(pipeline);
+                    render_pass.set_bind_group(2, &character.mesh.bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, character.mesh.vertex_buffer.slice(..));
+                    render_pass.set_vertex_buffer(1, character.instance_buffer.slice(..));
+                    render_pass.set_index_buffer(character.mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..character.mesh.num_indices, 0, 0..1);
+                }
+            }
+
+            if let Some(morph) = &self.morph {
+                let pipeline = match self.settings.view {
+                    DebugView::Wireframe => morph.wireframe_pipeline.as_ref(),
+                    _ => Some(&morph.pipeline),
+                };
+                if let Some(pipeline) = pipeline {
+                    render_pass.set_pipeline(pipeline);
+                    render_pass.set_bind_group(2, &morph.mesh.bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, morph.mesh.vertex_buffer.slice(..));
+                    render_pass.set_vertex_buffer(1, morph.instance_buffer.slice(..));
+                    render_pass.set_index_buffer(morph.mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..morph.mesh.num_indices, 0, 0..1);
+                }
+            }
+
+            // Después de lo opaco para quedar detrás de los objetos; cambia el grupo 0
+            if self.settings.helpers.grid {
+                self.grid.draw(&mut render_pass);
+            }
+
+            // Al final porque cambia el layout del grupo 0
+            if let Some(selection) = self.selection {
+                let mesh = &self.meshes[self.objects[selection.object].mesh];
+                render_pass.set_pipeline(&self.highlight.pipeline);
+                render_pass.set_bind_group(0, &self.highlight.object_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, mesh.asset.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(mesh.asset.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.asset.index_count, 0, 0..1);
+                if selection.triangle.is_some() {
+                    render_pass.set_bind_group(0, &self.highlight.triangle_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, self.highlight.triangle_buffer.slice(..));
+                    render_pass.draw(0..3, 0..1);
+                }
+            }
+            self.debug_draw.draw(&mut render_pass);
+        }
+
+        // La captura se hace antes de la interfaz para guardar solo la escena
+        let capture = if self.screenshot_requested || self.recorder.is_some() {
+            let capture = capture_texture(&self.device, &mut encoder, &output.texture);
+            if capture.is_none() {
+                // Sin esto la captura se reintentaría en cada frame sin avisar
+                println!("⚠️  No se puede capturar la superficie: formato no soportado {:?}", output.texture.format());
+                self.screenshot_requested = false;
+                self.stop_recording();
+            }
+            capture
+        } else {
+            None
+        };
+
+        // El HUD tampoco sale en las capturas
+        if let Some(text) = self.text.as_ref().filter(|text| !text.is_empty()) {
+            let mut text_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Text Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            text.draw(&mut text_pass);
+        }
+
+        let settings = &mut self.settings;
+        let camera = &mut self.camera;
+        let uniforms = &mut self.uniforms;
+        let clock = &mut self.clock;
+        let animation = &mut self.animation;
+        let skinned = self.skinned.as_mut();
+        let morph = self.morph.as_mut();
+        let gizmo = &mut self.gizmo;
+        let history = &self.history;
+        let selected = self.selection.map(|selection| (selection.object, self.objects[selection.object].base));
+        let assets = &self.assets;
+        self.debug_ui.render(
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &view,
+            &self.window,
+            |ctx| {
+                draw_debug_panels(ctx, settings, camera, uniforms, clock);
+                draw_animation_panels(ctx, animation, skinned, morph);
+                draw_gizmo_panel(ctx, gizmo, history, selected);
+                draw_assets_panel(ctx, assets);
+            },
+        );
+
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.resolve(&mut encoder);
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.collect(&self.device);
+        }
+        if let Some(culler) = &mut self.gpu_culler {
+            culler.collect(&self.device);
+            // Los contadores llegan con retraso; hasta la primera lectura no hay dato
+            self.stats = match culler.visible_count() {
+                Some(drawn) => RenderStats { drawn, culled: culler.object_count().saturating_sub(drawn) },
+                None => RenderStats::default(),
+            };
+        }
+
+        if let Some(capture) = capture {
+            self.save_capture(capture);
+        }
+
+        Ok(())
+    }
+
+    // Una misma copia del frame sirve para la captura de F12 y para la grabación
+    fn save_capture(&mut self, capture: PendingCapture) {
+        let screenshot = std::mem::take(&mut self.screenshot_requested)
+            .then(|| std::path::Path::new("screenshots").join(format!("captura_{}.png", timestamp_millis())));
+        let frame = self.recorder.as_mut().map(|recorder| recorder.next_path());
+        if screenshot.is_none() && frame.is_none() {
+            return;
+        }
+
+        let image = match capture.read(&self.device) {
+            Ok(image) => image,
+            Err(e) => {
+                println!("⚠️  No se pudo leer el frame: {}", e);
+                self.stop_recording();
+                return;
+            }
+        };
+        if let Some(path) = screenshot {
+            match save_image(&image, &path) {
+                Ok(()) => println!("📷 Captura guardada en {}", path.display()),
+                Err(e) => println!("⚠️  No se pudo guardar {}: {}", path.display(), e),
+            }
+        }
+        if let Some(path) = frame {
+            if let Err(e) = save_image(&image, &path) {
+                println!("⚠️  No se pudo guardar {}: {}", path.display(), e);
+                self.stop_recording();
+            }
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            println!("🎬 Grabación terminada: {} frames en {}", recorder.frame, recorder.dir.display());
+            self.clock.set_mode(recorder.previous_clock_mode);
+        }
+    }
+}
+
+// Módulo de shader.wgsl con el fragment shader que usan todas las mallas de la escena
+struct SceneShader {
+    shader: Handle<ShaderAsset>,
+    // "fs_main" con textura 2D, "fs_cubemap" con cubemap o "fs_array" con textura array
+    fragment_entry_point: &'static str,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_scene_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &SceneShader,
+    format: wgpu::TextureFormat,
+    polygon_mode: wgpu::PolygonMode,
+    vertex_entry_point: &str,
+    vertex_layout: wgpu::VertexBufferLayout<'_>,
+) -> wgpu::RenderPipeline {
+    let label = match (polygon_mode, vertex_entry_point) {
+        (wgpu::PolygonMode::Line, _) => "Wireframe Pipeline",
+        (_, "vs_wire") => "Barycentric Wireframe Pipeline",
+        (_, "vs_skinned") => "Skinned Pipeline",
+        (_, "vs_morph") => "Morph Pipeline",
+        _ => "Render Pipeline",
+    };
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: &shader.shader.module,
+            entry_point: vertex_entry_point,
+            buffers: &[vertex_layout, InstanceRaw::desc()],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader.shader.module,
+            entry_point: shader.fragment_entry_point,
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None, // Desactivar culling para asegurar que se vean todas las caras
+            polygon_mode,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
+
+fn create_texture_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    texture: &Texture,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&texture.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&texture.sampler),
+            },
+        ],
+        label: Some("diffuse_bind_group"),
+    })
+}
+
+struct Texture {
+    view: wgpu::TextureView,
+    sampler: Arc<wgpu::Sampler>,
+    // Textura del registro detrás de la vista; la mantiene viva mientras se use
+    asset: Handle<TextureAsset>,
+}
+
+// Las texturas de color se enlazan con un sampler Filtering, que no admite comparación
+fn color_sampler(settings: &SamplerSettings) -> SamplerSettings {
+    let mut settings = *settings;
+    if settings.compare.take().is_some() {
+        println!("⚠️  La comparación del sampler solo sirve para texturas de profundidad; se ignora");
+    }
+    settings
+}
+
+// Paneles de la interfaz de depuración, con los valores vivos de la cámara y los uniforms
+fn draw_debug_panels(
+    ctx: &egui::Context,
+    settings: &mut RenderSettings,
+    camera: &mut Camera,
+    uniforms: &mut Uniforms,
+    clock: &mut AnimationClock,
+) {
+    egui::Window::new("Renderer").default_width(260.0).show(ctx, |ui| {
+        ui.add(egui::Slider::new(&mut settings.rotation_speed, 0.0..=5.0).text("Velocidad de rotación"));
+        ui.horizontal(|ui| {
+            ui.label("Color de fondo");
+            ui.color_edit_button_rgb(&mut settings.clear_color);
+        });
+
+        ui.collapsing("Sampler", |ui| draw_sampler_editor(ui, &mut settings.sampler));
+
+        egui::ComboBox::from_label("Vista (1-7)")
+            .selected_text(settings.view.name())
+            .show_ui(ui, |ui| {
+                for view in DebugView::ALL {
+                    ui.selectable_value(&mut settings.view, view, view.name());
+                }
+            });
+        egui::ComboBox::from_label("Picking (clic)")
+            .selected_text(settings.pick_mode.name())
+            .show_ui(ui, |ui| {
+                for mode in [PickMode::Ray, PickMode::IdBuffer] {
+                    ui.selectable_value(&mut settings.pick_mode, mode, mode.name());
+                }
+            });
+
+        ui.collapsing("Ayudas", |ui| {
+            let helpers = &mut settings.helpers;
+            ui.checkbox(&mut helpers.boxes, "Cajas (AABB)");
+            ui.checkbox(&mut helpers.spheres, "Esferas envolventes");
+            ui.checkbox(&mut helpers.axes, "Ejes del mundo");
+            ui.checkbox(&mut helpers.light, "Dirección de la luz");
+            ui.checkbox(&mut helpers.grid, "Grilla del suelo (G)");
+            if helpers.grid {
+                let grid = &mut helpers.grid_settings;
+                ui.add(egui::DragValue::new(&mut grid.height).speed(0.05).prefix("altura "));
+                ui.add(egui::DragValue::new(&mut grid.cell_size).speed(0.01).range(0.01..=10.0).prefix("celda "));
+                ui.add(egui::Slider::new(&mut grid.major_every, 1..=20).text("Celdas por línea mayor"));
+                ui.checkbox(&mut grid.axes, "Ejes X, Y, Z");
+            }
+            // Se congela la cámara actual para verla al moverla desde el panel Cámara
+            let mut frozen = helpers.frozen_frustum.is_some();
+            if ui.checkbox(&mut frozen, "Congelar frustum de la cámara").changed() {
+                helpers.frozen_frustum = frozen.then(|| {
+                    let view = Matrix4::look_at_rh(camera.eye, camera.target, camera.up);
+                    camera.projection() * view
+                });
+            }
+        });
+    });
+
+    egui::Window::new("Cámara").default_width(260.0).show(ctx, |ui| {
+        let point = |ui: &mut egui::Ui, label: &str, p: &mut Point3<f32>| {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.add(egui::DragValue::new(&mut p.x).speed(0.05).prefix("x "));
+                ui.add(egui::DragValue::new(&mut p.y).speed(0.05).prefix("y "));
+                ui.add(egui::DragValue::new(&mut p.z).speed(0.05).prefix("z "));
+            });
+        };
+        point(ui, "Ojo", &mut camera.eye);
+        point(ui, "Objetivo", &mut camera.target);
+        ui.add(egui::Slider::new(&mut camera.fovy, 10.0..=120.0).text("FOV (°)"));
+        ui.checkbox(&mut camera.orthographic, "Ortográfica (O)");
+        ui.add(egui::Slider::new(&mut camera.znear, 0.01..=10.0).logarithmic(true).text("znear"));
+        ui.add(egui::Slider::new(&mut camera.zfar, 10.0..=1000.0).logarithmic(true).text("zfar"));
+        ui.label(format!("Aspecto: {:.3}", camera.aspect));
+
+        ui.collapsing("view_proj", |ui| {
+            egui::Grid::new("view_proj").striped(true).show(ui, |ui| {
+                // Se muestra por filas; cgmath guarda la matriz por columnas
+                for row in 0..4 {
+                    for column in uniforms.view_proj.iter() {
+                        ui.monospace(format!("{:>8.3}", column[row]));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    });
+
+    egui::Window::new("Reloj").default_width(260.0).show(ctx, |ui| {
+        ui.label(format!("Tiempo: {:.3} s | Frame: {}", clock.time(), clock.frame()));
+        ui.horizontal(|ui| {
+            let mut mode = clock.mode();
+            ui.selectable_value(&mut mode, ClockMode::RealTime, "Tiempo real");
+            let fixed = match mode {
+                ClockMode::FixedStep { .. } => mode,
+                _ => ClockMode::FixedStep { step: 1.0 / 60.0 },
+            };
+            ui.selectable_value(&mut mode, fixed, "Paso fijo");
+            ui.selectable_value(&mut mode, ClockMode::Paused, "Pausado");
+            if mode != clock.mode() {
+                clock.set_mode(mode);
+            }
+        });
+        let mut scale = clock.scale();
+        if ui.add(egui::Slider::new(&mut scale, MIN_TIME_SCALE..=4.0).logarithmic(true).text("Escala de tiempo")).changed() {
+            clock.set_scale(scale);
+        }
+        ui.horizontal(|ui| {
+            if ui.button(if clock.is_paused() { "▶ Continuar (P)" } else { "⏸ Pausar (P)" }).clicked() {
+                clock.toggle_pause();
+            }
+            if ui.button("⏭ Avanzar frame (N)").clicked() {
+                clock.step();
+            }
+            if ui.button("⏮ Reiniciar").clicked() {
+                clock.reset();
+            }
+        });
+    });
+
+    egui::Window::new("Luz").default_width(260.0).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Dirección");
+            for (axis, value) in ["x ", "y ", "z "].iter().zip(uniforms.light_direction.iter_mut()) {
+                ui.add(egui::DragValue::new(value).speed(0.02).range(-1.0..=1.0).prefix(*axis));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Color");
+            ui.color_edit_button_rgb(&mut uniforms.light_color);
+        });
+        ui.add(egui::Slider::new(&mut uniforms.light_intensity, 0.0..=3.0).text("Intensidad"));
+        ui.add(egui::Slider::new(&mut uniforms.ambient, 0.0..=1.0).text("Ambiente"));
+    });
+}
+
+// Ajustes del sampler de la textura del visor. La comparación no aparece porque
+// solo sirve para texturas de profundidad.
+fn draw_sampler_editor(ui: &mut egui::Ui, sampler: &mut SamplerSettings) {
+    fn combo<T: Copy + PartialEq + std::fmt::Debug>(ui: &mut egui::Ui, label: &str, value: &mut T, options: &[T]) {
+        egui::ComboBox::from_label(label)
+            .selected_text(format!("{:?}", value))
+            .show_ui(ui, |ui| {
+                for option in options {
+                    ui.selectable_value(value, *option, format!("{:?}", option));
+                }
+            });
+    }
+    combo(ui, "Borde U", &mut sampler.address_mode_u, &AddressMode::ALL);
+    combo(ui, "Borde V", &mut sampler.address_mode_v, &AddressMode::ALL);
+    combo(ui, "Borde W", &mut sampler.address_mode_w, &AddressMode::ALL);
+    let filters = [Filter::Nearest, Filter::Linear];
+    combo(ui, "Filtro mag", &mut sampler.mag_filter, &filters);
+    combo(ui, "Filtro min", &mut sampler.min_filter, &filters);
+    combo(ui, "Filtro mip", &mut sampler.mipmap_filter, &filters);
+    ui.add(egui::Slider::new(&mut sampler.lod_min_clamp, 0.0..=16.0).text("LOD mínimo"));
+    ui.add(egui::Slider::new(&mut sampler.lod_max_clamp, 0.0..=32.0).text("LOD máximo"));
+    ui.add(egui::Slider::new(&mut sampler.anisotropy_clamp, 1..=16).text("Anisotropía"));
+    combo(ui, "Color de borde", &mut sampler.border_color, &BorderColor::ALL);
+    if ui.button("Restablecer").clicked() {
+        *sampler = SamplerSettings::default();
+    }
+}
+
+// Assets vivos del registro con su memoria, de mayor a menor
+fn draw_assets_panel(ctx: &egui::Context, assets: &Assets) {
+    egui::Window::new("Assets").default_width(320.0).default_open(false).show(ctx, |ui| {
+        ui.label(assets.memory().to_string());
+        ui.label(format!("Samplers en caché: {}", assets.sampler_count()));
+        egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+            egui::Grid::new("assets").striped(true).show(ui, |ui| {
+                for info in assets.report() {
+                    ui.label(info.kind);
+                    ui.label(&info.name);
+                    ui.monospace(format_bytes(info.bytes));
+                    ui.label(format!("{} handles", info.handles));
+                    ui.end_row();
+                }
+            });
+        });
+    });
+}
+
+// Opciones del gizmo, solo con un objeto elegido
+fn draw_gizmo_panel(ctx: &egui::Context, gizmo: &mut Gizmo, history: &History, selected: Option<(usize, Transform)>) {
+    let Some((object, transform)) = selected else { return };
+    egui::Window::new("Gizmo").default_width(260.0).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            for mode in [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale] {
+                ui.selectable_value(&mut gizmo.mode, mode, mode.name());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Ejes (Q)");
+            ui.selectable_value(&mut gizmo.space, GizmoSpace::World, "Mundo");
+            ui.selectable_value(&mut gizmo.space, GizmoSpace::Local, "Locales");
+        });
+        ui.checkbox(&mut gizmo.snap, "Snapping (o mantener Ctrl)");
+        let snapping = &mut gizmo.snapping;
+        ui.add(egui::DragValue::new(&mut snapping.translate).speed(0.01).range(0.01..=10.0).prefix("mover "));
+        ui.add(egui::DragValue::new(&mut snapping.rotate_degrees).speed(0.5).range(1.0..=90.0).prefix("rotar ").suffix("°"));
+        ui.add(egui::DragValue::new(&mut snapping.scale).speed(0.01).range(0.01..=1.0).prefix("escalar "));
+
+        ui.separator();
+        let t = transform.translation;
+        let s = transform.scale;
+        ui.label(format!("Objeto {}", object));
+        ui.monospace(format!("posición ({:.2}, {:.2}, {:.2})", t.x, t.y, t.z));
+        ui.monospace(format!("escala   ({:.2}, {:.2}, {:.2})", s.x, s.y, s.z));
+        ui.label(format!(
+            "Deshacer (Ctrl+Z): {} | Rehacer (Ctrl+Y): {}",
+            history.undo_len(),
+            history.redo_len()
+        ));
+    });
+}
+
+// Paneles de los clips de keyframes, el esqueleto y los morph targets, solo si están cargados
+fn draw_animation_panels(
+    ctx: &egui::Context,
+    animation: &mut AnimationState,
+    skinned: Option<&mut SkinnedCharacter>,
+    morph: Option<&mut MorphObject>,
+) {
+    if !animation.clips.is_empty() {
+        egui::Window::new("Animación").default_width(260.0).show(ctx, |ui| {
+            let names: Vec<&str> = animation.clips.iter().map(|c| c.name.as_str()).collect();
+            egui::ComboBox::from_label("Clip")
+                .selected_text(names[animation.current])
+                .show_ui(ui, |ui| {
+                    for (i, name) in names.iter().enumerate() {
+                        ui.selectable_value(&mut animation.current, i, *name);
+                    }
+                });
+
+            let player = &mut animation.player;
+            let duration = animation.clips[animation.current].duration();
+            ui.horizontal(|ui| {
+                if ui.button(if player.playing { "⏸ Pausar (Espacio)" } else { "▶ Reproducir (Espacio)" }).clicked() {
+                    player.playing = !player.playing;
+                }
+                if ui.button("⏮ Inicio").clicked() {
+                    player.seek(0.0);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut player.mode, PlaybackMode::Once, "Una vez");
+                ui.selectable_value(&mut player.mode, PlaybackMode::Loop, "Bucle");
+                ui.selectable_value(&mut player.mode, PlaybackMode::PingPong, "Ida y vuelta");
+            });
+            ui.add(egui::Slider::new(&mut player.speed, -2.0..=2.0).text("Velocidad"));
+            let mut time = player.clip_time(duration);
+            if ui.add(egui::Slider::new(&mut time, 0.0..=duration).text("Tiempo (s)")).changed() {
+                player.seek(time);
+            }
+        });
+    }
+
+    if let Some(character) = skinned {
+        egui::Window::new("Esqueleto").default_width(260.0).show(ctx, |ui| {
+            let clips = &character.model.clips;
+            let animator = &mut character.animator;
+            ui.label(format!("{} articulaciones", character.model.skeleton.joints.len()));
+            if clips.is_empty() {
+                ui.label("El modelo no tiene animaciones");
+                return;
+            }
+
+            ui.add(egui::Slider::new(&mut character.fade_time, 0.0..=2.0).text("Crossfade (s)"));
+            for (i, clip) in clips.iter().enumerate() {
+                let label = format!("{} ({:.2} s)", clip.name, clip.duration());
+                if ui.selectable_label(animator.clip == i, label).clicked() && animator.clip != i {
+                    animator.crossfade(i, character.fade_time);
+                }
+            }
+
+            ui.separator();
+            ui.add_enabled_ui(!animator.is_fading(), |ui| {
+                let name = |clip: Option<usize>| clip.map_or("Ninguno", |i| clips[i].name.as_str());
+                let mut blend_clip = animator.blend_clip;
+                egui::ComboBox::from_label("Mezclar con")
+                    .selected_text(name(blend_clip))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut blend_clip, None, "Ninguno");
+                        for i in 0..clips.len() {
+                            ui.selectable_value(&mut blend_clip, Some(i), name(Some(i)));
+                        }
+                    });
+                if blend_clip != animator.blend_clip {
+                    animator.blend_clip = blend_clip;
+                    animator.blend_player.seek(animator.player.time());
+                }
+                if animator.blend_clip.is_some() {
+                    ui.add(egui::Slider::new(&mut animator.blend, 0.0..=1.0).text("Peso"));
+                }
+            });
+
+            ui.separator();
+            let player = &mut animator.player;
+            ui.checkbox(&mut player.playing, "Reproducir (Espacio)");
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut player.mode, PlaybackMode::Once, "Una vez");
+                ui.selectable_value(&mut player.mode, PlaybackMode::Loop, "Bucle");
+                ui.selectable_value(&mut player.mode, PlaybackMode::PingPong, "Ida y vuelta");
+            });
+            ui.add(egui::Slider::new(&mut player.speed, -2.0..=2.0).text("Velocidad"));
+        });
+    }
+
+    if let Some(morph) = morph {
+        egui::Window::new("Morph targets").default_width(260.0).show(ctx, |ui| {
+            let tracks = &morph.model.tracks;
+            let name = |track: Option<usize>| track.map_or("Manual", |i| tracks[i].name.as_str());
+            egui::ComboBox::from_label("Pesos")
+                .selected_text(name(morph.track))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut morph.track, None, "Manual");
+                    for i in 0..tracks.len() {
+                        ui.selectable_value(&mut morph.track, Some(i), name(Some(i)));
+                    }
+                });
+            if morph.track.is_some() {
+                ui.checkbox(&mut morph.player.playing, "Reproducir (Espacio)");
+                ui.add(egui::Slider::new(&mut morph.player.speed, -2.0..=2.0).text("Velocidad"));
+            }
+
+            ui.separator();
+            ui.add_enabled_ui(morph.track.is_none(), |ui| {
+                for (target, weight) in morph.model.targets.iter().zip(&mut morph.weights) {
+                    ui.add(egui::Slider::new(weight, 0.0..=1.0).text(&target.name));
+                }
+            });
+        });
+    }
+}
+
+// Cubo de ejemplo con normales por cara y dos morph targets: "estirar" alarga el
+// cubo en Y y "torcer" gira la cara superior 45° alrededor de Y
+fn create_morph_cube() -> MorphModel {
+    let vertices: Vec<MorphVertex> = VERTICES
+        .chunks(4)
+        .flat_map(|face| {
+            let [a, b, c] = [face[0], face[1], face[2]].map(|v| Vector3::from(v.position));
+            let normal: [f32; 3] = (b - a).cross(c - a).normalize().into();
+            face.iter().map(move |v| MorphVertex {
+                position: v.position,
+                tex_coords: v.tex_coords,
+                normal,
+            })
+        })
+        .collect();
+
+    let stretch = MorphTarget {
+        name: "estirar".to_string(),
+        positions: vertices.iter().map(|v| [0.0, v.position[1] * 0.8, 0.0]).collect(),
+        normals: Vec::new(),
+    };
+
+    // Ángulo de 0 en la base a 45° arriba
+    let twist_angle = |y: f32| Deg(45.0) * ((y + 1.0) * 0.5);
+    let rotate = |p: [f32; 3], angle: Deg<f32>| -> [f32; 3] { (Matrix3::from_angle_y(angle) * Vector3::from(p)).into() };
+    let twist = MorphTarget {
+        name: "torcer".to_string(),
+        positions: vertices
+            .iter()
+            .map(|v| {
+                let rotated = rotate(v.position, twist_angle(v.position[1]));
+                std::array::from_fn(|i| rotated[i] - v.position[i])
+            })
+            .collect(),
+        normals: vertices
+            .iter()
+            .map(|v| {
+                let rotated = rotate(v.normal, twist_angle(v.position[1]));
+                std::array::from_fn(|i| rotated[i] - v.normal[i])
+            })
+            .collect(),
+    };
+
+    let mut model = MorphModel {
+        indices: INDICES.to_vec(),
+        vertices,
+        targets: vec![stretch, twist],
+        default_weights: vec![0.0, 0.0],
+        tracks: vec![WeightTrack {
+            name: "estirar y torcer".to_string(),
+            interpolation: Interpolation::Linear,
+            times: vec![0.0, 1.0, 2.0, 3.0],
+            weights: vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![0.0, 1.0], vec![0.0, 0.0]],
+        }],
+    };
+    model.validate();
+    model
+}
+
+// Primitivas de --shape, del mismo tamaño que el cubo original (lado 2)
+fn create_shape(name: &str, atlas: Option<&CubeUvLayout>) -> Option<MeshData> {
+    Some(match name {
+        "cube" => primitives::cube(2.0, &CubeUvLayout::full()),
+        // Una región distinta del atlas en cada cara (por defecto, la rejilla del atlas generado)
+        "atlas-cube" => primitives::cube(2.0, &atlas.copied().unwrap_or_else(|| CubeUvLayout::grid(4, 2))),
+        "sphere" => primitives::uv_sphere(1.0, 32, 16),
+        "icosphere" => primitives::icosphere(1.0, 3),
+        "cylinder" => primitives::cylinder(1.0, 2.0, 32),
+        "cone" => primitives::cone(1.0, 2.0, 32),
+        "torus" => primitives::torus(0.75, 0.25, 32, 16),
+        "plane" => primitives::plane(2.0, 2.0, 4, 4),
+        "capsule" => primitives::capsule(0.5, 1.0, 32, 8),
+        _ => return None,
+    })
+}
+
+// Escena por defecto: rejilla de --grid x --grid copias de la primitiva o el cubo
+fn build_grid(
+    device: &wgpu::Device,
+    assets: &mut Assets,
+    options: &Options,
+    atlas: Option<&CubeUvLayout>,
+    barycentric: bool,
+) -> (Vec<Mesh>, Vec<SceneObject>) {
+    let shape = match options.shape.as_deref() {
+        Some(name) => {
+            let shape = create_shape(name, atlas);
+            if shape.is_none() {
+                println!("⚠️  Primitiva desconocida '{}', usando el cubo", name);
+            }
+            shape
+        }
+        None => atlas.map(|layout| primitives::cube(2.0, layout)),
+    };
+    let shape_name = match (options.shape.as_deref(), atlas) {
+        (Some(name), _) => name,
+        (None, Some(_)) => "atlas-cube",
+        (None, None) => "cube",
+    };
+    let source = MeshSource::Primitive(shape_name.to_string());
+    let shape_mesh = shape.and_then(|data| match Mesh::from_data(device, assets, &mesh_key(&source), &data, barycentric) {
+        Ok(mut mesh) => {
+            mesh.source = source.clone();
+            println!(
+                "✅ Primitiva generada: {} vértices, {} triángulos",
+                data.vertices.len(),
+                data.triangle_count()
+            );
+            Some(mesh)
+        }
+        Err(e) => {
+            println!("⚠️  {}, usando el cubo", e);
+            None
+        }
+    });
+    let meshes = vec![shape_mesh.unwrap_or_else(|| Mesh::cube(device, assets, barycentric))];
+
+    // Rejilla de grid x grid cubos en el plano XZ (1 = solo el cubo central)
+    let spacing = 3.0;
+    let half = (options.grid as f32 - 1.0) * 0.5;
+    let mut objects = Vec::new();
+    for i in 0..options.grid {
+        for j in 0..options.grid {
+            let offset = Vector3::new(
+                (i as f32 - half) * spacing,
+                0.0,
+                (j as f32 - half) * spacing,
+            );
+            let base = Transform::from_translation(offset);
+            let layer = if options.layer_per_instance {
+                [objects.len() as u32, 0]
+            } else {
+                [0, 1]
+            };
+            objects.push(SceneObject {
+                mesh: 0,
+                layer,
+                base,
+                model: base.to_matrix(),
+            });
+        }
+    }
+    (meshes, objects)
+}
+
+// Escena de --scene. Hay una malla por cada par (geometría, material): los objetos
+// se dibujan agrupados por malla y cada grupo usa un solo bind group. Las mallas de
+// archivo se piden al cargador una vez por ruta y hasta que llegan se dibuja el cubo.
+fn build_scene(
+    device: &wgpu::Device,
+    assets: &mut Assets,
+    asset_loader: &mut AssetLoader,
+    scene: &SceneFile,
+    materials: &[Material],
+    atlas: Option<&CubeUvLayout>,
+    barycentric: bool,
+) -> (Vec<Mesh>, Vec<SceneObject>, Vec<PendingLoad>) {
+    let mut meshes: Vec<Mesh> = Vec::new();
+    let mut objects = Vec::new();
+    let mut pending = Vec::new();
+    for desc in &scene.objects {
+        let material = desc.material.as_ref().and_then(|name| {
+            let index = materials.iter().position(|material| &material.desc.name == name);
+            if index.is_none() {
+                println!("⚠️  Material desconocido '{}', usando la textura del visor", name);
+            }
+            index
+        });
+        let mesh = match meshes.iter().position(|mesh| mesh.source == desc.mesh && mesh.material == material) {
+            Some(index) => index,
+            None => {
+                if let MeshSource::File(path) = &desc.mesh {
+                    if !pending.iter().any(|load: &PendingLoad| &load.path == path) {
+                        pending.push(PendingLoad {
+                            id: asset_loader.load_mesh(path),
+                            path: path.clone(),
+                        });
+                    }
+                }
+                let mut mesh = load_scene_mesh(device, assets, &desc.mesh, atlas, barycentric);
+                mesh.material = material;
+                meshes.push(mesh);
+                meshes.len() - 1
+            }
+        };
+        let base: Transform = desc.transform.into();
+        objects.push(SceneObject {
+            mesh,
+            layer: [0, 1],
+            base,
+            model: base.to_matrix(),
+        });
+    }
+
+    if objects.is_empty() {
+        println!("⚠️  La escena no tiene objetos, se muestra el cubo");
+        meshes.push(Mesh::cube(device, assets, barycentric));
+        let base = Transform::default();
+        objects.push(SceneObject {
+            mesh: 0,
+            layer: [0, 1],
+            base,
+            model: base.to_matrix(),
+        });
+    }
+    (meshes, objects, pending)
+}
+
+// Las primitivas se crean en el momento; las mallas de archivo empiezan como el cubo
+// y se reemplazan al terminar su carga. Si la malla no se puede crear se queda el cubo,
+// pero se conserva la fuente para que la escena se guarde igual que se leyó.
+fn load_scene_mesh(
+    device: &wgpu::Device,
+    assets: &mut Assets,
+    source: &MeshSource,
+    atlas: Option<&CubeUvLayout>,
+    barycentric: bool,
+) -> Mesh {
+    let mut mesh = match source {
+        MeshSource::Primitive(name) => create_shape(name, atlas)
+            .ok_or_else(|| "primitiva desconocida".to_string())
+            .and_then(|data| Mesh::from_data(device, assets, &mesh_key(source), &data, barycentric))
+            .unwrap_or_else(|e| {
+                println!("⚠️  {}: {}, usando el cubo", name, e);
+                Mesh::cube(device, assets, barycentric)
+            }),
+        MeshSource::File(_) => Mesh::cube(device, assets, barycentric),
+    };
+    mesh.source = source.clone();
+    mesh
+}
+
+// Material de la escena: su descripción, el sampler resuelto y la textura con
+// sampler del registro. Sin textura (cargando o fallida) se usa la del visor.
+struct Material {
+    desc: SceneMaterial,
+    sampler: SamplerSettings,
+    asset: Option<Handle<MaterialAsset>>,
+}
+
+// Pide al cargador la textura de cada material, una vez por ruta
+fn queue_material_textures(asset_loader: &mut AssetLoader, materials: &[Material]) -> Vec<PendingLoad> {
+    let mut pending: Vec<PendingLoad> = Vec::new();
+    for material in materials {
+        if !pending.iter().any(|load| load.path == material.desc.texture) {
+            pending.push(PendingLoad {
+                id: asset_loader.load_texture(&material.desc.texture),
+                path: material.desc.texture.clone(),
+            });
+        }
+    }
+    pending
+}
+
+// Función para generar textura atlas con diferentes colores
+fn create_texture_atlas() -> Vec<u8> {
+    let width = 256;
+    let height = 128;
+    let section_width = width / 4; // 4 secciones horizontales
+    let section_height = height / 2; // 2 secciones verticales
+    
+    let img = ImageBuffer::from_fn(width, height, |x, y| {
+        let section_x = x / section_width;
+        let section_y = y / section_height;
+        
+        // Determinar el color basado en la sección
+        match (section_x, section_y) {
+            (0, 0) => Rgba([255u8, 100u8, 100u8, 255u8]), // Rojo - Front face
+            (1, 0) => Rgba([100u8, 255u8, 100u8, 255u8]), // Verde - Back face
+            (2, 0) => Rgba([100u8, 100u8, 255u8, 255u8]), // Azul - Top face
+            (3, 0) => Rgba([255u8, 255u8, 100u8, 255u8]), // Amarillo - Bottom face
+            (0, 1) => Rgba([255u8, 100u8, 255u8, 255u8]), // Magenta - Right face
+            (1, 1) => Rgba([100u8, 255u8, 255u8, 255u8]), // Cian - Left face
+            _ => Rgba([255u8, 255u8, 255u8, 255u8]),       // Blanco por defecto
+        }
+    });
+    
+    let mut bytes = Vec::new();
+    use std::io::Cursor;
+    img.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .expect("Error al escribir imagen");
+    bytes
+}
+
+// Vista y sampler de una textura del registro. Avisa si la textura comprimida se
+// tuvo que descomprimir en CPU porque el adaptador no soporta el formato.
+fn texture_from_asset(asset: Handle<TextureAsset>, sampler: Arc<wgpu::Sampler>) -> Texture {
+    if asset.source_format.is_compressed() {
+        if asset.compressed {
+            println!(
+                "✅ Textura {:?} de {} x {} con {} mipmaps subida comprimida desde {}",
+                asset.source_format,
+                asset.texture.width(),
+                asset.texture.height(),
+                asset.texture.mip_level_count(),
+                asset.name()
+            );
+        } else {
+            println!(
+                "⚠️  El adaptador no soporta {:?}, {} descomprimida en CPU a RGBA8",
+                asset.source_format,
+                asset.name()
+            );
+        }
+    }
+
+    let view = asset.texture.create_view(&wgpu::TextureViewDescriptor::default());
+    Texture {
+        view,
+        sampler,
+        asset,
+    }
+}
+
+// Función principal para crear o cargar textura. Devuelve un tablero de ajedrez
+// mientras el cargador decodifica la textura real, la carga pendiente y la
+// distribución de UV del cubo cuando la textura es un atlas con una región por cara.
+// Carga en segundo plano junto con la ruta pedida, que es su clave en el registro
+struct PendingLoad {
+    id: LoadId,
+    path: std::path::PathBuf,
+}
+
+// Texturas 2D que se prueban en orden hasta que una se carga
+#[derive(Clone, Debug)]
+enum TextureCandidate {
+    File(std::path::PathBuf),
+    // Atlas generado en código y guardado en assets/texture.png
+    Generated,
+}
+
+impl TextureCandidate {
+    fn load(&self, loader: &mut AssetLoader) -> PendingLoad {
+        let (id, path) = match self {
+            TextureCandidate::File(path) => (loader.load_texture(path), path.clone()),
+            TextureCandidate::Generated => {
+                let id = loader.load_with("textura generada", || {
+                    // Crear directorio assets si no existe
+                    #[allow(clippy::unnecessary_lazy_evaluations)]
+                    std::fs::create_dir_all("assets").unwrap_or_else(|_| {});
+
+                    // Generar textura y guardarla
+                    let generated_bytes = create_texture_atlas();
+                    std::fs::write("assets/texture.png", &generated_bytes)
+                        .unwrap_or_else(|_| println!("No se pudo guardar la textura generada"));
+                    println!("✅ Textura atlas generada y guardada en assets/texture.png");
+
+                    let image = image::load_from_memory(&generated_bytes).map_err(|e| e.to_string())?;
+                    Ok(LoadedAsset::Image(image.to_rgba8()))
+                });
+                (id, "assets/texture.png".into())
+            }
+        };
+        PendingLoad { id, path }
+    }
+}
+
+impl std::fmt::Display for TextureCandidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextureCandidate::File(path) => write!(f, "{}", path.display()),
+            TextureCandidate::Generated => write!(f, "la textura generada"),
+        }
+    }
+}
+
+// Devuelve la textura inicial, la carga en curso, las alternativas por si esa
+// carga falla y la distribución de UV del cubo si la textura es un atlas
+fn create_or_load_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    atlas_dir: Option<&std::path::Path>,
+    texture_path: Option<&std::path::Path>,
+    assets: &mut Assets,
+    loader: &mut AssetLoader,
+) -> (Texture, Option<PendingLoad>, Vec<TextureCandidate>, Option<CubeUvLayout>) {
+    let placeholder = |assets: &mut Assets| {
+        let image = LoadedAsset::Image(checkerboard(256, 8));
+        let path = std::path::Path::new("placeholder:checkerboard");
+        let handle = assets
+            .insert_texture(device, queue, path, TextureSettings::default(), &image)
+            .expect("Error al crear el placeholder");
+        texture_from_asset(handle, assets.sampler(device, &SamplerSettings::default()))
+    };
+
+    let mut candidates = Vec::new();
+    if let Some(path) = texture_path {
+        candidates.push(TextureCandidate::File(path.to_path_buf()));
+    } else if let Some(dir) = atlas_dir {
+        // El atlas se arma al arrancar porque la malla necesita su distribución de UV
+        match build_face_atlas(dir) {
+            Ok((image, layout)) => {
+                let path = std::path::Path::new("assets/atlas.png");
+                let handle = assets
+                    .insert_texture(device, queue, path, TextureSettings::default(), &LoadedAsset::Image(image))
+                    .expect("Error al cargar textura");
+                let texture = texture_from_asset(handle, assets.sampler(device, &SamplerSettings::default()));
+                return (texture, None, Vec::new(), Some(layout));
+            }
+            Err(e) => println!("⚠️  No se pudo crear el atlas de {}: {}", dir.display(), e),
+        }
+    }
+
+    // Sin --texture ni --atlas se usa la textura cocinada por cubo-cook si existe, y
+    // si no assets/texture.jpg. Todas quedan como alternativas si la anterior falla.
+    for path in ["assets/texture.cubotex", "assets/texture.jpg"].map(std::path::Path::new) {
+        if path.exists() {
+            candidates.push(TextureCandidate::File(path.to_path_buf()));
+        }
+    }
+    candidates.push(TextureCandidate::Generated);
+
+    let first = candidates.remove(0);
+    // El atlas generado es una rejilla de 4 x 2 secciones, una por cara
+    let layout = if matches!(first, TextureCandidate::Generated) {
+        println!("⚠️  No se encontró assets/texture.jpg, generando textura por defecto...");
+        Some(CubeUvLayout::grid(4, 2))
+    } else {
+        None
+    };
+    (placeholder(assets), Some(first.load(loader)), candidates, layout)
+}
+
+// Empaqueta las imágenes de las caras y guarda assets/atlas.png y assets/atlas.json
+fn build_face_atlas(dir: &std::path::Path) -> Result<(image::RgbaImage, CubeUvLayout), Box<dyn std::error::Error>> {
+    let mut builder = AtlasBuilder::new(4);
+    builder.add_dir(dir)?;
+    let (image, layout) = builder.build()?;
+    let cube_layout = layout
+        .cube_layout()
+        .ok_or("faltan imágenes: se necesitan front, back, top, bottom, right y left")?;
+
+    std::fs::create_dir_all("assets")?;
+    image.save("assets/atlas.png")?;
+    layout.save_json(std::path::Path::new("assets/atlas.json"))?;
+    println!(
+        "✅ Atlas de {} x {} con {} imágenes guardado en assets/atlas.png y assets/atlas.json",
+        layout.width,
+        layout.height,
+        layout.rects.len()
+    );
+    Ok((image, cube_layout))
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
+    env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("cubo_viewer=info,textured_cube=info"),
+    )
+    .init();
     let event_loop = EventLoop::new()?;
     let window = WindowBuilder::new()
         .with_title("Cubo con Textura Atlas - Josero31")
         .with_inner_size(winit::dpi::LogicalSize::new(800, 600))
         .build(&event_loop)?;
 
-    let mut state = pollster::block_on(State::new(window));
+    let options = Options::from_args();
+    let clock = match options.fixed_step {
+        Some(fps) => AnimationClock::fixed_step(fps),
+        None => AnimationClock::real_time(),
+    };
+    let mut state = pollster::block_on(State::new(window, &options, clock));
 
     event_loop.run(move |event, target| {
         match event {
             Event::WindowEvent {
                 ref event,
                 window_id,
-            } if window_id == state.window().id() => match event {
-                WindowEvent::CloseRequested => target.exit(),
-                WindowEvent::Resized(physical_size) => {
-                    state.resize(*physical_size);
-                }
-                WindowEvent::ScaleFactorChanged { inner_size_writer: _, scale_factor: _ } => {
-                    // En la nueva API, necesitamos manejar esto diferente
-                    let new_size = state.window().inner_size();
-                    state.resize(new_size);
-                }
-                WindowEvent::KeyboardInput {
-                    event: key_event,
-                    ..
-                } => {
-                    if key_event.state == ElementState::Pressed {
-                        if let PhysicalKey::Code(KeyCode::Escape) = key_event.physical_key {
-                            target.exit();
+            } if window_id == state.window().id() => {
+                if state.input(event) {
+                    return;
+                }
+                match event {
+                    WindowEvent::CloseRequested => target.exit(),
+                    WindowEvent::Resized(physical_size) => {
+                        state.resize(*physical_size);
+                    }
+                    WindowEvent::ScaleFactorChanged { inner_size_writer: _, scale_factor: _ } => {
+                        // En la nueva API, necesitamos manejar esto diferente
+                        let new_size = state.window().inner_size();
+                        state.resize(new_size);
+                    }
+                    #[allow(clippy::collapsible_match)]
+                    WindowEvent::KeyboardInput {
+                        event: key_event,
+                        ..
+                    } => {
+                        if key_event.state == ElementState::Pressed {
+                            match key_event.physical_key {
+                                PhysicalKey::Code(KeyCode::Escape) => target.exit(),
+                                // F1 muestra u oculta la interfaz de depuración
+                                PhysicalKey::Code(KeyCode::F1) => {
+                                    state.debug_ui.visible = !state.debug_ui.visible;
+                                }
+                                PhysicalKey::Code(KeyCode::F12) => state.request_screenshot(),
+                                // F5 guarda la escena en el archivo de --scene (o scene.ron)
+                                PhysicalKey::Code(KeyCode::F5) => state.save_scene(),
+                                // P pausa, N avanza un frame, [ y ] cambian la escala de tiempo
+                                PhysicalKey::Code(KeyCode::KeyP) => state.clock.toggle_pause(),
+                                PhysicalKey::Code(KeyCode::KeyN) => state.clock.step(),
+                                PhysicalKey::Code(KeyCode::BracketLeft) => {
+                                    state.clock.set_scale(state.clock.scale() * 0.5);
+                                }
+                                PhysicalKey::Code(KeyCode::BracketRight) => {
+                                    state.clock.set_scale(state.clock.scale() * 2.0);
+                                }
+                                // F10 inicia o detiene la grabación de frames
+                                PhysicalKey::Code(KeyCode::F10) => state.toggle_recording(),
+                                // Ctrl+Z deshace, Ctrl+Y o Ctrl+Shift+Z rehace
+                                PhysicalKey::Code(KeyCode::KeyZ) if state.modifiers.control_key() => {
+                                    if state.modifiers.shift_key() {
+                                        state.redo();
+                                    } else {
+                                        state.undo();
+                                    }
+                                }
+                                PhysicalKey::Code(KeyCode::KeyY) if state.modifiers.control_key() => state.redo(),
+                                PhysicalKey::Code(KeyCode::KeyH) => state.hud_visible = !state.hud_visible,
+                                // G muestra la grilla del suelo, O alterna perspectiva y ortográfica
+                                PhysicalKey::Code(KeyCode::KeyG) => {
+                                    state.settings.helpers.grid = !state.settings.helpers.grid;
+                                }
+                                PhysicalKey::Code(KeyCode::KeyO) => {
+                                    state.camera.orthographic = !state.camera.orthographic;
+                                }
+                                // W, E y R eligen el modo del gizmo; Q alterna ejes locales y del mundo
+                                PhysicalKey::Code(KeyCode::KeyW) => state.gizmo.mode = GizmoMode::Translate,
+                                PhysicalKey::Code(KeyCode::KeyE) => state.gizmo.mode = GizmoMode::Rotate,
+                                PhysicalKey::Code(KeyCode::KeyR) => state.gizmo.mode = GizmoMode::Scale,
+                                PhysicalKey::Code(KeyCode::KeyQ) => {
+                                    state.gizmo.space = match state.gizmo.space {
+                                        GizmoSpace::World => GizmoSpace::Local,
+                                        GizmoSpace::Local => GizmoSpace::World,
+                                    };
+                                }
+                                PhysicalKey::Code(KeyCode::Space) => {
+                                    state.animation.player.playing = !state.animation.player.playing;
+                                    if let Some(morph) = &mut state.morph {
+                                        morph.player.playing = !morph.player.playing;
+                                    }
+                                    if let Some(character) = &mut state.skinned {
+                                        let player = &mut character.animator.player;
+                                        player.playing = !player.playing;
+                                    }
+                                }
+                                PhysicalKey::Code(code) => {
+                                    if let Some(view) = DebugView::from_key(code) {
+                                        state.settings.view = view;
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    // Clic izquierdo: gizmo del objeto elegido o picking del objeto bajo el cursor
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    } => state.mouse_pressed(),
+                    WindowEvent::RedrawRequested => {
+                        state.update();
+                        match state.render() {
+                            Ok(_) => {}
+                            Err(wgpu::SurfaceError::Lost) => state.resize(state.size),
+                            Err(wgpu::SurfaceError::OutOfMemory) => target.exit(),
+                            Err(e) => eprintln!("{:?}", e),
                         }
                     }
+                    _ => {}
                 }
-                _ => {}
-            },
+            }
             Event::AboutToWait => {
                 state.window().request_redraw();
             }
-            Event::WindowEvent {
-                event: WindowEvent::RedrawRequested,
-                window_id,
-            } if window_id == state.window().id() => {
-                state.update();
-                match state.render() {
-                    Ok(_) => {}
-                    Err(wgpu::SurfaceError::Lost) => state.resize(state.size),
-                    Err(wgpu::SurfaceError::OutOfMemory) => target.exit(),
-                    Err(e) => eprintln!("{:?}", e),
-                }
-            }
             _ => {}
         }
     })?;
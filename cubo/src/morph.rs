@@ -0,0 +1,375 @@
+use crate::animation::{hermite_component, Interpolation};
+use std::path::Path;
+use wgpu::util::DeviceExt;
+
+// Límite del uniform de pesos (4 vec4)
+pub const MAX_MORPH_TARGETS: usize = 16;
+
+// Vértice de una malla deformable. La normal va en la ubicación 9; si es cero
+// el shader usa la normal plana de la cara.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MorphVertex {
+    pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
+    pub normal: [f32; 3],
+}
+
+impl MorphVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x2,
+        9 => Float32x3,
+    ];
+
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<MorphVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// Desplazamientos de cada vértice cuando el peso del target es 1
+pub struct MorphTarget {
+    pub name: String,
+    pub positions: Vec<[f32; 3]>,
+    // Puede estar vacío si el target no cambia las normales
+    pub normals: Vec<[f32; 3]>,
+}
+
+// Pesos de todos los targets en cada keyframe
+pub struct WeightTrack {
+    pub name: String,
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    // Un vector de pesos por keyframe (tres en cubic spline: entrada, valor, salida)
+    pub weights: Vec<Vec<f32>>,
+}
+
+impl WeightTrack {
+    pub fn duration(&self) -> f32 {
+        self.times.last().copied().unwrap_or(0.0)
+    }
+
+    pub fn sample(&self, time: f32) -> Vec<f32> {
+        let cubic = self.interpolation == Interpolation::CubicSpline;
+        let value = |i: usize| &self.weights[if cubic { i * 3 + 1 } else { i }];
+
+        let last = self.times.len() - 1;
+        if time <= self.times[0] {
+            return value(0).clone();
+        }
+        if time >= self.times[last] {
+            return value(last).clone();
+        }
+
+        let i = self.times.partition_point(|&t| t <= time) - 1;
+        let dt = self.times[i + 1] - self.times[i];
+        let s = (time - self.times[i]) / dt;
+        let (a, b) = (value(i), value(i + 1));
+
+        match self.interpolation {
+            Interpolation::Step => a.clone(),
+            Interpolation::Linear => a.iter().zip(b).map(|(a, b)| a + (b - a) * s).collect(),
+            Interpolation::CubicSpline => {
+                let (out0, in1) = (&self.weights[i * 3 + 2], &self.weights[(i + 1) * 3]);
+                (0..a.len())
+                    .map(|w| hermite_component(a[w], out0[w], b[w], in1[w], s, dt))
+                    .collect()
+            }
+        }
+    }
+
+    fn is_valid(&self, target_count: usize) -> bool {
+        let per_key = if self.interpolation == Interpolation::CubicSpline { 3 } else { 1 };
+        !self.times.is_empty()
+            && self.weights.len() == self.times.len() * per_key
+            && self.weights.iter().all(|w| w.len() == target_count)
+    }
+}
+
+// Malla con morph targets y las animaciones de sus pesos
+pub struct MorphModel {
+    pub vertices: Vec<MorphVertex>,
+    pub indices: Vec<u32>,
+    pub targets: Vec<MorphTarget>,
+    pub default_weights: Vec<f32>,
+    pub tracks: Vec<WeightTrack>,
+}
+
+impl MorphModel {
+    // Carga la primera malla con morph targets del archivo. Todas sus primitivas
+    // deben tener la misma cantidad de targets, como exige glTF.
+    pub fn load_gltf(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let gltf = gltf::Gltf::open(path)?;
+        let document = &gltf.document;
+        let buffers = gltf::import_buffers(document, path.parent(), gltf.blob.clone())?;
+        let buffer_data = |buffer: gltf::Buffer| Some(buffers[buffer.index()].0.as_slice());
+
+        let mesh = document
+            .meshes()
+            .find(|mesh| mesh.primitives().any(|p| p.morph_targets().len() > 0))
+            .ok_or("El archivo no tiene ninguna malla con morph targets")?;
+        let target_count = mesh.primitives().map(|p| p.morph_targets().len()).max().unwrap_or(0);
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut targets: Vec<MorphTarget> = (0..target_count)
+            .map(|i| MorphTarget {
+                name: format!("target {}", i),
+                positions: Vec::new(),
+                normals: Vec::new(),
+            })
+            .collect();
+
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(buffer_data);
+            let positions: Vec<[f32; 3]> = reader.read_positions().ok_or("Primitiva sin posiciones")?.collect();
+            let count = positions.len();
+            let mut normals = reader.read_normals();
+            let mut tex_coords = reader.read_tex_coords(0).map(|t| t.into_f32());
+
+            let base = vertices.len() as u32;
+            match reader.read_indices() {
+                Some(read) => indices.extend(read.into_u32().map(|i| base + i)),
+                None => indices.extend(base..base + count as u32),
+            }
+            vertices.extend(positions.into_iter().map(|position| MorphVertex {
+                position,
+                tex_coords: tex_coords.as_mut().and_then(|t| t.next()).unwrap_or([0.0, 0.0]),
+                normal: normals.as_mut().and_then(|n| n.next()).unwrap_or([0.0; 3]),
+            }));
+
+            let mut read_targets = reader.read_morph_targets();
+            for target in &mut targets {
+                let (positions, normals, _) = read_targets.next().unwrap_or((None, None, None));
+                let mut positions = positions.into_iter().flatten();
+                let mut normals = normals.into_iter().flatten();
+                for _ in 0..count {
+                    target.positions.push(positions.next().unwrap_or([0.0; 3]));
+                    target.normals.push(normals.next().unwrap_or([0.0; 3]));
+                }
+            }
+        }
+
+        if targets.len() > MAX_MORPH_TARGETS {
+            log::warn!(
+                "La malla tiene {} morph targets, se usan los primeros {}",
+                targets.len(),
+                MAX_MORPH_TARGETS
+            );
+            targets.truncate(MAX_MORPH_TARGETS);
+        }
+
+        let mut default_weights = mesh.weights().map(<[f32]>::to_vec).unwrap_or_default();
+        default_weights.resize(target_count, 0.0);
+
+        // Canales de pesos de los nodos que usan esta malla
+        let mut tracks = Vec::new();
+        for animation in document.animations() {
+            for channel in animation.channels() {
+                if channel.target().node().mesh().map(|m| m.index()) != Some(mesh.index()) {
+                    continue;
+                }
+                let reader = channel.reader(buffer_data);
+                let (Some(inputs), Some(gltf::animation::util::ReadOutputs::MorphTargetWeights(weights))) =
+                    (reader.read_inputs(), reader.read_outputs())
+                else {
+                    continue;
+                };
+                let weights: Vec<f32> = weights.into_f32().collect();
+                let interpolation = match channel.sampler().interpolation() {
+                    gltf::animation::Interpolation::Linear => Interpolation::Linear,
+                    gltf::animation::Interpolation::Step => Interpolation::Step,
+                    gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+                };
+                tracks.push(WeightTrack {
+                    name: animation
+                        .name()
+                        .map(String::from)
+                        .unwrap_or_else(|| format!("animación {}", animation.index())),
+                    interpolation,
+                    times: inputs.collect(),
+                    weights: weights.chunks(target_count).map(|w| w.to_vec()).collect(),
+                });
+            }
+        }
+
+        let mut model = Self {
+            vertices,
+            indices,
+            targets,
+            default_weights,
+            tracks,
+        };
+        model.validate();
+        Ok(model)
+    }
+
+    // Descarta las pistas mal formadas para que sample() no falle
+    pub fn validate(&mut self) {
+        let target_count = self.default_weights.len();
+        self.tracks.retain(|track| {
+            let valid = track.is_valid(target_count);
+            if !valid {
+                log::warn!("Pista de pesos \"{}\" inválida, se ignora", track.name);
+            }
+            valid
+        });
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct MorphUniform {
+    weights: [[f32; 4]; MAX_MORPH_TARGETS / 4],
+    target_count: u32,
+    vertex_count: u32,
+    _padding: [u32; 2],
+}
+
+// Buffers en GPU de un MorphModel. Los deltas van en storage buffers ordenados por
+// target (target * vertex_count + vértice) y los pesos en un uniform. Usa el grupo 2
+// desde el binding 1; el 0 es el de las articulaciones del skinning.
+pub struct MorphMesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub num_indices: u32,
+    weight_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    target_count: u32,
+    vertex_count: u32,
+}
+
+impl MorphMesh {
+    pub fn is_supported(adapter: &wgpu::Adapter) -> bool {
+        adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::VERTEX_STORAGE)
+            && adapter.limits().max_storage_buffers_per_shader_stage >= 2
+    }
+
+    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        let storage = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("morph_bind_group_layout"),
+            entries: &[
+                storage(1),
+                storage(2),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, model: &MorphModel) -> Self {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Morph Vertex Buffer"),
+            contents: bytemuck::cast_slice(&model.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Morph Index Buffer"),
+            contents: bytemuck::cast_slice(&model.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        // vec4 por delta para respetar el stride de 16 bytes de los arrays en WGSL
+        let vertex_count = model.vertices.len();
+        let mut positions = Vec::with_capacity(model.targets.len() * vertex_count);
+        let mut normals = Vec::with_capacity(model.targets.len() * vertex_count);
+        for target in &model.targets {
+            for v in 0..vertex_count {
+                let [x, y, z] = target.positions.get(v).copied().unwrap_or([0.0; 3]);
+                positions.push([x, y, z, 0.0]);
+                let [x, y, z] = target.normals.get(v).copied().unwrap_or([0.0; 3]);
+                normals.push([x, y, z, 0.0]);
+            }
+        }
+        // Los storage buffers no pueden estar vacíos
+        if positions.is_empty() {
+            positions.push([0.0; 4]);
+            normals.push([0.0; 4]);
+        }
+
+        let position_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Morph Position Delta Buffer"),
+            contents: bytemuck::cast_slice(&positions),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let normal_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Morph Normal Delta Buffer"),
+            contents: bytemuck::cast_slice(&normals),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let weight_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Morph Weight Buffer"),
+            size: std::mem::size_of::<MorphUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("morph_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: position_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: normal_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: weight_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            num_indices: model.indices.len() as u32,
+            weight_buffer,
+            bind_group,
+            target_count: model.targets.len() as u32,
+            vertex_count: vertex_count as u32,
+        }
+    }
+
+    pub fn set_weights(&self, queue: &wgpu::Queue, weights: &[f32]) {
+        let mut uniform = MorphUniform {
+            weights: [[0.0; 4]; MAX_MORPH_TARGETS / 4],
+            target_count: self.target_count,
+            vertex_count: self.vertex_count,
+            _padding: [0; 2],
+        };
+        for (i, weight) in weights.iter().take(MAX_MORPH_TARGETS).enumerate() {
+            uniform.weights[i / 4][i % 4] = *weight;
+        }
+        queue.write_buffer(&self.weight_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+}
@@ -0,0 +1,476 @@
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+use wgpu::util::DeviceExt;
+
+use crate::bounds::Aabb;
+use crate::primitives::MeshData;
+
+// Rayo `origin + t * direction`. La dirección no se normaliza: así `t` se conserva al
+// pasar el rayo al espacio de cada objeto y las distancias se pueden comparar.
+#[derive(Copy, Clone, Debug)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    // Rayo que pasa por el centro del píxel `cursor` de una ventana de `size`.
+    // Empieza en el plano cercano (z = 0 en NDC) y llega al lejano en t = 1.
+    pub fn from_cursor(cursor: (f32, f32), size: (u32, u32), view_proj: &Matrix4<f32>) -> Option<Self> {
+        let inverse = view_proj.invert()?;
+        let x = 2.0 * cursor.0 / size.0 as f32 - 1.0;
+        let y = 1.0 - 2.0 * cursor.1 / size.1 as f32;
+        let unproject = |z: f32| {
+            let p = inverse * Vector4::new(x, y, z, 1.0);
+            Point3::from_homogeneous(p)
+        };
+        let origin = unproject(0.0);
+        Some(Self { origin, direction: unproject(1.0) - origin })
+    }
+
+    pub fn at(&self, t: f32) -> Point3<f32> {
+        self.origin + self.direction * t
+    }
+
+    pub fn transformed(&self, matrix: &Matrix4<f32>) -> Self {
+        Self {
+            origin: Point3::from_homogeneous(matrix * self.origin.to_homogeneous()),
+            direction: (matrix * self.direction.extend(0.0)).truncate(),
+        }
+    }
+
+    // Entrada a la caja por el método de las losas; None si no la toca con t >= 0
+    pub fn intersect_aabb(&self, aabb: &Aabb, max_t: f32) -> Option<f32> {
+        let (mut near, mut far) = (0.0f32, max_t);
+        for axis in 0..3 {
+            let inverse = 1.0 / self.direction[axis];
+            let mut t0 = (aabb.min[axis] - self.origin[axis]) * inverse;
+            let mut t1 = (aabb.max[axis] - self.origin[axis]) * inverse;
+            if inverse < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // max/min ignoran los NaN de 0 * infinito
+            near = near.max(t0);
+            far = far.min(t1);
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+
+    // Möller-Trumbore sin descartar caras traseras: (t, u, v) con el punto en
+    // a + u (b - a) + v (c - a)
+    pub fn intersect_triangle(&self, a: Point3<f32>, b: Point3<f32>, c: Point3<f32>) -> Option<(f32, f32, f32)> {
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = self.direction.cross(edge2);
+        let det = edge1.dot(p);
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let inverse = 1.0 / det;
+        let s = self.origin - a;
+        let u = s.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(edge1);
+        let v = self.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = edge2.dot(q) * inverse;
+        (t >= 0.0).then_some((t, u, v))
+    }
+}
+
+// Impacto sobre una malla, en su espacio de objeto
+#[derive(Copy, Clone, Debug)]
+pub struct MeshHit {
+    pub triangle: usize,
+    pub t: f32,
+    pub barycentric: [f32; 3],
+    pub uv: [f32; 2],
+    pub position: Point3<f32>,
+}
+
+impl MeshHit {
+    // Cara como la numera la vista "ID de cara": 4 vértices por cara
+    pub fn face(&self, indices: &[u32]) -> u32 {
+        let first = &indices[self.triangle * 3..self.triangle * 3 + 3];
+        first.iter().min().unwrap() / 4
+    }
+}
+
+// Nodo del BVH. Las hojas guardan un rango de `order`; los nodos internos tienen
+// el hijo izquierdo a continuación y el derecho en `right`.
+#[derive(Copy, Clone, Debug)]
+struct BvhNode {
+    aabb: Aabb,
+    right: u32,
+    first: u32,
+    count: u32,
+}
+
+const BVH_LEAF_SIZE: usize = 4;
+
+// Geometría en CPU para el ray casting, con un BVH de triángulos
+pub struct PickMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub tex_coords: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+    nodes: Vec<BvhNode>,
+    // Triángulos ordenados de forma que cada hoja es un rango contiguo
+    order: Vec<u32>,
+}
+
+impl PickMesh {
+    pub fn new(positions: Vec<[f32; 3]>, tex_coords: Vec<[f32; 2]>, indices: Vec<u32>) -> Self {
+        let mut mesh = Self {
+            positions,
+            tex_coords,
+            indices,
+            nodes: Vec::new(),
+            order: Vec::new(),
+        };
+        let triangles = mesh.indices.len() / 3;
+        mesh.order = (0..triangles as u32).collect();
+        if triangles > 0 {
+            let centroids: Vec<Point3<f32>> = (0..triangles)
+                .map(|i| {
+                    let [a, b, c] = mesh.triangle(i);
+                    Point3::centroid(&[a, b, c])
+                })
+                .collect();
+            mesh.build(0, triangles, &centroids);
+        }
+        mesh
+    }
+
+    pub fn from_data(data: &MeshData) -> Self {
+        Self::new(
+            data.positions().collect(),
+            data.vertices.iter().map(|v| v.tex_coords).collect(),
+            data.indices.clone(),
+        )
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn triangle(&self, i: usize) -> [Point3<f32>; 3] {
+        std::array::from_fn(|k| Point3::from(self.positions[self.indices[i * 3 + k] as usize]))
+    }
+
+    // Divide por la mediana del eje más largo de los centroides
+    fn build(&mut self, first: usize, count: usize, centroids: &[Point3<f32>]) -> usize {
+        let range = first..first + count;
+        let aabb = Aabb::from_points(self.order[range.clone()].iter().flat_map(|&t| self.triangle(t as usize).map(Into::into)));
+        let index = self.nodes.len();
+        self.nodes.push(BvhNode { aabb, right: 0, first: first as u32, count: count as u32 });
+        if count <= BVH_LEAF_SIZE {
+            return index;
+        }
+
+        let bounds = Aabb::from_points(self.order[range.clone()].iter().map(|&t| centroids[t as usize].into()));
+        let extent = bounds.max - bounds.min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let half = count / 2;
+        self.order[range].select_nth_unstable_by(half, |&a, &b| {
+            centroids[a as usize][axis].total_cmp(&centroids[b as usize][axis])
+        });
+
+        self.build(first, half, centroids);
+        let right = self.build(first + half, count - half, centroids);
+        self.nodes[index].count = 0;
+        self.nodes[index].right = right as u32;
+        index
+    }
+
+    // Impacto más cercano con t >= 0
+    pub fn intersect(&self, ray: &Ray) -> Option<MeshHit> {
+        let mut best: Option<(usize, f32, f32, f32)> = None;
+        let mut stack = Vec::with_capacity(32);
+        if !self.nodes.is_empty() {
+            stack.push(0usize);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let max_t = best.map_or(f32::INFINITY, |(_, t, _, _)| t);
+            if ray.intersect_aabb(&node.aabb, max_t).is_none() {
+                continue;
+            }
+            if node.count > 0 {
+                for &triangle in &self.order[node.first as usize..(node.first + node.count) as usize] {
+                    let [a, b, c] = self.triangle(triangle as usize);
+                    if let Some((t, u, v)) = ray.intersect_triangle(a, b, c) {
+                        if best.is_none_or(|(_, best_t, _, _)| t < best_t) {
+                            best = Some((triangle as usize, t, u, v));
+                        }
+                    }
+                }
+            } else {
+                stack.push(node.right as usize);
+                stack.push(index + 1);
+            }
+        }
+
+        best.map(|(triangle, t, u, v)| {
+            let barycentric = [1.0 - u - v, u, v];
+            let corners: [usize; 3] = std::array::from_fn(|k| self.indices[triangle * 3 + k] as usize);
+            let uv = if self.tex_coords.is_empty() {
+                [0.0, 0.0]
+            } else {
+                std::array::from_fn(|c| corners.iter().zip(barycentric).map(|(&i, w)| self.tex_coords[i][c] * w).sum())
+            };
+            MeshHit { triangle, t, barycentric, uv, position: ray.at(t) }
+        })
+    }
+}
+
+// Lo que hay bajo un píxel del ID buffer
+#[derive(Copy, Clone, Debug)]
+pub struct IdHit {
+    pub object: u32,
+    pub uv: [f32; 2],
+    // Profundidad en NDC
+    pub depth: f32,
+}
+
+// Un grupo de instancias de la misma malla para el pase de IDs. Las instancias usan
+// el formato del visor (matriz en las locations 2-5) con el ID del objeto en la
+// location 10.
+pub struct IdDraw<'a> {
+    pub vertex_buffer: &'a wgpu::Buffer,
+    pub index_buffer: &'a wgpu::Buffer,
+    pub index_format: wgpu::IndexFormat,
+    pub num_indices: u32,
+    pub instances: std::ops::Range<u32>,
+}
+
+// Pase que dibuja el ID de objeto y la UV de cada píxel en una textura Rgba32Uint,
+// para un picking exacto al píxel. Solo rasteriza el píxel pedido (scissor) y lo
+// lee de vuelta esperando a la GPU.
+pub struct IdBuffer {
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    target: Option<(wgpu::Texture, wgpu::Texture)>,
+    readback: wgpu::Buffer,
+}
+
+const ID_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Uint;
+const ID_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+impl IdBuffer {
+    pub fn new(
+        device: &wgpu::Device,
+        vertex_layout: wgpu::VertexBufferLayout<'_>,
+        instance_layout: wgpu::VertexBufferLayout<'_>,
+    ) -> Self {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("ID Buffer Uniforms"),
+            contents: bytemuck::cast_slice(&[[[0.0f32; 4]; 4]]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ID Buffer Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ID Buffer Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("ID Buffer Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("picking.wgsl").into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ID Buffer Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("ID Buffer Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                buffers: &[vertex_layout, instance_layout],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(ID_FORMAT.into())],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Ccw,
+                // Igual que el pipeline de la escena, que dibuja ambas caras
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: ID_DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ID Buffer Readback"),
+            size: 16,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self { pipeline, uniform_buffer, bind_group, target: None, readback }
+    }
+
+    // Dibuja el píxel `pixel` de una ventana de `size` y devuelve lo que se ve en él
+    #[allow(clippy::too_many_arguments)]
+    pub fn pick(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        view_proj: &Matrix4<f32>,
+        instance_buffer: &wgpu::Buffer,
+        draws: &[IdDraw],
+        pixel: (u32, u32),
+        size: (u32, u32),
+    ) -> Result<Option<IdHit>, Box<dyn std::error::Error>> {
+        if pixel.0 >= size.0 || pixel.1 >= size.1 {
+            return Ok(None);
+        }
+        self.resize(device, size);
+        let (color, depth) = self.target.as_ref().unwrap();
+        let color_view = color.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+        let matrix: [[f32; 4]; 4] = (*view_proj).into();
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[matrix]));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("ID Buffer Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("ID Buffer Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_scissor_rect(pixel.0, pixel.1, 1, 1);
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            for draw in draws.iter().filter(|draw| !draw.instances.is_empty()) {
+                pass.set_vertex_buffer(0, draw.vertex_buffer.slice(..));
+                pass.set_index_buffer(draw.index_buffer.slice(..), draw.index_format);
+                pass.draw_indexed(0..draw.num_indices, 0, draw.instances.clone());
+            }
+        }
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: color,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: pixel.0, y: pixel.1, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: None,
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = self.readback.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+        let texel: [u32; 4] = {
+            let data = slice.get_mapped_range();
+            bytemuck::pod_read_unaligned(&data[..16])
+        };
+        self.readback.unmap();
+
+        // El 0 es el fondo; los objetos se escriben con su índice + 1
+        Ok((texel[0] > 0).then(|| IdHit {
+            object: texel[0] - 1,
+            uv: [f32::from_bits(texel[1]), f32::from_bits(texel[2])],
+            depth: f32::from_bits(texel[3]),
+        }))
+    }
+
+    fn resize(&mut self, device: &wgpu::Device, size: (u32, u32)) {
+        let stale = self.target.as_ref().is_none_or(|(color, _)| (color.width(), color.height()) != size);
+        if stale {
+            let texture = |label, format, usage| {
+                device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage,
+                    view_formats: &[],
+                })
+            };
+            let attachment = wgpu::TextureUsages::RENDER_ATTACHMENT;
+            self.target = Some((
+                texture("ID Buffer", ID_FORMAT, attachment | wgpu::TextureUsages::COPY_SRC),
+                texture("ID Buffer Depth", ID_DEPTH_FORMAT, attachment),
+            ));
+        }
+    }
+}
@@ -0,0 +1,43 @@
+// Pase de IDs para el picking: cada píxel guarda el objeto, la UV y la profundidad
+@group(0) @binding(0)
+var<uniform> view_proj: mat4x4<f32>;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+struct InstanceInput {
+    @location(2) model_0: vec4<f32>,
+    @location(3) model_1: vec4<f32>,
+    @location(4) model_2: vec4<f32>,
+    @location(5) model_3: vec4<f32>,
+    // x = índice del objeto
+    @location(10) id: vec2<u32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) @interpolate(flat) object: u32,
+}
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let matrix = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    var out: VertexOutput;
+    out.clip_position = view_proj * matrix * vec4<f32>(model.position, 1.0);
+    out.tex_coords = model.tex_coords;
+    out.object = instance.id.x;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<u32> {
+    return vec4<u32>(
+        in.object + 1u,
+        bitcast<u32>(in.tex_coords.x),
+        bitcast<u32>(in.tex_coords.y),
+        bitcast<u32>(in.clip_position.z),
+    );
+}
@@ -0,0 +1,566 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+// Generadores de mallas indexadas. Todas usan triángulos CCW vistos desde afuera
+// (FrontFace::Ccw) y normales unitarias hacia afuera.
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
+    pub normal: [f32; 3],
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MeshData {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = [f32; 3]> + Clone + '_ {
+        self.vertices.iter().map(|v| v.position)
+    }
+}
+
+// Rectángulo de UV dentro de una textura (0..1)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct UvRect {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl UvRect {
+    pub const FULL: UvRect = UvRect {
+        min: [0.0, 0.0],
+        max: [1.0, 1.0],
+    };
+
+    fn map(&self, u: f32, v: f32) -> [f32; 2] {
+        [
+            self.min[0] + u * (self.max[0] - self.min[0]),
+            self.min[1] + v * (self.max[1] - self.min[1]),
+        ]
+    }
+}
+
+// Caras del cubo en el mismo orden que el cubo original y el atlas generado
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CubeFace {
+    Front = 0,
+    Back = 1,
+    Top = 2,
+    Bottom = 3,
+    Right = 4,
+    Left = 5,
+}
+
+// Región de la textura que usa cada cara del cubo, indexada por CubeFace
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CubeUvLayout {
+    pub faces: [UvRect; 6],
+}
+
+impl CubeUvLayout {
+    // Cada cara usa la textura completa
+    pub fn full() -> Self {
+        Self {
+            faces: [UvRect::FULL; 6],
+        }
+    }
+
+    // Atlas en rejilla de columns x rows celdas: la cara i usa la celda i, de
+    // izquierda a derecha y de arriba abajo (el atlas generado es de 4 x 2)
+    pub fn grid(columns: u32, rows: u32) -> Self {
+        let (w, h) = (1.0 / columns as f32, 1.0 / rows as f32);
+        Self {
+            faces: std::array::from_fn(|i| {
+                let (x, y) = ((i as u32 % columns) as f32, (i as u32 / columns) as f32);
+                UvRect {
+                    min: [x * w, y * h],
+                    max: [(x + 1.0) * w, (y + 1.0) * h],
+                }
+            }),
+        }
+    }
+}
+
+impl Default for CubeUvLayout {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+// Cubo de lado `size` centrado en el origen, 4 vértices por cara
+pub fn cube(size: f32, layout: &CubeUvLayout) -> MeshData {
+    let h = size * 0.5;
+    // (normal, eje u, eje v) de cada cara, con u x v = normal
+    let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+        ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+        ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+        ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+    ];
+
+    let mut mesh = MeshData::default();
+    for (face, (normal, u, v)) in faces.iter().enumerate() {
+        let base = mesh.vertices.len() as u32;
+        // Esquinas en orden CCW; la v de textura crece hacia abajo
+        for (su, sv, tu, tv) in [(-1.0, -1.0, 0.0, 1.0), (1.0, -1.0, 1.0, 1.0), (1.0, 1.0, 1.0, 0.0), (-1.0, 1.0, 0.0, 0.0)] {
+            mesh.vertices.push(MeshVertex {
+                position: std::array::from_fn(|i| (normal[i] + su * u[i] + sv * v[i]) * h),
+                tex_coords: layout.faces[face].map(tu, tv),
+                normal: *normal,
+            });
+        }
+        mesh.indices.extend([base, base + 1, base + 2, base + 2, base + 3, base]);
+    }
+    mesh
+}
+
+// Punto del perfil que se gira alrededor del eje Y
+#[derive(Copy, Clone)]
+struct ProfilePoint {
+    radius: f32,
+    y: f32,
+    // Normal en el plano (radial, y)
+    normal: [f32; 2],
+    v: f32,
+}
+
+// Gira un perfil (de arriba abajo, o del centro hacia afuera en las tapas superiores)
+// alrededor de Y. Cada columna tiene sus vértices propios, con la costura duplicada.
+// Donde el radio es 0 se genera un solo triángulo por cuadrilátero.
+fn revolve(mesh: &mut MeshData, profile: &[ProfilePoint], segments: u32, planar_uv: bool) {
+    let base = mesh.vertices.len() as u32;
+    let columns = segments + 1;
+    let max_radius = profile.iter().map(|p| p.radius).fold(0.0, f32::max);
+
+    for point in profile {
+        for s in 0..columns {
+            let phi = 2.0 * PI * s as f32 / segments as f32;
+            let (sin, cos) = phi.sin_cos();
+            let tex_coords = if planar_uv {
+                let scale = 0.5 / max_radius.max(f32::EPSILON);
+                [0.5 + point.radius * cos * scale, 0.5 + point.radius * sin * scale]
+            } else {
+                [s as f32 / segments as f32, point.v]
+            };
+            mesh.vertices.push(MeshVertex {
+                position: [point.radius * cos, point.y, point.radius * sin],
+                tex_coords,
+                normal: [point.normal[0] * cos, point.normal[1], point.normal[0] * sin],
+            });
+        }
+    }
+
+    for row in 0..profile.len() as u32 - 1 {
+        for s in 0..segments {
+            let a = base + row * columns + s;
+            let b = a + columns;
+            let c = b + 1;
+            let d = a + 1;
+            if profile[row as usize].radius > 0.0 {
+                mesh.indices.extend([a, d, c]);
+            }
+            if profile[row as usize + 1].radius > 0.0 {
+                mesh.indices.extend([a, c, b]);
+            }
+        }
+    }
+}
+
+// Puntos de un arco de circunferencia de `radius` centrado en (0, center_y), entre
+// los ángulos polares `from` y `to` (0 = polo superior)
+fn arc(radius: f32, center_y: f32, from: f32, to: f32, steps: u32, v: (f32, f32)) -> Vec<ProfilePoint> {
+    (0..=steps)
+        .map(|i| {
+            let t = i as f32 / steps as f32;
+            let theta = from + (to - from) * t;
+            let (sin, cos) = theta.sin_cos();
+            ProfilePoint {
+                // En los polos sin(theta) no es exactamente 0
+                radius: if sin.abs() < 1e-6 { 0.0 } else { radius * sin },
+                y: center_y + radius * cos,
+                normal: [sin, cos],
+                v: v.0 + (v.1 - v.0) * t,
+            }
+        })
+        .collect()
+}
+
+// Esfera UV con `segments` divisiones en longitud y `rings` en latitud
+pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> MeshData {
+    assert!(segments >= 3 && rings >= 2);
+    let mut mesh = MeshData::default();
+    revolve(&mut mesh, &arc(radius, 0.0, 0.0, PI, rings, (0.0, 1.0)), segments, false);
+    mesh
+}
+
+// Icosaedro subdividido `subdivisions` veces, con vértices compartidos. Las UV son
+// esféricas, así que los triángulos que cruzan la costura interpolan mal la u.
+pub fn icosphere(radius: f32, subdivisions: u32) -> MeshData {
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut positions: Vec<[f32; 3]> = [
+        [-1.0, t, 0.0],
+        [1.0, t, 0.0],
+        [-1.0, -t, 0.0],
+        [1.0, -t, 0.0],
+        [0.0, -1.0, t],
+        [0.0, 1.0, t],
+        [0.0, -1.0, -t],
+        [0.0, 1.0, -t],
+        [t, 0.0, -1.0],
+        [t, 0.0, 1.0],
+        [-t, 0.0, -1.0],
+        [-t, 0.0, 1.0],
+    ]
+    .into_iter()
+    .map(normalize)
+    .collect();
+    let mut triangles: Vec<[u32; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Punto medio de cada arista, compartido entre los dos triángulos vecinos
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, positions: &mut Vec<[f32; 3]>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let (pa, pb) = (positions[a as usize], positions[b as usize]);
+                positions.push(normalize(std::array::from_fn(|i| (pa[i] + pb[i]) * 0.5)));
+                positions.len() as u32 - 1
+            })
+        };
+        triangles = triangles
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    MeshData {
+        vertices: positions
+            .into_iter()
+            .map(|n| MeshVertex {
+                position: n.map(|c| c * radius),
+                tex_coords: [0.5 + n[2].atan2(n[0]) / (2.0 * PI), n[1].clamp(-1.0, 1.0).acos() / PI],
+                normal: n,
+            })
+            .collect(),
+        indices: triangles.into_iter().flatten().collect(),
+    }
+}
+
+// Cilindro con tapas, centrado en el origen y con el eje en Y
+pub fn cylinder(radius: f32, height: f32, segments: u32) -> MeshData {
+    assert!(segments >= 3);
+    let h = height * 0.5;
+    let point = |radius, y, normal, v| ProfilePoint { radius, y, normal, v };
+    let mut mesh = MeshData::default();
+    revolve(&mut mesh, &[point(0.0, h, [0.0, 1.0], 0.0), point(radius, h, [0.0, 1.0], 1.0)], segments, true);
+    revolve(&mut mesh, &[point(radius, h, [1.0, 0.0], 0.0), point(radius, -h, [1.0, 0.0], 1.0)], segments, false);
+    revolve(&mut mesh, &[point(radius, -h, [0.0, -1.0], 0.0), point(0.0, -h, [0.0, -1.0], 1.0)], segments, true);
+    mesh
+}
+
+// Cono con base, centrado en el origen, con la punta hacia +Y
+pub fn cone(radius: f32, height: f32, segments: u32) -> MeshData {
+    assert!(segments >= 3);
+    let h = height * 0.5;
+    // Normal de la superficie lateral: perpendicular a la generatriz
+    let slant = (radius * radius + height * height).sqrt();
+    let side_normal = [height / slant, radius / slant];
+    let point = |radius, y, normal, v| ProfilePoint { radius, y, normal, v };
+    let mut mesh = MeshData::default();
+    revolve(&mut mesh, &[point(0.0, h, side_normal, 0.0), point(radius, -h, side_normal, 1.0)], segments, false);
+    revolve(&mut mesh, &[point(radius, -h, [0.0, -1.0], 0.0), point(0.0, -h, [0.0, -1.0], 1.0)], segments, true);
+    mesh
+}
+
+// Toro alrededor del eje Y: `major_radius` hasta el centro del tubo, `minor_radius` del tubo
+pub fn torus(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> MeshData {
+    assert!(major_segments >= 3 && minor_segments >= 3);
+    // Perfil: circunferencia del tubo recorrida desde arriba por el lado de afuera
+    let profile: Vec<ProfilePoint> = (0..=minor_segments)
+        .map(|i| {
+            let theta = 2.0 * PI * i as f32 / minor_segments as f32;
+            let (sin, cos) = theta.sin_cos();
+            ProfilePoint {
+                radius: major_radius + minor_radius * sin,
+                y: minor_radius * cos,
+                normal: [sin, cos],
+                v: i as f32 / minor_segments as f32,
+            }
+        })
+        .collect();
+    let mut mesh = MeshData::default();
+    revolve(&mut mesh, &profile, major_segments, false);
+    mesh
+}
+
+// Plano en XZ centrado en el origen, mirando hacia +Y, con subdivisiones en cada eje
+pub fn plane(width: f32, depth: f32, subdivisions_x: u32, subdivisions_z: u32) -> MeshData {
+    assert!(subdivisions_x >= 1 && subdivisions_z >= 1);
+    let mut mesh = MeshData::default();
+    for i in 0..=subdivisions_x {
+        for j in 0..=subdivisions_z {
+            let (u, v) = (i as f32 / subdivisions_x as f32, j as f32 / subdivisions_z as f32);
+            mesh.vertices.push(MeshVertex {
+                position: [(u - 0.5) * width, 0.0, (v - 0.5) * depth],
+                tex_coords: [u, v],
+                normal: [0.0, 1.0, 0.0],
+            });
+        }
+    }
+    let columns = subdivisions_z + 1;
+    for i in 0..subdivisions_x {
+        for j in 0..subdivisions_z {
+            let a = i * columns + j;
+            let b = a + 1;
+            let c = b + columns;
+            let d = a + columns;
+            mesh.indices.extend([a, b, c, a, c, d]);
+        }
+    }
+    mesh
+}
+
+// Cápsula con eje en Y: cilindro de altura `height` con semiesferas de `radius`
+// en los extremos (altura total height + 2 * radius)
+pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> MeshData {
+    assert!(segments >= 3 && rings >= 1);
+    let h = height * 0.5;
+    // La v se reparte según la longitud del perfil
+    let total = PI * radius + height;
+    let v_cap = PI * radius * 0.5 / total;
+    let mut profile = arc(radius, h, 0.0, PI * 0.5, rings, (0.0, v_cap));
+    profile.extend(arc(radius, -h, PI * 0.5, PI, rings, (1.0 - v_cap, 1.0)));
+    let mut mesh = MeshData::default();
+    revolve(&mut mesh, &profile, segments, false);
+    mesh
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    v.map(|c| c / length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+        [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+    }
+
+    fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+        [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+    }
+
+    fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+    }
+
+    // Índice de cada vértice después de unir los que están en la misma posición
+    fn welded(mesh: &MeshData) -> Vec<u32> {
+        let mut ids = HashMap::new();
+        mesh.positions()
+            .map(|p| {
+                let key = p.map(|c| (c * 1e4).round() as i64);
+                let next = ids.len() as u32;
+                *ids.entry(key).or_insert(next)
+            })
+            .collect()
+    }
+
+    // Aristas dirigidas de los triángulos no degenerados, con vértices unidos
+    fn directed_edges(mesh: &MeshData) -> HashMap<(u32, u32), u32> {
+        let ids = welded(mesh);
+        let mut edges = HashMap::new();
+        for tri in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| ids[tri[i] as usize]);
+            assert!(a != b && b != c && c != a, "triángulo degenerado");
+            for edge in [(a, b), (b, c), (c, a)] {
+                *edges.entry(edge).or_insert(0) += 1;
+            }
+        }
+        edges
+    }
+
+    fn check_indices(mesh: &MeshData) {
+        assert_eq!(mesh.indices.len() % 3, 0);
+        assert!(mesh.indices.iter().all(|&i| (i as usize) < mesh.vertices.len()));
+    }
+
+    // Cada arista aparece una vez en cada sentido: la malla es cerrada y todos los
+    // triángulos tienen la misma orientación
+    fn check_watertight(mesh: &MeshData) {
+        let edges = directed_edges(mesh);
+        for (&(a, b), &count) in &edges {
+            assert_eq!(count, 1, "arista ({}, {}) repetida en el mismo sentido", a, b);
+            assert_eq!(edges.get(&(b, a)), Some(&1), "arista ({}, {}) sin vecina", a, b);
+        }
+    }
+
+    // Las caras miran hacia el mismo lado que las normales de sus vértices
+    fn check_outward(mesh: &MeshData) {
+        for tri in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[tri[i] as usize]);
+            let face = cross(sub(b.position, a.position), sub(c.position, a.position));
+            let normal = [0, 1, 2].map(|i| a.normal[i] + b.normal[i] + c.normal[i]);
+            assert!(dot(face, normal) > 0.0, "triángulo {:?} con orientación invertida", tri);
+        }
+    }
+
+    fn check_normals(mesh: &MeshData) {
+        for v in &mesh.vertices {
+            assert!((dot(v.normal, v.normal) - 1.0).abs() < 1e-4, "normal no unitaria {:?}", v.normal);
+        }
+    }
+
+    // Volumen con signo por el teorema de la divergencia; positivo si las caras miran afuera
+    fn signed_volume(mesh: &MeshData) -> f32 {
+        mesh.indices
+            .chunks(3)
+            .map(|tri| {
+                let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[tri[i] as usize].position);
+                dot(a, cross(b, c)) / 6.0
+            })
+            .sum()
+    }
+
+    fn check_closed(mesh: &MeshData) {
+        check_indices(mesh);
+        check_normals(mesh);
+        check_watertight(mesh);
+        check_outward(mesh);
+        assert!(signed_volume(mesh) > 0.0);
+    }
+
+    #[test]
+    fn cube_counts_and_volume() {
+        let mesh = cube(2.0, &CubeUvLayout::full());
+        assert_eq!(mesh.vertices.len(), 24);
+        assert_eq!(mesh.triangle_count(), 12);
+        check_closed(&mesh);
+        assert!((signed_volume(&mesh) - 8.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn cube_faces_use_their_atlas_cell() {
+        let layout = CubeUvLayout::grid(4, 2);
+        let mesh = cube(1.0, &layout);
+        for (face, vertices) in mesh.vertices.chunks(4).enumerate() {
+            let rect = layout.faces[face];
+            for v in vertices {
+                for axis in 0..2 {
+                    assert!(v.tex_coords[axis] >= rect.min[axis] - 1e-6);
+                    assert!(v.tex_coords[axis] <= rect.max[axis] + 1e-6);
+                }
+            }
+        }
+        assert_eq!(layout.faces[CubeFace::Left as usize].min, [0.25, 0.5]);
+    }
+
+    #[test]
+    fn uv_sphere_counts() {
+        let (segments, rings) = (16, 8);
+        let mesh = uv_sphere(1.0, segments, rings);
+        assert_eq!(mesh.vertices.len() as u32, (rings + 1) * (segments + 1));
+        assert_eq!(mesh.triangle_count() as u32, 2 * segments * (rings - 1));
+        check_closed(&mesh);
+    }
+
+    #[test]
+    fn icosphere_counts() {
+        for subdivisions in 0..3 {
+            let mesh = icosphere(1.0, subdivisions);
+            assert_eq!(mesh.vertices.len(), 10 * 4usize.pow(subdivisions) + 2);
+            assert_eq!(mesh.triangle_count(), 20 * 4usize.pow(subdivisions));
+            check_closed(&mesh);
+        }
+    }
+
+    #[test]
+    fn cylinder_counts() {
+        let segments = 12;
+        let mesh = cylinder(0.5, 2.0, segments);
+        assert_eq!(mesh.vertices.len() as u32, 6 * (segments + 1));
+        assert_eq!(mesh.triangle_count() as u32, 4 * segments);
+        check_closed(&mesh);
+    }
+
+    #[test]
+    fn cone_counts() {
+        let segments = 12;
+        let mesh = cone(0.5, 1.0, segments);
+        assert_eq!(mesh.vertices.len() as u32, 4 * (segments + 1));
+        assert_eq!(mesh.triangle_count() as u32, 2 * segments);
+        check_closed(&mesh);
+    }
+
+    #[test]
+    fn torus_counts() {
+        let (major, minor) = (24, 12);
+        let mesh = torus(1.0, 0.25, major, minor);
+        assert_eq!(mesh.vertices.len() as u32, (major + 1) * (minor + 1));
+        assert_eq!(mesh.triangle_count() as u32, 2 * major * minor);
+        check_closed(&mesh);
+    }
+
+    #[test]
+    fn capsule_counts() {
+        let (segments, rings) = (16, 4);
+        let mesh = capsule(0.5, 1.0, segments, rings);
+        assert_eq!(mesh.vertices.len() as u32, 2 * (rings + 1) * (segments + 1));
+        assert_eq!(mesh.triangle_count() as u32, 4 * segments * rings);
+        check_closed(&mesh);
+    }
+
+    #[test]
+    fn plane_is_open_with_consistent_winding() {
+        let (sx, sz) = (4, 3);
+        let mesh = plane(2.0, 1.0, sx, sz);
+        assert_eq!(mesh.vertices.len() as u32, (sx + 1) * (sz + 1));
+        assert_eq!(mesh.triangle_count() as u32, 2 * sx * sz);
+        check_indices(&mesh);
+        check_outward(&mesh);
+
+        // Aristas sin vecina solo en el borde, y nunca repetidas en el mismo sentido
+        let edges = directed_edges(&mesh);
+        assert!(edges.values().all(|&count| count == 1));
+        let boundary = edges.keys().filter(|&&(a, b)| !edges.contains_key(&(b, a))).count();
+        assert_eq!(boundary as u32, 2 * (sx + sz));
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AddressMode {
+    #[default]
+    ClampToEdge,
+    Repeat,
+    MirrorRepeat,
+    // Fuera de [0, 1] devuelve `border_color`; requiere ADDRESS_MODE_CLAMP_TO_BORDER
+    ClampToBorder,
+}
+
+impl AddressMode {
+    pub const ALL: [AddressMode; 4] = [
+        AddressMode::ClampToEdge,
+        AddressMode::Repeat,
+        AddressMode::MirrorRepeat,
+        AddressMode::ClampToBorder,
+    ];
+}
+
+impl From<AddressMode> for wgpu::AddressMode {
+    fn from(mode: AddressMode) -> Self {
+        match mode {
+            AddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+            AddressMode::Repeat => wgpu::AddressMode::Repeat,
+            AddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+            AddressMode::ClampToBorder => wgpu::AddressMode::ClampToBorder,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Filter {
+    Nearest,
+    #[default]
+    Linear,
+}
+
+impl From<Filter> for wgpu::FilterMode {
+    fn from(filter: Filter) -> Self {
+        match filter {
+            Filter::Nearest => wgpu::FilterMode::Nearest,
+            Filter::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+impl From<wgpu::FilterMode> for Filter {
+    fn from(filter: wgpu::FilterMode) -> Self {
+        match filter {
+            wgpu::FilterMode::Nearest => Filter::Nearest,
+            wgpu::FilterMode::Linear => Filter::Linear,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CompareFunction {
+    Never,
+    Less,
+    Equal,
+    LessEqual,
+    Greater,
+    NotEqual,
+    GreaterEqual,
+    Always,
+}
+
+impl From<CompareFunction> for wgpu::CompareFunction {
+    fn from(compare: CompareFunction) -> Self {
+        match compare {
+            CompareFunction::Never => wgpu::CompareFunction::Never,
+            CompareFunction::Less => wgpu::CompareFunction::Less,
+            CompareFunction::Equal => wgpu::CompareFunction::Equal,
+            CompareFunction::LessEqual => wgpu::CompareFunction::LessEqual,
+            CompareFunction::Greater => wgpu::CompareFunction::Greater,
+            CompareFunction::NotEqual => wgpu::CompareFunction::NotEqual,
+            CompareFunction::GreaterEqual => wgpu::CompareFunction::GreaterEqual,
+            CompareFunction::Always => wgpu::CompareFunction::Always,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BorderColor {
+    #[default]
+    TransparentBlack,
+    OpaqueBlack,
+    OpaqueWhite,
+}
+
+impl BorderColor {
+    pub const ALL: [BorderColor; 3] = [BorderColor::TransparentBlack, BorderColor::OpaqueBlack, BorderColor::OpaqueWhite];
+}
+
+impl From<BorderColor> for wgpu::SamplerBorderColor {
+    fn from(color: BorderColor) -> Self {
+        match color {
+            BorderColor::TransparentBlack => wgpu::SamplerBorderColor::TransparentBlack,
+            BorderColor::OpaqueBlack => wgpu::SamplerBorderColor::OpaqueBlack,
+            BorderColor::OpaqueWhite => wgpu::SamplerBorderColor::OpaqueWhite,
+        }
+    }
+}
+
+// Todo lo que define un wgpu::Sampler, serializable para las escenas. Los campos que
+// faltan en el archivo toman el valor por defecto, que es el sampler histórico del
+// visor: ClampToEdge, mag Linear, min y mip Nearest.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplerSettings {
+    pub address_mode_u: AddressMode,
+    pub address_mode_v: AddressMode,
+    pub address_mode_w: AddressMode,
+    pub mag_filter: Filter,
+    pub min_filter: Filter,
+    pub mipmap_filter: Filter,
+    pub lod_min_clamp: f32,
+    pub lod_max_clamp: f32,
+    // 1 = sin filtrado anisotrópico; más de 1 exige los tres filtros en Linear
+    pub anisotropy_clamp: u16,
+    // Sampler de comparación, solo para texturas de profundidad (sombras)
+    pub compare: Option<CompareFunction>,
+    // Color fuera de [0, 1] con ClampToBorder
+    pub border_color: BorderColor,
+}
+
+impl Default for SamplerSettings {
+    fn default() -> Self {
+        Self {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Nearest,
+            mipmap_filter: Filter::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+            anisotropy_clamp: 1,
+            compare: None,
+            border_color: BorderColor::TransparentBlack,
+        }
+    }
+}
+
+// Igualdad y hash por bits para poder usar los ajustes como clave de caché
+impl PartialEq for SamplerSettings {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for SamplerSettings {}
+
+impl Hash for SamplerSettings {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+type SettingsKey = (
+    [AddressMode; 3],
+    [Filter; 3],
+    [u32; 2],
+    u16,
+    Option<CompareFunction>,
+    BorderColor,
+);
+
+impl SamplerSettings {
+    fn key(&self) -> SettingsKey {
+        (
+            [self.address_mode_u, self.address_mode_v, self.address_mode_w],
+            [self.mag_filter, self.min_filter, self.mipmap_filter],
+            [self.lod_min_clamp.to_bits(), self.lod_max_clamp.to_bits()],
+            self.anisotropy_clamp,
+            self.compare,
+            self.border_color,
+        )
+    }
+
+    fn address_modes(&self) -> [AddressMode; 3] {
+        [self.address_mode_u, self.address_mode_v, self.address_mode_w]
+    }
+
+    // Corrige lo que wgpu rechazaría con un error de validación, avisando por el log
+    pub fn sanitized(mut self, features: wgpu::Features) -> Self {
+        if !self.lod_min_clamp.is_finite() || self.lod_min_clamp < 0.0 {
+            self.lod_min_clamp = 0.0;
+        }
+        if !self.lod_max_clamp.is_finite() || self.lod_max_clamp < self.lod_min_clamp {
+            self.lod_max_clamp = self.lod_min_clamp.max(32.0);
+        }
+        self.anisotropy_clamp = self.anisotropy_clamp.clamp(1, 16);
+        let all_linear = [self.mag_filter, self.min_filter, self.mipmap_filter]
+            .iter()
+            .all(|filter| *filter == Filter::Linear);
+        if self.anisotropy_clamp > 1 && !all_linear {
+            log::warn!("⚠️  La anisotropía necesita los filtros mag, min y mip en Linear; se desactiva");
+            self.anisotropy_clamp = 1;
+        }
+        if self.address_modes().contains(&AddressMode::ClampToBorder)
+            && !features.contains(wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER)
+        {
+            log::warn!("⚠️  El adaptador no soporta ClampToBorder; se usa ClampToEdge");
+            for mode in [&mut self.address_mode_u, &mut self.address_mode_v, &mut self.address_mode_w] {
+                if *mode == AddressMode::ClampToBorder {
+                    *mode = AddressMode::ClampToEdge;
+                }
+            }
+        }
+        self
+    }
+
+    pub fn descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        let border = self.address_modes().contains(&AddressMode::ClampToBorder);
+        wgpu::SamplerDescriptor {
+            label: None,
+            address_mode_u: self.address_mode_u.into(),
+            address_mode_v: self.address_mode_v.into(),
+            address_mode_w: self.address_mode_w.into(),
+            mag_filter: self.mag_filter.into(),
+            min_filter: self.min_filter.into(),
+            mipmap_filter: self.mipmap_filter.into(),
+            lod_min_clamp: self.lod_min_clamp,
+            lod_max_clamp: self.lod_max_clamp,
+            compare: self.compare.map(Into::into),
+            anisotropy_clamp: self.anisotropy_clamp,
+            border_color: border.then(|| self.border_color.into()),
+        }
+    }
+}
+
+// Un wgpu::Sampler por combinación de ajustes. Los ajustes se corrigen según las
+// features del device antes de buscarlos, así que dos pedidos que terminan igual
+// comparten sampler.
+#[derive(Default)]
+pub struct SamplerCache {
+    samplers: HashMap<SamplerSettings, Arc<wgpu::Sampler>>,
+}
+
+impl SamplerCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&mut self, device: &wgpu::Device, settings: &SamplerSettings) -> Arc<wgpu::Sampler> {
+        let settings = settings.sanitized(device.features());
+        let count = self.samplers.len();
+        self.samplers
+            .entry(settings)
+            .or_insert_with(|| {
+                log::debug!("📦 Sampler nuevo ({} en caché): {:?}", count + 1, settings);
+                Arc::new(device.create_sampler(&settings.descriptor()))
+            })
+            .clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samplers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samplers.is_empty()
+    }
+
+    // Suelta los samplers que ya no usa nadie fuera de la caché
+    pub fn purge(&mut self) {
+        self.samplers.retain(|_, sampler| Arc::strong_count(sampler) > 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn border_settings() -> SamplerSettings {
+        SamplerSettings {
+            address_mode_u: AddressMode::ClampToBorder,
+            address_mode_v: AddressMode::Repeat,
+            address_mode_w: AddressMode::ClampToBorder,
+            border_color: BorderColor::OpaqueWhite,
+            ..SamplerSettings::default()
+        }
+    }
+
+    #[test]
+    fn clamp_to_border_is_kept_with_the_feature() {
+        let settings = border_settings().sanitized(wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER);
+        assert_eq!(settings, border_settings());
+        let descriptor = settings.descriptor();
+        assert_eq!(descriptor.address_mode_u, wgpu::AddressMode::ClampToBorder);
+        assert_eq!(descriptor.border_color, Some(wgpu::SamplerBorderColor::OpaqueWhite));
+    }
+
+    #[test]
+    fn clamp_to_border_falls_back_to_edge_without_the_feature() {
+        let settings = border_settings().sanitized(wgpu::Features::empty());
+        assert_eq!(settings.address_mode_u, AddressMode::ClampToEdge);
+        assert_eq!(settings.address_mode_v, AddressMode::Repeat);
+        assert_eq!(settings.address_mode_w, AddressMode::ClampToEdge);
+        assert_eq!(settings.descriptor().border_color, None);
+    }
+}
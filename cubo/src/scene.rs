@@ -0,0 +1,316 @@
+use std::path::{Path, PathBuf};
+
+use cgmath::{Quaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::animation::Transform;
+use crate::sampler::SamplerSettings;
+
+// Descripción de lo que muestra el visor, en .ron o .json. Las rutas de texturas y
+// mallas son relativas al directorio de trabajo, igual que las opciones del visor.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneFile {
+    #[serde(default)]
+    pub camera: SceneCamera,
+    #[serde(default = "default_background")]
+    pub background: [f32; 3],
+    // Velocidad con la que gira toda la escena (0 = quieta)
+    #[serde(default = "default_rotation_speed")]
+    pub rotation_speed: f32,
+    #[serde(default = "default_lights")]
+    pub lights: Vec<Light>,
+    // Sampler de la textura del visor y de los materiales sin sampler propio
+    #[serde(default)]
+    pub sampler: SamplerSettings,
+    #[serde(default)]
+    pub materials: Vec<SceneMaterial>,
+    pub objects: Vec<SceneObject>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneCamera {
+    pub eye: [f32; 3],
+    pub target: [f32; 3],
+    // Campo de visión vertical en grados
+    pub fovy: f32,
+    pub znear: f32,
+    pub zfar: f32,
+    // Proyección ortográfica en lugar de perspectiva
+    #[serde(default)]
+    pub orthographic: bool,
+}
+
+impl Default for SceneCamera {
+    fn default() -> Self {
+        Self {
+            eye: [0.0, 0.0, 3.0],
+            target: [0.0, 0.0, 0.0],
+            fovy: 45.0,
+            znear: 0.1,
+            zfar: 100.0,
+            orthographic: false,
+        }
+    }
+}
+
+// El visor usa la primera luz de cada tipo
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Light {
+    Ambient {
+        intensity: f32,
+    },
+    // `direction` es hacia donde viaja la luz
+    Directional {
+        direction: [f32; 3],
+        color: [f32; 3],
+        intensity: f32,
+    },
+}
+
+// Material con nombre, referenciado desde los objetos
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneMaterial {
+    pub name: String,
+    pub texture: PathBuf,
+    // Sampler propio; sin él se usa el `sampler` de la escena
+    #[serde(default)]
+    pub sampler: Option<SamplerSettings>,
+    // false para texturas con datos lineales
+    #[serde(default = "default_srgb")]
+    pub srgb: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MeshSource {
+    // Nombre de una primitiva del visor (cube, sphere, torus, ...)
+    Primitive(String),
+    // .cubomesh, .gltf o .glb
+    File(PathBuf),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneObject {
+    pub mesh: MeshSource,
+    #[serde(default)]
+    pub transform: SceneTransform,
+    // Nombre del material; sin material se usa la textura del visor
+    #[serde(default)]
+    pub material: Option<String>,
+}
+
+// Transformación con la rotación como cuaternión [x, y, z, w], igual que en las animaciones
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneTransform {
+    #[serde(default)]
+    pub translation: [f32; 3],
+    #[serde(default = "default_rotation")]
+    pub rotation: [f32; 4],
+    #[serde(default = "default_scale")]
+    pub scale: [f32; 3],
+}
+
+impl Default for SceneTransform {
+    fn default() -> Self {
+        Transform::default().into()
+    }
+}
+
+impl From<Transform> for SceneTransform {
+    fn from(transform: Transform) -> Self {
+        let q = transform.rotation;
+        Self {
+            translation: transform.translation.into(),
+            rotation: [q.v.x, q.v.y, q.v.z, q.s],
+            scale: transform.scale.into(),
+        }
+    }
+}
+
+impl From<SceneTransform> for Transform {
+    fn from(transform: SceneTransform) -> Self {
+        let [x, y, z, w] = transform.rotation;
+        Self {
+            translation: Vector3::from(transform.translation),
+            rotation: Quaternion::new(w, x, y, z),
+            scale: Vector3::from(transform.scale),
+        }
+    }
+}
+
+fn default_background() -> [f32; 3] {
+    [0.1, 0.2, 0.3]
+}
+
+fn default_rotation_speed() -> f32 {
+    1.0
+}
+
+fn default_lights() -> Vec<Light> {
+    vec![
+        Light::Ambient { intensity: 0.35 },
+        Light::Directional {
+            direction: [-0.5, -1.0, -0.3],
+            color: [1.0, 1.0, 1.0],
+            intensity: 0.8,
+        },
+    ]
+}
+
+fn default_srgb() -> bool {
+    true
+}
+
+fn default_rotation() -> [f32; 4] {
+    [0.0, 0.0, 0.0, 1.0]
+}
+
+fn default_scale() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+impl SceneFile {
+    // Carga un archivo .ron o .json
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path)?;
+        match extension(path).as_deref() {
+            Some("ron") => Ok(ron::from_str(&text)?),
+            Some("json") => Ok(serde_json::from_str(&text)?),
+            _ => Err(format!("Formato de escena no soportado: {}", path.display()).into()),
+        }
+    }
+
+    // Guarda en RON o JSON según la extensión
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let text = match extension(path).as_deref() {
+            Some("ron") => ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?,
+            Some("json") => serde_json::to_string_pretty(self)?,
+            _ => return Err(format!("Formato de escena no soportado: {}", path.display()).into()),
+        };
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    pub fn material(&self, name: &str) -> Option<&SceneMaterial> {
+        self.materials.iter().find(|material| material.name == name)
+    }
+
+    // Sampler de un material: el suyo o el de la escena
+    pub fn material_sampler(&self, material: &SceneMaterial) -> SamplerSettings {
+        material.sampler.unwrap_or(self.sampler)
+    }
+
+    pub fn ambient(&self) -> Option<f32> {
+        self.lights.iter().find_map(|light| match light {
+            Light::Ambient { intensity } => Some(*intensity),
+            _ => None,
+        })
+    }
+
+    // (dirección, color, intensidad) de la primera luz direccional
+    pub fn directional(&self) -> Option<([f32; 3], [f32; 3], f32)> {
+        self.lights.iter().find_map(|light| match light {
+            Light::Directional { direction, color, intensity } => Some((*direction, *color, *intensity)),
+            _ => None,
+        })
+    }
+
+    // Reemplaza la primera luz de cada tipo, que es la que edita el visor, y deja las
+    // demás como estaban. Si no hay una de ese tipo se agrega al final.
+    pub fn set_lights(&mut self, ambient: f32, directional: ([f32; 3], [f32; 3], f32)) {
+        let (direction, color, intensity) = directional;
+        let edited = [
+            Light::Ambient { intensity: ambient },
+            Light::Directional { direction, color, intensity },
+        ];
+        for light in edited {
+            let same_kind = |other: &Light| std::mem::discriminant(other) == std::mem::discriminant(&light);
+            match self.lights.iter_mut().find(|other| same_kind(other)) {
+                Some(other) => *other = light,
+                None => self.lights.push(light),
+            }
+        }
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_scene() -> SceneFile {
+        SceneFile {
+            camera: SceneCamera { orthographic: true, ..SceneCamera::default() },
+            background: [0.2, 0.3, 0.4],
+            rotation_speed: 0.5,
+            lights: vec![
+                Light::Ambient { intensity: 0.2 },
+                Light::Directional { direction: [0.0, -1.0, 0.0], color: [1.0, 0.9, 0.8], intensity: 0.7 },
+                Light::Directional { direction: [1.0, 0.0, 0.0], color: [0.2, 0.2, 1.0], intensity: 0.3 },
+            ],
+            sampler: SamplerSettings::default(),
+            materials: vec![SceneMaterial {
+                name: "piedra".to_string(),
+                texture: "assets/piedra.png".into(),
+                sampler: Some(SamplerSettings { anisotropy_clamp: 4, ..SamplerSettings::default() }),
+                srgb: false,
+            }],
+            objects: vec![
+                SceneObject {
+                    mesh: MeshSource::Primitive("torus".to_string()),
+                    transform: SceneTransform { translation: [1.0, 2.0, 3.0], ..SceneTransform::default() },
+                    material: Some("piedra".to_string()),
+                },
+                SceneObject {
+                    mesh: MeshSource::File("assets/models/barra.gltf".into()),
+                    transform: SceneTransform::default(),
+                    material: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trips_through_ron_and_json() {
+        let dir = std::env::temp_dir().join(format!("cubo-scene-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let scene = sample_scene();
+        for name in ["escena.ron", "escena.json"] {
+            let path = dir.join(name);
+            scene.save(&path).unwrap();
+            assert_eq!(SceneFile::load(&path).unwrap(), scene, "{}", name);
+        }
+        assert!(scene.save(&dir.join("escena.txt")).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn demo_scene_parses() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/scenes/demo.ron");
+        let scene = SceneFile::load(&path).unwrap();
+        assert_eq!(scene.objects.len(), 5);
+        assert_eq!(scene.materials.len(), 2);
+        assert_eq!(scene.ambient(), Some(0.3));
+        let frente = scene.material("frente").unwrap();
+        assert_eq!(scene.material_sampler(frente).mag_filter, crate::sampler::Filter::Nearest);
+        assert_eq!(scene.objects[4].mesh, MeshSource::File("assets/models/barra.gltf".into()));
+    }
+
+    #[test]
+    fn set_lights_keeps_the_extra_lights() {
+        let mut scene = sample_scene();
+        scene.set_lights(0.5, ([0.0, 0.0, -1.0], [1.0; 3], 1.0));
+        assert_eq!(scene.lights.len(), 3);
+        assert_eq!(scene.ambient(), Some(0.5));
+        assert_eq!(scene.directional(), Some(([0.0, 0.0, -1.0], [1.0; 3], 1.0)));
+        assert_eq!(scene.lights[2], sample_scene().lights[2]);
+
+        scene.lights.clear();
+        scene.set_lights(0.1, ([0.0, -1.0, 0.0], [1.0; 3], 0.5));
+        assert_eq!(scene.lights.len(), 2);
+    }
+}
@@ -1,6 +1,15 @@
 // Vertex shader
 struct Uniforms {
     view_proj: mat4x4<f32>,
+    light_direction: vec3<f32>,
+    ambient: f32,
+    light_color: vec3<f32>,
+    light_intensity: f32,
+    debug_view: u32,
+    znear: f32,
+    zfar: f32,
+    texture_layers: u32,
+    orthographic: u32,
 }
 
 @group(1) @binding(0)
@@ -11,18 +20,186 @@ struct VertexInput {
     @location(1) tex_coords: vec2<f32>,
 }
 
+struct InstanceInput {
+    @location(2) model_0: vec4<f32>,
+    @location(3) model_1: vec4<f32>,
+    @location(4) model_2: vec4<f32>,
+    @location(5) model_3: vec4<f32>,
+    // Capa de la textura array: x + face_id * y
+    @location(10) layer: vec2<u32>,
+    // Vértices por cara de la malla; 0 = sin caras numeradas
+    @location(11) face_vertices: u32,
+}
+
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
+    @location(1) world_position: vec3<f32>,
+    @location(2) barycentric: vec3<f32>,
+    @location(3) @interpolate(flat) face_id: u32,
+    // Normal del vértice; cero = usar la normal plana de la cara
+    @location(4) normal: vec3<f32>,
+    // Posición antes de la matriz del modelo, para muestrear el cubemap
+    @location(5) object_position: vec3<f32>,
+    @location(6) @interpolate(flat) layer: u32,
+}
+
+fn model_matrix(instance: InstanceInput) -> mat4x4<f32> {
+    return mat4x4<f32>(
+        instance.model_0,
+        instance.model_1,
+        instance.model_2,
+        instance.model_3,
+    );
+}
+
+fn transform(position: vec3<f32>, instance: InstanceInput) -> vec4<f32> {
+    return model_matrix(instance) * vec4<f32>(position, 1.0);
+}
+
+// face_id de las mallas sin caras numeradas (todo lo que no es un cubo)
+const NO_FACE: u32 = 0xffffffffu;
+
+fn face_index(vertex_index: u32, vertices_per_face: u32) -> u32 {
+    if (vertices_per_face == 0u) {
+        return NO_FACE;
+    }
+    return vertex_index / vertices_per_face;
+}
+
+// Cada instancia elige una capa base y cuánto avanza en cada cara
+fn texture_layer(instance: InstanceInput, face_id: u32) -> u32 {
+    if (face_id == NO_FACE) {
+        return instance.layer.x;
+    }
+    return instance.layer.x + face_id * instance.layer.y;
 }
 
 @vertex
 fn vs_main(
     model: VertexInput,
+    instance: InstanceInput,
+    @builtin(vertex_index) vertex_index: u32,
+) -> VertexOutput {
+    let world_position = transform(model.position, instance);
+    var out: VertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.world_position = world_position.xyz;
+    out.clip_position = uniforms.view_proj * world_position;
+    // Con PolygonMode::Line todo lo que se rasteriza es borde
+    out.barycentric = vec3<f32>(0.0);
+    out.normal = vec3<f32>(0.0);
+    out.object_position = model.position;
+    out.face_id = face_index(vertex_index, instance.face_vertices);
+    out.layer = texture_layer(instance, out.face_id);
+    return out;
+}
+
+// Wireframe sin PolygonMode::Line: vértices desindexados con coordenadas baricéntricas
+@vertex
+fn vs_wire(
+    model: VertexInput,
+    @location(6) barycentric: vec3<f32>,
+    instance: InstanceInput,
+    @builtin(vertex_index) vertex_index: u32,
+) -> VertexOutput {
+    let world_position = transform(model.position, instance);
+    var out: VertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.world_position = world_position.xyz;
+    out.clip_position = uniforms.view_proj * world_position;
+    out.barycentric = barycentric;
+    out.normal = vec3<f32>(0.0);
+    out.object_position = model.position;
+    // Desindexada: una cara de n vértices son (n - 2) * 3
+    let wire_vertices = select(0u, (instance.face_vertices - 2u) * 3u, instance.face_vertices >= 3u);
+    out.face_id = face_index(vertex_index, wire_vertices);
+    out.layer = texture_layer(instance, out.face_id);
+    return out;
+}
+
+// Skinning: una matriz por articulación, actualizada cada frame
+@group(2) @binding(0)
+var<storage, read> joint_matrices: array<mat4x4<f32>>;
+
+struct SkinnedInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(7) joints: vec4<u32>,
+    @location(8) weights: vec4<f32>,
+}
+
+@vertex
+fn vs_skinned(
+    model: SkinnedInput,
+    instance: InstanceInput,
+    @builtin(vertex_index) vertex_index: u32,
 ) -> VertexOutput {
+    let skin = joint_matrices[model.joints.x] * model.weights.x
+        + joint_matrices[model.joints.y] * model.weights.y
+        + joint_matrices[model.joints.z] * model.weights.z
+        + joint_matrices[model.joints.w] * model.weights.w;
+    let position = (skin * vec4<f32>(model.position, 1.0)).xyz;
+    let world_position = transform(position, instance);
     var out: VertexOutput;
     out.tex_coords = model.tex_coords;
-    out.clip_position = uniforms.view_proj * vec4<f32>(model.position, 1.0);
+    out.world_position = world_position.xyz;
+    out.clip_position = uniforms.view_proj * world_position;
+    out.barycentric = vec3<f32>(0.0);
+    out.normal = vec3<f32>(0.0);
+    out.object_position = position;
+    // Mallas importadas: no hay caras numeradas
+    out.face_id = NO_FACE;
+    out.layer = instance.layer.x;
+    return out;
+}
+
+// Morph targets: deltas ordenados por target (target * vertex_count + vértice).
+// Comparten el grupo 2 con el skinning, que usa el binding 0.
+struct MorphInfo {
+    weights: array<vec4<f32>, 4>,
+    target_count: u32,
+    vertex_count: u32,
+}
+
+@group(2) @binding(1)
+var<storage, read> morph_positions: array<vec4<f32>>;
+@group(2) @binding(2)
+var<storage, read> morph_normals: array<vec4<f32>>;
+@group(2) @binding(3)
+var<uniform> morph: MorphInfo;
+
+struct MorphInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(9) normal: vec3<f32>,
+}
+
+@vertex
+fn vs_morph(
+    model: MorphInput,
+    instance: InstanceInput,
+    @builtin(vertex_index) vertex_index: u32,
+) -> VertexOutput {
+    var position = model.position;
+    var normal = model.normal;
+    for (var i = 0u; i < morph.target_count; i++) {
+        let weight = morph.weights[i / 4u][i % 4u];
+        let delta = i * morph.vertex_count + vertex_index;
+        position += weight * morph_positions[delta].xyz;
+        normal += weight * morph_normals[delta].xyz;
+    }
+
+    let world_position = transform(position, instance);
+    var out: VertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.world_position = world_position.xyz;
+    out.clip_position = uniforms.view_proj * world_position;
+    out.barycentric = vec3<f32>(0.0);
+    out.face_id = face_index(vertex_index, instance.face_vertices);
+    out.normal = (model_matrix(instance) * vec4<f32>(normal, 0.0)).xyz;
+    out.object_position = position;
+    out.layer = texture_layer(instance, out.face_id);
     return out;
 }
 
@@ -31,8 +208,111 @@ fn vs_main(
 var t_diffuse: texture_2d<f32>;
 @group(0) @binding(1)
 var s_diffuse: sampler;
+// Alternativa a t_diffuse para fs_cubemap, con un bind group layout de vista Cube
+@group(0) @binding(0)
+var t_cube: texture_cube<f32>;
+// Alternativa para fs_array, con un bind group layout de vista D2Array
+@group(0) @binding(0)
+var t_array: texture_2d_array<f32>;
+
+const VIEW_WIREFRAME: u32 = 1u;
+const VIEW_UV_CHECKER: u32 = 2u;
+const VIEW_UV_COLOR: u32 = 3u;
+const VIEW_NORMALS: u32 = 4u;
+const VIEW_DEPTH: u32 = 5u;
+const VIEW_FACE_ID: u32 = 6u;
+
+fn face_color(id: u32) -> vec3<f32> {
+    var palette = array<vec3<f32>, 8>(
+        vec3<f32>(1.0, 0.4, 0.4),
+        vec3<f32>(0.4, 1.0, 0.4),
+        vec3<f32>(0.4, 0.4, 1.0),
+        vec3<f32>(1.0, 1.0, 0.4),
+        vec3<f32>(1.0, 0.4, 1.0),
+        vec3<f32>(0.4, 1.0, 1.0),
+        vec3<f32>(1.0, 0.7, 0.3),
+        vec3<f32>(0.7, 0.7, 0.7),
+    );
+    return palette[id % 8u];
+}
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
-}
\ No newline at end of file
+    return shade(in, textureSample(t_diffuse, s_diffuse, in.tex_coords));
+}
+
+// Cubemap muestreado con la dirección desde el centro del objeto
+@fragment
+fn fs_cubemap(in: VertexOutput) -> @location(0) vec4<f32> {
+    return shade(in, textureSample(t_cube, s_diffuse, in.object_position));
+}
+
+// Textura array con la capa elegida por la instancia, repitiéndose si se pasa
+@fragment
+fn fs_array(in: VertexOutput) -> @location(0) vec4<f32> {
+    let layer = in.layer % max(uniforms.texture_layers, 1u);
+    return shade(in, textureSample(t_array, s_diffuse, in.tex_coords, layer));
+}
+
+// Iluminación y vistas de depuración, comunes a los dos fragment shaders
+fn shade(in: VertexOutput, color: vec4<f32>) -> vec4<f32> {
+    // Normal plana de la cara a partir de las derivadas de la posición,
+    // salvo que la malla traiga normales por vértice
+    let flat_normal = normalize(cross(dpdy(in.world_position), dpdx(in.world_position)));
+    let has_normal = dot(in.normal, in.normal) > 0.0;
+    let normal = select(flat_normal, normalize(in.normal), has_normal);
+
+    switch uniforms.debug_view {
+        case VIEW_WIREFRAME: {
+            let width = fwidth(in.barycentric);
+            let edge = step(in.barycentric, width * 1.5);
+            if (max(edge.x, max(edge.y, edge.z)) == 0.0) {
+                discard;
+            }
+            return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+        }
+        case VIEW_UV_CHECKER: {
+            // Cuadros de 8x8 teñidos con u (rojo) y v (verde) para ver la orientación
+            let cell = floor(in.tex_coords * 8.0);
+            let checker = (i32(cell.x) + i32(cell.y)) & 1;
+            let base = select(0.25, 0.9, checker == 1);
+            return vec4<f32>(base * vec3<f32>(0.5 + 0.5 * in.tex_coords.x, 0.5 + 0.5 * in.tex_coords.y, 0.6), 1.0);
+        }
+        case VIEW_UV_COLOR: {
+            return vec4<f32>(fract(in.tex_coords), 0.0, 1.0);
+        }
+        case VIEW_NORMALS: {
+            return vec4<f32>(normal * 0.5 + 0.5, 1.0);
+        }
+        case VIEW_DEPTH: {
+            // Las dos proyecciones dejan la profundidad de 0 a 1; la ortográfica es
+            // lineal y la perspectiva no
+            let n = uniforms.znear;
+            let f = uniforms.zfar;
+            let z = in.clip_position.z;
+            var linear = n * f / (f - z * (f - n));
+            if (uniforms.orthographic != 0u) {
+                linear = n + z * (f - n);
+            }
+            let d = clamp(linear / f, 0.0, 1.0);
+            return vec4<f32>(vec3<f32>(1.0 - d), 1.0);
+        }
+        case VIEW_FACE_ID: {
+            // Gris neutro en las mallas sin caras numeradas
+            if (in.face_id == NO_FACE) {
+                return vec4<f32>(0.35, 0.35, 0.35, 1.0);
+            }
+            return vec4<f32>(face_color(in.face_id), 1.0);
+        }
+        default: {
+            // Una dirección nula apaga la luz direccional; normalize daría NaN
+            let to_light = -uniforms.light_direction;
+            var diffuse = 0.0;
+            if (dot(to_light, to_light) > 1e-8) {
+                diffuse = max(dot(normal, normalize(to_light)), 0.0);
+            }
+            let light = uniforms.ambient + diffuse * uniforms.light_intensity * uniforms.light_color;
+            return vec4<f32>(color.rgb * light, color.a);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use crate::animation::{clips_from_gltf, AnimationClip, AnimationPlayer, PlaybackMode, Transform};
+use cgmath::{Matrix4, Quaternion, SquareMatrix, Vector3};
+use std::path::Path;
+use wgpu::util::DeviceExt;
+
+// Vértice con hasta 4 articulaciones. Las ubicaciones 7 y 8 siguen a las de
+// instancia (2-5) y a la baricéntrica (6) del shader de la escena.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkinnedVertex {
+    pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
+    pub joints: [u16; 4],
+    pub weights: [f32; 4],
+}
+
+impl SkinnedVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x2,
+        7 => Uint16x4,
+        8 => Float32x4,
+    ];
+
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SkinnedVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+pub struct SkeletonNode {
+    pub parent: Option<usize>,
+    pub rest: Transform,
+}
+
+// Jerarquía de nodos del glTF; las animaciones usan el índice del nodo como en
+// AnimationTarget::Object
+pub struct Skeleton {
+    pub nodes: Vec<SkeletonNode>,
+    // Nodo de cada articulación, en el orden de los índices de los vértices
+    pub joints: Vec<usize>,
+    pub inverse_bind: Vec<Matrix4<f32>>,
+}
+
+impl Skeleton {
+    pub fn rest_pose(&self) -> Vec<Transform> {
+        self.nodes.iter().map(|n| n.rest).collect()
+    }
+
+    // Matrices que recibe el shader: global de la articulación por su inverse bind
+    pub fn joint_matrices(&self, pose: &[Transform]) -> Vec<[[f32; 4]; 4]> {
+        let mut globals = vec![None; self.nodes.len()];
+        self.joints
+            .iter()
+            .zip(&self.inverse_bind)
+            .map(|(&node, inverse_bind)| (self.global(pose, node, &mut globals) * inverse_bind).into())
+            .collect()
+    }
+
+    fn global(&self, pose: &[Transform], node: usize, globals: &mut [Option<Matrix4<f32>>]) -> Matrix4<f32> {
+        if let Some(matrix) = globals[node] {
+            return matrix;
+        }
+        let local = pose[node].to_matrix();
+        let matrix = match self.nodes[node].parent {
+            Some(parent) => self.global(pose, parent, globals) * local,
+            None => local,
+        };
+        globals[node] = Some(matrix);
+        matrix
+    }
+}
+
+// Malla con esqueleto y sus clips, leída de un glTF
+pub struct SkinnedModel {
+    pub vertices: Vec<SkinnedVertex>,
+    pub indices: Vec<u32>,
+    pub skeleton: Skeleton,
+    pub clips: Vec<AnimationClip>,
+}
+
+impl SkinnedModel {
+    // Carga la primera malla con skin del archivo (todas sus primitivas)
+    pub fn load_gltf(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let gltf = gltf::Gltf::open(path)?;
+        let document = &gltf.document;
+        let buffers = gltf::import_buffers(document, path.parent(), gltf.blob.clone())?;
+        let buffer_data = |buffer: gltf::Buffer| Some(buffers[buffer.index()].0.as_slice());
+
+        let (mesh, skin) = document
+            .nodes()
+            .find_map(|node| Some((node.mesh()?, node.skin()?)))
+            .ok_or("El archivo no tiene ninguna malla con skin")?;
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(buffer_data);
+            let positions: Vec<[f32; 3]> = reader.read_positions().ok_or("Primitiva sin posiciones")?.collect();
+            let mut tex_coords = reader.read_tex_coords(0).map(|t| t.into_f32());
+            let mut joints = reader
+                .read_joints(0)
+                .ok_or("Primitiva sin JOINTS_0")?
+                .into_u16();
+            let mut weights = reader
+                .read_weights(0)
+                .ok_or("Primitiva sin WEIGHTS_0")?
+                .into_f32();
+
+            let base = vertices.len() as u32;
+            match reader.read_indices() {
+                Some(read) => indices.extend(read.into_u32().map(|i| base + i)),
+                None => indices.extend(base..base + positions.len() as u32),
+            }
+
+            for position in positions {
+                let mut weight = weights.next().unwrap_or([1.0, 0.0, 0.0, 0.0]);
+                // Algunos exportadores no normalizan los pesos
+                let sum: f32 = weight.iter().sum();
+                if sum > 0.0 {
+                    weight = weight.map(|w| w / sum);
+                }
+                vertices.push(SkinnedVertex {
+                    position,
+                    tex_coords: tex_coords.as_mut().and_then(|t| t.next()).unwrap_or([0.0, 0.0]),
+                    joints: joints.next().unwrap_or([0; 4]),
+                    weights: weight,
+                });
+            }
+        }
+
+        let mut nodes: Vec<SkeletonNode> = document
+            .nodes()
+            .map(|node| {
+                let (translation, [x, y, z, w], scale) = node.transform().decomposed();
+                SkeletonNode {
+                    parent: None,
+                    rest: Transform {
+                        translation: Vector3::from(translation),
+                        rotation: Quaternion::new(w, x, y, z),
+                        scale: Vector3::from(scale),
+                    },
+                }
+            })
+            .collect();
+        for node in document.nodes() {
+            for child in node.children() {
+                nodes[child.index()].parent = Some(node.index());
+            }
+        }
+
+        let joints: Vec<usize> = skin.joints().map(|j| j.index()).collect();
+        let inverse_bind = match skin.reader(buffer_data).read_inverse_bind_matrices() {
+            Some(matrices) => matrices.map(Matrix4::from).collect(),
+            None => vec![Matrix4::identity(); joints.len()],
+        };
+
+        Ok(Self {
+            vertices,
+            indices,
+            skeleton: Skeleton {
+                nodes,
+                joints,
+                inverse_bind,
+            },
+            clips: clips_from_gltf(document, &buffers)?,
+        })
+    }
+}
+
+// Reproduce un clip y opcionalmente lo mezcla con otro, a mano o con un crossfade
+pub struct SkeletalAnimator {
+    pub clip: usize,
+    pub player: AnimationPlayer,
+    // Clip mezclado encima con peso `blend` (0 = solo `clip`, 1 = solo `blend_clip`)
+    pub blend_clip: Option<usize>,
+    pub blend_player: AnimationPlayer,
+    pub blend: f32,
+    // Peso por segundo del crossfade en curso
+    fade_rate: Option<f32>,
+}
+
+impl SkeletalAnimator {
+    pub fn new(mode: PlaybackMode) -> Self {
+        Self {
+            clip: 0,
+            player: AnimationPlayer::new(mode),
+            blend_clip: None,
+            blend_player: AnimationPlayer::new(mode),
+            blend: 0.0,
+            fade_rate: None,
+        }
+    }
+
+    // Cambia al clip `clip` mezclándolo durante `duration` segundos (0 = corte directo)
+    pub fn crossfade(&mut self, clip: usize, duration: f32) {
+        let mut player = AnimationPlayer::new(self.player.mode);
+        player.speed = self.player.speed;
+        if duration <= 0.0 {
+            self.clip = clip;
+            self.player = player;
+            self.blend_clip = None;
+            self.blend = 0.0;
+            self.fade_rate = None;
+        } else {
+            self.blend_clip = Some(clip);
+            self.blend_player = player;
+            self.blend = 0.0;
+            self.fade_rate = Some(1.0 / duration);
+        }
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fade_rate.is_some()
+    }
+
+    // Avanza los clips y devuelve las matrices de las articulaciones
+    pub fn update(&mut self, dt: f32, model: &SkinnedModel) -> Vec<[[f32; 4]; 4]> {
+        // El clip mezclado sigue los controles de reproducción del principal
+        self.blend_player.mode = self.player.mode;
+        self.blend_player.speed = self.player.speed;
+        self.blend_player.playing = self.player.playing;
+        self.player.advance(dt);
+        self.blend_player.advance(dt);
+
+        if let Some(rate) = self.fade_rate {
+            self.blend += rate * dt;
+            if self.blend >= 1.0 {
+                if let Some(clip) = self.blend_clip.take() {
+                    self.clip = clip;
+                    self.player = self.blend_player;
+                }
+                self.blend = 0.0;
+                self.fade_rate = None;
+            }
+        }
+
+        let mut pose = self.sample(model, self.clip, &self.player);
+        if let Some(blend_clip) = self.blend_clip {
+            if self.blend > 0.0 {
+                let other = self.sample(model, blend_clip, &self.blend_player);
+                for (a, b) in pose.iter_mut().zip(&other) {
+                    *a = a.lerp(b, self.blend.min(1.0));
+                }
+            }
+        }
+        model.skeleton.joint_matrices(&pose)
+    }
+
+    fn sample(&self, model: &SkinnedModel, clip: usize, player: &AnimationPlayer) -> Vec<Transform> {
+        let mut pose = model.skeleton.rest_pose();
+        if let Some(clip) = model.clips.get(clip) {
+            clip.apply(player.clip_time(clip.duration()), &mut pose, None);
+        }
+        pose
+    }
+}
+
+// Buffers en GPU de un SkinnedModel. Las matrices de las articulaciones van en
+// un storage buffer que lee el vertex shader (grupo 2).
+pub struct SkinnedMesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub num_indices: u32,
+    joint_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl SkinnedMesh {
+    // Hace falta poder leer storage buffers desde el vertex shader
+    pub fn is_supported(adapter: &wgpu::Adapter) -> bool {
+        adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::VERTEX_STORAGE)
+            && adapter.limits().max_storage_buffers_per_shader_stage > 0
+    }
+
+    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("joint_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        })
+    }
+
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, model: &SkinnedModel) -> Self {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Skinned Vertex Buffer"),
+            contents: bytemuck::cast_slice(&model.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Skinned Index Buffer"),
+            contents: bytemuck::cast_slice(&model.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let rest = model.skeleton.joint_matrices(&model.skeleton.rest_pose());
+        let identity: [[f32; 4]; 4] = Matrix4::identity().into();
+        let joint_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Joint Matrix Buffer"),
+            contents: bytemuck::cast_slice(if rest.is_empty() { std::slice::from_ref(&identity) } else { &rest }),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("joint_bind_group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: joint_buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            num_indices: model.indices.len() as u32,
+            joint_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn update_joints(&self, queue: &wgpu::Queue, matrices: &[[[f32; 4]; 4]]) {
+        queue.write_buffer(&self.joint_buffer, 0, bytemuck::cast_slice(matrices));
+    }
+}
@@ -0,0 +1,445 @@
+use std::collections::HashMap;
+
+use ab_glyph::{Font, FontVec, GlyphId, PxScale, ScaleFont};
+use cgmath::{Matrix4, Point3};
+
+// Caracteres que se rasterizan: ASCII imprimible y Latin-1 (acentos, ñ, °, ...)
+const CHARSET: [std::ops::RangeInclusive<char>; 2] = [' '..='~', '\u{a0}'..='\u{ff}'];
+const FALLBACK: char = '?';
+const ATLAS_WIDTH: u32 = 512;
+// Separación entre glifos para que el filtro lineal no mezcle vecinos
+const PADDING: u32 = 2;
+
+#[derive(Copy, Clone, Debug)]
+struct Glyph {
+    id: GlyphId,
+    // Rectángulo en el atlas, en UV
+    uv_min: [f32; 2],
+    uv_max: [f32; 2],
+    // Tamaño y posición del bitmap respecto del punto de la línea base, en píxeles del atlas
+    size: [f32; 2],
+    offset: [f32; 2],
+    advance: f32,
+}
+
+// Glifos de una fuente rasterizados una vez en una textura R8 (cobertura)
+pub struct GlyphAtlas {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    font: FontVec,
+    pixel_size: f32,
+    ascent: f32,
+    line_height: f32,
+    glyphs: HashMap<char, Glyph>,
+}
+
+impl GlyphAtlas {
+    // Rasteriza el juego de caracteres con `pixel_size` píxeles de alto, empaquetado en estantes
+    pub fn new(font_data: Vec<u8>, pixel_size: f32) -> Result<Self, Box<dyn std::error::Error>> {
+        let font = FontVec::try_from_vec(font_data)?;
+        let scaled = font.as_scaled(PxScale::from(pixel_size));
+
+        struct Bitmap {
+            ch: char,
+            id: GlyphId,
+            width: u32,
+            height: u32,
+            offset: [f32; 2],
+            advance: f32,
+            coverage: Vec<u8>,
+        }
+        let mut bitmaps = Vec::new();
+        for ch in CHARSET.into_iter().flatten() {
+            let id = font.glyph_id(ch);
+            if id.0 == 0 && ch != FALLBACK {
+                continue;
+            }
+            let advance = scaled.h_advance(id);
+            let mut bitmap = Bitmap { ch, id, width: 0, height: 0, offset: [0.0; 2], advance, coverage: Vec::new() };
+            // Los espacios no tienen contorno, solo avance
+            if let Some(outlined) = font.outline_glyph(id.with_scale(pixel_size)) {
+                let bounds = outlined.px_bounds();
+                bitmap.width = bounds.width() as u32;
+                bitmap.height = bounds.height() as u32;
+                bitmap.offset = [bounds.min.x, bounds.min.y];
+                bitmap.coverage = vec![0; (bitmap.width * bitmap.height) as usize];
+                outlined.draw(|x, y, c| {
+                    if x < bitmap.width && y < bitmap.height {
+                        bitmap.coverage[(y * bitmap.width + x) as usize] = (c.clamp(0.0, 1.0) * 255.0) as u8;
+                    }
+                });
+            }
+            bitmaps.push(bitmap);
+        }
+
+        // Estantes de izquierda a derecha; el alto final se redondea a potencia de dos
+        let mut positions = Vec::with_capacity(bitmaps.len());
+        let (mut x, mut y, mut shelf) = (PADDING, PADDING, 0);
+        for bitmap in &bitmaps {
+            if x + bitmap.width + PADDING > ATLAS_WIDTH {
+                x = PADDING;
+                y += shelf + PADDING;
+                shelf = 0;
+            }
+            positions.push((x, y));
+            x += bitmap.width + PADDING;
+            shelf = shelf.max(bitmap.height);
+        }
+        let width = ATLAS_WIDTH;
+        let height = (y + shelf + PADDING).next_power_of_two();
+
+        let mut pixels = vec![0; (width * height) as usize];
+        let mut glyphs = HashMap::with_capacity(bitmaps.len());
+        for (bitmap, (x, y)) in bitmaps.iter().zip(positions) {
+            for row in 0..bitmap.height {
+                let src = (row * bitmap.width) as usize;
+                let dst = ((y + row) * width + x) as usize;
+                pixels[dst..dst + bitmap.width as usize]
+                    .copy_from_slice(&bitmap.coverage[src..src + bitmap.width as usize]);
+            }
+            let uv = |px: u32, size: u32| px as f32 / size as f32;
+            glyphs.insert(
+                bitmap.ch,
+                Glyph {
+                    id: bitmap.id,
+                    uv_min: [uv(x, width), uv(y, height)],
+                    uv_max: [uv(x + bitmap.width, width), uv(y + bitmap.height, height)],
+                    size: [bitmap.width as f32, bitmap.height as f32],
+                    offset: bitmap.offset,
+                    advance: bitmap.advance,
+                },
+            );
+        }
+
+        let ascent = scaled.ascent();
+        let line_height = scaled.height() + scaled.line_gap();
+        Ok(Self { width, height, pixels, font, pixel_size, ascent, line_height, glyphs })
+    }
+
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    fn glyph(&self, ch: char) -> Option<&Glyph> {
+        self.glyphs.get(&ch).or_else(|| self.glyphs.get(&FALLBACK))
+    }
+
+    // Ancho y alto en píxeles de `text` dibujado con `size` píxeles de alto
+    pub fn measure(&self, text: &str, size: f32) -> (f32, f32) {
+        let scale = size / self.pixel_size;
+        let mut lines = 0;
+        let mut width: f32 = 0.0;
+        for line in text.lines() {
+            lines += 1;
+            width = width.max(self.line_width(line) * scale);
+        }
+        (width, lines.max(1) as f32 * self.line_height * scale)
+    }
+
+    fn line_width(&self, line: &str) -> f32 {
+        let scaled = self.font.as_scaled(PxScale::from(self.pixel_size));
+        let mut width = 0.0;
+        let mut previous: Option<GlyphId> = None;
+        for glyph in line.chars().filter_map(|ch| self.glyph(ch)) {
+            if let Some(previous) = previous {
+                width += scaled.kern(previous, glyph.id);
+            }
+            width += glyph.advance;
+            previous = Some(glyph.id);
+        }
+        width
+    }
+
+    // Agrega dos triángulos por glifo con la esquina superior izquierda del texto en `origin`
+    fn layout(&self, text: &str, origin: [f32; 2], size: f32, color: [f32; 4], vertices: &mut Vec<TextVertex>) {
+        let scaled = self.font.as_scaled(PxScale::from(self.pixel_size));
+        let scale = size / self.pixel_size;
+        let mut baseline = origin[1] + self.ascent * scale;
+        for line in text.lines() {
+            let mut pen = origin[0];
+            let mut previous: Option<GlyphId> = None;
+            for glyph in line.chars().filter_map(|ch| self.glyph(ch)) {
+                if let Some(previous) = previous {
+                    pen += scaled.kern(previous, glyph.id) * scale;
+                }
+                if glyph.size[0] > 0.0 {
+                    // Se redondea al píxel para que el texto pequeño no se vea borroso
+                    let x0 = (pen + glyph.offset[0] * scale).round();
+                    let y0 = (baseline + glyph.offset[1] * scale).round();
+                    let (x1, y1) = (x0 + glyph.size[0] * scale, y0 + glyph.size[1] * scale);
+                    let (u0, v0, u1, v1) = (glyph.uv_min[0], glyph.uv_min[1], glyph.uv_max[0], glyph.uv_max[1]);
+                    let corner = |x: f32, y: f32, u: f32, v: f32| TextVertex { position: [x, y], uv: [u, v], color };
+                    vertices.extend_from_slice(&[
+                        corner(x0, y0, u0, v0),
+                        corner(x1, y0, u1, v0),
+                        corner(x0, y1, u0, v1),
+                        corner(x0, y1, u0, v1),
+                        corner(x1, y0, u1, v0),
+                        corner(x1, y1, u1, v1),
+                    ]);
+                }
+                pen += glyph.advance * scale;
+                previous = Some(glyph.id);
+            }
+            baseline += self.line_height * scale;
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct TextVertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+    color: [f32; 4],
+}
+
+struct TextItem {
+    text: String,
+    size: f32,
+    color: [f32; 4],
+}
+
+// Texto en pantalla con un atlas de glifos. Como DebugDraw, se acumula cada frame:
+// `hud` en píxeles desde la esquina superior izquierda y `label` anclado a un punto
+// 3D, que `prepare` proyecta con el view_proj del frame. Todo se dibuja encima de
+// la escena con una sombra de un píxel para que se lea sobre cualquier fondo.
+// `draw` necesita un pass sin depth attachment.
+pub struct TextRenderer {
+    atlas: GlyphAtlas,
+    pipeline: wgpu::RenderPipeline,
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+    hud: Vec<(TextItem, [f32; 2])>,
+    labels: Vec<(TextItem, Point3<f32>)>,
+}
+
+impl TextRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        color_format: wgpu::TextureFormat,
+        atlas: GlyphAtlas,
+    ) -> Self {
+        let size = wgpu::Extent3d {
+            width: atlas.width,
+            height: atlas.height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            texture.as_image_copy(),
+            &atlas.pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(atlas.width),
+                rows_per_image: Some(atlas.height),
+            },
+            size,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Glyph Atlas Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("text_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Text Uniforms"),
+            size: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("text_bind_group"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Text Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("text.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Text Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<TextVertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // Siempre encima, en un pass sin depth buffer
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            atlas,
+            pipeline,
+            uniforms,
+            bind_group,
+            vertex_buffer: create_vertex_buffer(device, 6 * 256),
+            vertex_count: 0,
+            hud: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn atlas(&self) -> &GlyphAtlas {
+        &self.atlas
+    }
+
+    // Texto de `size` píxeles de alto con la esquina superior izquierda en `position`
+    pub fn hud(&mut self, text: impl Into<String>, position: [f32; 2], size: f32, color: [f32; 4]) {
+        self.hud.push((TextItem { text: text.into(), size, color }, position));
+    }
+
+    // Texto centrado justo encima de `point`, en el espacio del view_proj de `prepare`
+    pub fn label(&mut self, text: impl Into<String>, point: Point3<f32>, size: f32, color: [f32; 4]) {
+        self.labels.push((TextItem { text: text.into(), size, color }, point));
+    }
+
+    // Arma los quads del frame y los sube; las etiquetas detrás de la cámara se descartan
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        view_proj: &Matrix4<f32>,
+        screen_size: (u32, u32),
+    ) {
+        let (width, height) = (screen_size.0 as f32, screen_size.1 as f32);
+        let mut items: Vec<(TextItem, [f32; 2])> = Vec::with_capacity(self.labels.len() + self.hud.len());
+        for (item, point) in self.labels.drain(..) {
+            let clip = view_proj * point.to_homogeneous();
+            if clip.w <= 0.0 || clip.z < 0.0 || clip.z > clip.w {
+                continue;
+            }
+            let (x, y) = ((clip.x / clip.w * 0.5 + 0.5) * width, (0.5 - clip.y / clip.w * 0.5) * height);
+            let (text_width, text_height) = self.atlas.measure(&item.text, item.size);
+            items.push((item, [x - text_width * 0.5, y - text_height - 2.0]));
+        }
+        // El HUD al final para que quede encima de las etiquetas
+        items.append(&mut self.hud);
+
+        let mut vertices = Vec::new();
+        for (item, [x, y]) in &items {
+            let shadow = (item.size / 16.0).max(1.0).round();
+            let shadow_color = [0.0, 0.0, 0.0, item.color[3] * 0.75];
+            self.atlas.layout(&item.text, [x + shadow, y + shadow], item.size, shadow_color, &mut vertices);
+            self.atlas.layout(&item.text, [*x, *y], item.size, item.color, &mut vertices);
+        }
+        self.vertex_count = vertices.len() as u32;
+        if vertices.is_empty() {
+            return;
+        }
+
+        let needed = (vertices.len() * std::mem::size_of::<TextVertex>()) as wgpu::BufferAddress;
+        if needed > self.vertex_buffer.size() {
+            self.vertex_buffer = create_vertex_buffer(device, vertices.len().next_power_of_two());
+        }
+        queue.write_buffer(&self.uniforms, 0, bytemuck::cast_slice(&[width, height, 0.0, 0.0]));
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertex_count == 0
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..self.vertex_count, 0..1);
+    }
+}
+
+fn create_vertex_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Text Vertex Buffer"),
+        size: (capacity * std::mem::size_of::<TextVertex>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
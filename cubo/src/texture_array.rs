@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use image::{imageops, RgbaImage};
+
+// Capas de una textura array, todas del mismo tamaño
+pub struct TextureArrayImages {
+    pub width: u32,
+    pub height: u32,
+    pub layers: Vec<RgbaImage>,
+}
+
+impl TextureArrayImages {
+    pub fn new(layers: Vec<RgbaImage>) -> Result<Self, Box<dyn std::error::Error>> {
+        let first = layers.first().ok_or("la textura array no tiene capas")?;
+        let (width, height) = first.dimensions();
+        if width == 0 || height == 0 || layers.iter().any(|layer| layer.dimensions() != (width, height)) {
+            return Err("las capas de la textura array deben tener el mismo tamaño".into());
+        }
+        Ok(Self { width, height, layers })
+    }
+
+    // Una carpeta de imágenes (una capa por archivo) o una tira vertical de capas cuadradas
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if path.is_dir() {
+            Self::from_dir(path)
+        } else {
+            let image = image::open(path)?.to_rgba8();
+            if image.width() == 0 || !image.height().is_multiple_of(image.width()) {
+                return Err(format!(
+                    "una tira de {} x {} no se puede dividir en capas cuadradas",
+                    image.width(),
+                    image.height()
+                )
+                .into());
+            }
+            Self::from_strip(&image, image.height() / image.width())
+        }
+    }
+
+    // Las capas quedan en el orden alfabético de los archivos
+    pub fn from_dir(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| image::ImageFormat::from_path(path).is_ok())
+            .collect();
+        paths.sort();
+        let layers = paths
+            .iter()
+            .map(|path| Ok(image::open(path)?.to_rgba8()))
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+        Self::new(layers)
+    }
+
+    // Divide una imagen en `count` capas apiladas de arriba abajo
+    pub fn from_strip(image: &RgbaImage, count: u32) -> Result<Self, Box<dyn std::error::Error>> {
+        if count == 0 || !image.height().is_multiple_of(count) {
+            return Err(format!("{} px de alto no se dividen en {} capas", image.height(), count).into());
+        }
+        let height = image.height() / count;
+        Self::new(
+            (0..count)
+                .map(|i| imageops::crop_imm(image, 0, i * height, image.width(), height).to_image())
+                .collect(),
+        )
+    }
+}
+
+// Textura D2 con una capa por imagen y una vista TextureViewDimension::D2Array.
+// En el shader cada instancia elige la capa (ver texture_layer en shader.wgsl).
+pub struct TextureArray {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub layers: u32,
+}
+
+impl TextureArray {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        images: &TextureArrayImages,
+        label: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let layers = images.layers.len() as u32;
+        let max_layers = device.limits().max_texture_array_layers;
+        if layers > max_layers {
+            return Err(format!("{} capas, el dispositivo admite hasta {}", layers, max_layers).into());
+        }
+
+        let size = wgpu::Extent3d {
+            width: images.width,
+            height: images.height,
+            depth_or_array_layers: layers,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        for (layer, image) in images.layers.iter().enumerate() {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: 0, y: 0, z: layer as u32 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                image,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * images.width),
+                    rows_per_image: Some(images.height),
+                },
+                wgpu::Extent3d { depth_or_array_layers: 1, ..size },
+            );
+        }
+
+        // Con una sola capa la vista por defecto sería D2, hay que pedir D2Array
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some(label),
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+
+        Ok(Self { texture, view, layers })
+    }
+
+    // Igual que el layout de la textura 2D (textura en 0, sampler en 1) pero con vista D2Array
+    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("texture_array_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+}